@@ -0,0 +1,31 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use shogi::bitboard::Factory;
+use shogi::{Bitboard, Square};
+
+// Run with `--features portable` to measure the fallback used on CPUs without BMI2.
+fn bench_attack(c: &mut Criterion) {
+    Factory::init();
+
+    let mut occupied = Bitboard::empty();
+    for sq in Square::iter().filter(|sq| sq.index() % 3 == 0) {
+        occupied |= sq;
+    }
+
+    c.bench_function("rook_attack", |b| {
+        b.iter(|| {
+            Square::iter()
+                .map(|sq| Factory::rook_attack(sq, black_box(&occupied)).count())
+                .sum::<usize>()
+        })
+    });
+    c.bench_function("bishop_attack", |b| {
+        b.iter(|| {
+            Square::iter()
+                .map(|sq| Factory::bishop_attack(sq, black_box(&occupied)).count())
+                .sum::<usize>()
+        })
+    });
+}
+
+criterion_group!(benches, bench_attack);
+criterion_main!(benches);
@@ -1,6 +1,6 @@
 use criterion::{criterion_group, criterion_main, Criterion};
 use shogi::bitboard::Factory;
-use shogi::{Color, Position, Square};
+use shogi::{Color, Position};
 
 fn bench_perft(c: &mut Criterion) {
     Factory::init();
@@ -13,20 +13,7 @@ fn bench_perft(c: &mut Criterion) {
 }
 
 fn perft(pos: &Position) {
-    let mut cnt = 0;
-
-    for sq in Square::iter() {
-        let pc = pos.piece_at(sq);
-
-        if let Some(ref pc) = *pc {
-            if pc.color == Color::White {
-                let bb = pos.move_candidates(sq, *pc);
-                cnt += bb.count();
-            }
-        }
-    }
-
-    assert_eq!(32, cnt);
+    assert_eq!(32, pos.mobility(Color::White));
 }
 
 criterion_group!(benches, bench_perft);
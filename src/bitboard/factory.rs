@@ -1,5 +1,6 @@
 use super::*;
-use bitintr::*;
+use alloc::vec;
+use alloc::vec::Vec;
 
 macro_rules! BitboardOr {
     ($lhs: expr, $rhs: expr) => {
@@ -11,7 +12,7 @@ macro_rules! BitboardOr {
 
 /// Creates various bitboard instances.
 ///
-/// `init` method needs to be called first for pre-calculation.
+/// Lookup tables are pre-calculated on first use. `init` can be called to build them up front.
 ///
 /// # Examples
 ///
@@ -19,8 +20,6 @@ macro_rules! BitboardOr {
 /// use shogi::bitboard::Factory;
 /// use shogi::square::consts::*;
 ///
-/// // init() shold be called before other method calls.
-/// Factory::init();
 /// let bb = Factory::between(SQ_1A, SQ_9I);
 /// assert_eq!(7, bb.count());
 /// ```
@@ -28,58 +27,35 @@ pub struct Factory {}
 
 impl Factory {
     /// Pre-calculate complex bitboards for faster table lookup.
-    /// This method needs to be called once before other methods in `Factory` get called.
+    ///
+    /// Calling this method is optional since the tables are built lazily on first lookup.
+    /// It can be called more than once, or from multiple threads; the tables are only built once.
     pub fn init() {
-        init_rook_block();
-        init_rook_attack();
-        init_bishop_block();
-        init_bishop_attack();
-        init_king_attack();
-        init_gold_attack();
-        init_silver_attack();
-        init_pawn_attack();
-        init_knight_attack();
-        init_lance_attack();
-        init_between();
+        tables();
     }
 
     /// Returns a bitboard in which squares attacked by the given piece are filled.
     #[inline(always)]
     pub fn attacks_from(pt: PieceType, c: Color, sq: Square) -> Bitboard {
-        unsafe { ATTACK_BB[pt as usize][c as usize][sq.index()] }
+        tables().attack_bb[pt as usize][c as usize][sq.index()]
     }
 
     /// Returns a bitboard in which squares attacked by Rook at the given square are filled.
     #[inline(always)]
     pub fn rook_attack(sq: Square, occupied: &Bitboard) -> Bitboard {
-        unsafe {
-            let mask = &ROOK_BLOCK_MASK[sq.index()];
-            let index = occupied_to_index(&(occupied & mask), mask);
-
-            ROOK_ATTACK_BB[ROOK_ATTACK_INDEX[sq.index()] + index]
-        }
+        tables().rook_attack(sq, occupied)
     }
 
     /// Returns a bitboard in which squares attacked by Bishop at the given square are filled.
     #[inline(always)]
     pub fn bishop_attack(sq: Square, occupied: &Bitboard) -> Bitboard {
-        unsafe {
-            let mask = &BISHOP_BLOCK_MASK[sq.index()];
-            let index = occupied_to_index(&(occupied & mask), mask);
-
-            BISHOP_ATTACK_BB[BISHOP_ATTACK_INDEX[sq.index()] + index]
-        }
+        tables().bishop_attack(sq, occupied)
     }
 
     /// Returns a bitboard in which squares attacked by Lance at the given square are filled.
     #[inline(always)]
     pub fn lance_attack(c: Color, sq: Square, occupied: &Bitboard) -> Bitboard {
-        unsafe {
-            let mask = &FILE_BB[sq.file() as usize] & &!&(&RANK1_BB | &RANK9_BB);
-            let index = occupied_to_index(&(occupied & &mask), &mask);
-
-            LANCE_ATTACK_BB[c as usize][sq.index()][index]
-        }
+        tables().lance_attack(c, sq, occupied)
     }
 
     /// Returns a bitboard in which squares in opposite player's area are filled.
@@ -94,7 +70,7 @@ impl Factory {
     /// Returns a bitboard in which squares between the given two squares are filled.
     #[inline(always)]
     pub fn between(sq1: Square, sq2: Square) -> Bitboard {
-        unsafe { BETWEEN_BB[sq1.index()][sq2.index()] }
+        tables().between_bb[sq1.index()][sq2.index()]
     }
 }
 
@@ -202,9 +178,7 @@ const IN_FRONT_BB: [[Bitboard; 9]; 2] = [
     ],
 ];
 
-static mut ROOK_BLOCK_MASK: [Bitboard; 81] = [Bitboard { p: [0, 0] }; 81];
-static mut ROOK_ATTACK_INDEX: [usize; 81] = [0; 81];
-static mut ROOK_ATTACK_BB: [Bitboard; 495_616] = [Bitboard { p: [0, 0] }; 495_616];
+const ROOK_ATTACK_LEN: usize = 495_616;
 const ROOK_BLOCK_BITS: [usize; 81] = [
     14, 13, 13, 13, 13, 13, 13, 13, 14, 13, 12, 12, 12, 12, 12, 12, 12, 13, 13, 12, 12, 12, 12, 12,
     12, 12, 13, 13, 12, 12, 12, 12, 12, 12, 12, 13, 13, 12, 12, 12, 12, 12, 12, 12, 13, 13, 12, 12,
@@ -212,285 +186,533 @@ const ROOK_BLOCK_BITS: [usize; 81] = [
     14, 13, 13, 13, 13, 13, 13, 13, 14,
 ];
 
-static mut BISHOP_BLOCK_MASK: [Bitboard; 81] = [Bitboard { p: [0, 0] }; 81];
-static mut BISHOP_ATTACK_INDEX: [usize; 81] = [0; 81];
-static mut BISHOP_ATTACK_BB: [Bitboard; 20224] = [Bitboard { p: [0, 0] }; 20224];
+const BISHOP_ATTACK_LEN: usize = 20224;
 const BISHOP_BLOCK_BITS: [usize; 81] = [
     7, 6, 6, 6, 6, 6, 6, 6, 7, 6, 6, 6, 6, 6, 6, 6, 6, 6, 6, 6, 8, 8, 8, 8, 8, 6, 6, 6, 6, 8, 10,
     10, 10, 8, 6, 6, 6, 6, 8, 10, 12, 10, 8, 6, 6, 6, 6, 8, 10, 10, 10, 8, 6, 6, 6, 6, 8, 8, 8, 8,
     8, 6, 6, 6, 6, 6, 6, 6, 6, 6, 6, 6, 7, 6, 6, 6, 6, 6, 6, 6, 7,
 ];
 
-static mut LANCE_ATTACK_BB: [[[Bitboard; 128]; 81]; 2] = [[[Bitboard { p: [0, 0] }; 128]; 81]; 2];
-static mut ATTACK_BB: [[[Bitboard; 81]; 2]; 14] = [[[Bitboard { p: [0, 0] }; 81]; 2]; 14];
+const LANCE_BLOCK_BITS: usize = 7;
+
+/////////////////////////////////////////////////////////////////////////////
+// Lookup tables
+/////////////////////////////////////////////////////////////////////////////
+
+/// Pre-calculated bitboards shared by all `Factory` methods.
+struct Tables {
+    use_pext: bool,
+    rook_byte_table: Vec<ByteTable>,
+    bishop_byte_table: Vec<ByteTable>,
+    lance_byte_table: Vec<ByteTable>,
+    rook_block_mask: [Bitboard; 81],
+    rook_attack_index: [usize; 81],
+    rook_attack_bb: Vec<Bitboard>,
+    bishop_block_mask: [Bitboard; 81],
+    bishop_attack_index: [usize; 81],
+    bishop_attack_bb: Vec<Bitboard>,
+    lance_attack_bb: Vec<Bitboard>,
+    attack_bb: [[[Bitboard; 81]; 2]; PieceType::NUM],
+    between_bb: [[Bitboard; 81]; 81],
+}
 
-static mut BETWEEN_BB: [[Bitboard; 81]; 81] = [[Bitboard { p: [0, 0] }; 81]; 81];
+#[cfg(feature = "std")]
+static TABLES: std::sync::OnceLock<Tables> = std::sync::OnceLock::new();
+#[cfg(not(feature = "std"))]
+static TABLES: once_cell::race::OnceBox<Tables> = once_cell::race::OnceBox::new();
 
 #[inline(always)]
-fn index_to_occupied(index: usize, bits: usize, mask: &Bitboard) -> Bitboard {
-    let mut bb = Bitboard::empty();
-    let mut mask_work = *mask;
-    for i in 0..bits {
-        let sq = mask_work.pop();
-        if index & (1 << i) != 0 {
-            bb |= sq;
-        }
+fn tables() -> &'static Tables {
+    #[cfg(feature = "std")]
+    return TABLES.get_or_init(Tables::new);
+    #[cfg(not(feature = "std"))]
+    return TABLES.get_or_init(|| alloc::boxed::Box::new(Tables::new()));
+}
+
+impl Tables {
+    fn new() -> Tables {
+        let mut t = Tables {
+            use_pext: detect_pext(),
+            rook_byte_table: Vec::new(),
+            bishop_byte_table: Vec::new(),
+            lance_byte_table: Vec::new(),
+            rook_block_mask: [EMPTY_BB; 81],
+            rook_attack_index: [0; 81],
+            rook_attack_bb: vec![EMPTY_BB; ROOK_ATTACK_LEN],
+            bishop_block_mask: [EMPTY_BB; 81],
+            bishop_attack_index: [0; 81],
+            bishop_attack_bb: vec![EMPTY_BB; BISHOP_ATTACK_LEN],
+            lance_attack_bb: vec![EMPTY_BB; (2 * 81) << LANCE_BLOCK_BITS],
+            attack_bb: [[[EMPTY_BB; 81]; 2]; PieceType::NUM],
+            between_bb: [[EMPTY_BB; 81]; 81],
+        };
+
+        t.init_rook_block();
+        t.init_bishop_block();
+        t.init_byte_tables();
+        t.init_rook_attack();
+        t.init_bishop_attack();
+        t.init_king_attack();
+        t.init_gold_attack();
+        t.init_silver_attack();
+        t.init_pawn_attack();
+        t.init_knight_attack();
+        t.init_lance_attack();
+        t.init_between();
+        t
     }
 
-    bb
-}
+    #[inline(always)]
+    fn rook_attack(&self, sq: Square, occupied: &Bitboard) -> Bitboard {
+        let mask = &self.rook_block_mask[sq.index()];
+        let index = self.occupied_to_index(&self.rook_byte_table, sq.index(), occupied, mask);
 
-#[inline(always)]
-fn occupied_to_index(occupied: &Bitboard, mask: &Bitboard) -> usize {
-    occupied.merge().pext(mask.merge()) as usize
-}
+        self.rook_attack_bb[self.rook_attack_index[sq.index()] + index]
+    }
 
-#[inline(always)]
-fn color2index(c: Color) -> usize {
-    c as usize
-}
+    #[inline(always)]
+    fn bishop_attack(&self, sq: Square, occupied: &Bitboard) -> Bitboard {
+        let mask = &self.bishop_block_mask[sq.index()];
+        let index = self.occupied_to_index(&self.bishop_byte_table, sq.index(), occupied, mask);
 
-fn init_rook_block() {
-    for sq in Square::iter() {
-        let file = sq.file();
-        let rank = sq.rank();
+        self.bishop_attack_bb[self.bishop_attack_index[sq.index()] + index]
+    }
 
-        let mut bb = &FILE_BB[file as usize] ^ &RANK_BB[rank as usize];
+    #[inline(always)]
+    fn lance_attack(&self, c: Color, sq: Square, occupied: &Bitboard) -> Bitboard {
+        let mask = lance_block_mask(sq);
+        let index =
+            self.occupied_to_index(&self.lance_byte_table, sq.file() as usize, occupied, &mask);
 
-        if file != 0 {
-            bb &= &!&FILE1_BB;
-        }
-        if file != 8 {
-            bb &= &!&FILE9_BB;
-        }
-        if rank != 0 {
-            bb &= &!&RANK1_BB;
-        }
-        if rank != 8 {
-            bb &= &!&RANK9_BB;
-        }
+        self.lance_attack_bb[lance_index(c, sq) + index]
+    }
 
-        unsafe {
-            ROOK_BLOCK_MASK[sq.index()] = bb;
+    /// Converts the occupancy masked by `mask` into a table index.
+    ///
+    /// PEXT is used when the CPU supports it, otherwise `byte_table[i]` built for the same mask.
+    #[inline(always)]
+    fn occupied_to_index(
+        &self,
+        byte_table: &[ByteTable],
+        i: usize,
+        occupied: &Bitboard,
+        mask: &Bitboard,
+    ) -> usize {
+        let bits = (occupied & mask).merge();
+        if self.use_pext {
+            pext(bits, mask.merge()) as usize
+        } else {
+            byte_table[i].extract(bits)
         }
     }
-}
 
-fn init_bishop_block() {
-    for bishop_sq in Square::iter() {
-        let bf = bishop_sq.file() as i8;
-        let br = bishop_sq.rank() as i8;
+    fn init_byte_tables(&mut self) {
+        if self.use_pext {
+            return;
+        }
+
+        self.rook_byte_table = self
+            .rook_block_mask
+            .iter()
+            .map(|mask| ByteTable::new(mask.merge()))
+            .collect();
+        self.bishop_byte_table = self
+            .bishop_block_mask
+            .iter()
+            .map(|mask| ByteTable::new(mask.merge()))
+            .collect();
+        self.lance_byte_table = (0..9)
+            .map(|file| ByteTable::new(lance_block_mask(Square::new(file, 0).unwrap()).merge()))
+            .collect();
+    }
 
-        let mut bb = Bitboard::empty();
+    fn init_rook_block(&mut self) {
         for sq in Square::iter() {
-            let file = sq.file() as i8;
-            let rank = sq.rank() as i8;
+            let file = sq.file();
+            let rank = sq.rank();
 
-            if (file - bf).abs() == (rank - br).abs() {
-                bb |= sq;
+            let mut bb = &FILE_BB[file as usize] ^ &RANK_BB[rank as usize];
+
+            if file != 0 {
+                bb &= &!&FILE1_BB;
+            }
+            if file != 8 {
+                bb &= &!&FILE9_BB;
+            }
+            if rank != 0 {
+                bb &= &!&RANK1_BB;
+            }
+            if rank != 8 {
+                bb &= &!&RANK9_BB;
             }
-        }
-        bb &= &!&(&(&(&FILE1_BB | &FILE9_BB) | &RANK1_BB) | &RANK9_BB);
-        bb &= &!&SQUARE_BB[bishop_sq.index()];
 
-        unsafe {
-            BISHOP_BLOCK_MASK[bishop_sq.index()] = bb;
+            self.rook_block_mask[sq.index()] = bb;
         }
     }
-}
 
-fn calc_beam_attack(piece_sq: Square, dirs: &[(i8, i8)], occupied: &Bitboard) -> Bitboard {
-    let mut bb = Bitboard::empty();
-    for dir in dirs {
-        let mut ptr = piece_sq;
-        while let Some(sq) = ptr.shift(dir.0, dir.1) {
-            bb |= sq;
+    fn init_bishop_block(&mut self) {
+        for bishop_sq in Square::iter() {
+            let bf = bishop_sq.file() as i8;
+            let br = bishop_sq.rank() as i8;
 
-            if (occupied & sq).is_any() {
-                break;
+            let mut bb = Bitboard::empty();
+            for sq in Square::iter() {
+                let file = sq.file() as i8;
+                let rank = sq.rank() as i8;
+
+                if (file - bf).abs() == (rank - br).abs() {
+                    bb |= sq;
+                }
             }
+            bb &= &!&(&(&(&FILE1_BB | &FILE9_BB) | &RANK1_BB) | &RANK9_BB);
+            bb &= &!&SQUARE_BB[bishop_sq.index()];
 
-            ptr = sq;
+            self.bishop_block_mask[bishop_sq.index()] = bb;
         }
     }
 
-    bb
-}
-
-fn init_rook_attack() {
-    const ROOK_DIRS: [(i8, i8); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+    fn init_rook_attack(&mut self) {
+        const ROOK_DIRS: [(i8, i8); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
 
-    let mut index = 0;
-    for sq in Square::iter() {
-        unsafe {
-            ROOK_ATTACK_INDEX[sq.index()] = index;
-            let block_mask = &ROOK_BLOCK_MASK[sq.index()];
+        let mut index = 0;
+        for sq in Square::iter() {
+            self.rook_attack_index[sq.index()] = index;
+            let block_mask = self.rook_block_mask[sq.index()];
 
             let bits = ROOK_BLOCK_BITS[sq.index()];
             for i in 0..(1 << bits) {
-                let occupied = index_to_occupied(i, bits, block_mask);
-                let masked_occupied = &occupied & block_mask;
-
-                ROOK_ATTACK_BB[index + occupied_to_index(&masked_occupied, block_mask)] =
-                    calc_beam_attack(sq, &ROOK_DIRS, &occupied);
+                let occupied = index_to_occupied(i, bits, &block_mask);
+
+                let i = self.occupied_to_index(
+                    &self.rook_byte_table,
+                    sq.index(),
+                    &occupied,
+                    &block_mask,
+                );
+                self.rook_attack_bb[index + i] = calc_beam_attack(sq, &ROOK_DIRS, &occupied);
             }
 
             index += 1 << bits;
         }
     }
-}
 
-fn init_bishop_attack() {
-    const BISHOP_DIRS: [(i8, i8); 4] = [(1, 1), (-1, 1), (1, -1), (-1, -1)];
+    fn init_bishop_attack(&mut self) {
+        const BISHOP_DIRS: [(i8, i8); 4] = [(1, 1), (-1, 1), (1, -1), (-1, -1)];
 
-    let mut index = 0;
-    for sq in Square::iter() {
-        unsafe {
-            BISHOP_ATTACK_INDEX[sq.index()] = index;
-            let block_mask = &BISHOP_BLOCK_MASK[sq.index()];
+        let mut index = 0;
+        for sq in Square::iter() {
+            self.bishop_attack_index[sq.index()] = index;
+            let block_mask = self.bishop_block_mask[sq.index()];
 
             let bits = BISHOP_BLOCK_BITS[sq.index()];
             for i in 0..(1 << bits) {
-                let occupied = index_to_occupied(i, bits, block_mask);
-                let masked_occupied = &occupied & block_mask;
-
-                BISHOP_ATTACK_BB[index + occupied_to_index(&masked_occupied, block_mask)] =
-                    calc_beam_attack(sq, &BISHOP_DIRS, &occupied);
+                let occupied = index_to_occupied(i, bits, &block_mask);
+
+                let i = self.occupied_to_index(
+                    &self.bishop_byte_table,
+                    sq.index(),
+                    &occupied,
+                    &block_mask,
+                );
+                self.bishop_attack_bb[index + i] = calc_beam_attack(sq, &BISHOP_DIRS, &occupied);
             }
 
             index += 1 << bits;
         }
     }
-}
 
-fn init_king_attack() {
-    let index = PieceType::King as usize;
+    fn init_king_attack(&mut self) {
+        let index = PieceType::King as usize;
 
-    for sq in Square::iter() {
-        let bb = &Factory::rook_attack(sq, &FULL_BB) | &Factory::bishop_attack(sq, &FULL_BB);
-        unsafe {
-            ATTACK_BB[index][0][sq.index()] = bb;
-            ATTACK_BB[index][1][sq.index()] = bb;
+        for sq in Square::iter() {
+            let bb = &self.rook_attack(sq, &FULL_BB) | &self.bishop_attack(sq, &FULL_BB);
+            self.attack_bb[index][0][sq.index()] = bb;
+            self.attack_bb[index][1][sq.index()] = bb;
         }
     }
-}
 
-fn init_gold_attack() {
-    let index = PieceType::Gold as usize;
-    let king_index = PieceType::King as usize;
+    fn init_gold_attack(&mut self) {
+        let index = PieceType::Gold as usize;
+        let king_index = PieceType::King as usize;
 
-    for c in Color::iter() {
-        let color_index = color2index(c);
+        for c in Color::iter() {
+            let color_index = color2index(c);
 
-        for sq in Square::iter() {
-            unsafe {
-                let bb = &(&ATTACK_BB[king_index][color_index][sq.index()]
+            for sq in Square::iter() {
+                let bb = &(&self.attack_bb[king_index][color_index][sq.index()]
                     & &IN_FRONT_BB[color_index][sq.rank() as usize])
-                    | &Factory::rook_attack(sq, &FULL_BB);
-                ATTACK_BB[index][color_index][sq.index()] = bb;
+                    | &self.rook_attack(sq, &FULL_BB);
+                self.attack_bb[index][color_index][sq.index()] = bb;
             }
         }
     }
-}
 
-fn init_silver_attack() {
-    let index = PieceType::Silver as usize;
-    let king_index = PieceType::King as usize;
+    fn init_silver_attack(&mut self) {
+        let index = PieceType::Silver as usize;
+        let king_index = PieceType::King as usize;
 
-    for c in Color::iter() {
-        let color_index = color2index(c);
+        for c in Color::iter() {
+            let color_index = color2index(c);
 
-        for sq in Square::iter() {
-            unsafe {
-                let bb = &(&ATTACK_BB[king_index][color_index][sq.index()]
+            for sq in Square::iter() {
+                let bb = &(&self.attack_bb[king_index][color_index][sq.index()]
                     & &IN_FRONT_BB[color_index][sq.rank() as usize])
-                    | &Factory::bishop_attack(sq, &FULL_BB);
-                ATTACK_BB[index][color_index][sq.index()] = bb;
+                    | &self.bishop_attack(sq, &FULL_BB);
+                self.attack_bb[index][color_index][sq.index()] = bb;
             }
         }
     }
-}
 
-fn init_pawn_attack() {
-    let index = PieceType::Pawn as usize;
-    let silver_index = PieceType::Silver as usize;
+    fn init_pawn_attack(&mut self) {
+        let index = PieceType::Pawn as usize;
+        let silver_index = PieceType::Silver as usize;
 
-    for c in Color::iter() {
-        let color_index = color2index(c);
+        for c in Color::iter() {
+            let color_index = color2index(c);
 
-        for sq in Square::iter() {
-            unsafe {
-                ATTACK_BB[index][color_index][sq.index()] = &ATTACK_BB[silver_index][color_index]
-                    [sq.index()]
-                    ^ &Factory::bishop_attack(sq, &FULL_BB);
+            for sq in Square::iter() {
+                self.attack_bb[index][color_index][sq.index()] = &self.attack_bb[silver_index]
+                    [color_index][sq.index()]
+                    ^ &self.bishop_attack(sq, &FULL_BB);
             }
         }
     }
-}
 
-fn init_knight_attack() {
-    let index = PieceType::Knight as usize;
-    let pawn_index = PieceType::Pawn as usize;
+    fn init_knight_attack(&mut self) {
+        let index = PieceType::Knight as usize;
+        let pawn_index = PieceType::Pawn as usize;
 
-    for c in Color::iter() {
-        let color_index = color2index(c);
+        for c in Color::iter() {
+            let color_index = color2index(c);
 
-        for sq in Square::iter() {
-            let mut bb = Bitboard::empty();
-            unsafe {
-                let mut pawn_bb = ATTACK_BB[pawn_index][color_index][sq.index()];
+            for sq in Square::iter() {
+                let mut bb = Bitboard::empty();
+                let mut pawn_bb = self.attack_bb[pawn_index][color_index][sq.index()];
 
                 if pawn_bb.is_any() {
                     let psq = pawn_bb.pop();
-                    bb = &Factory::bishop_attack(psq, &FULL_BB)
+                    bb = &self.bishop_attack(psq, &FULL_BB)
                         & &IN_FRONT_BB[color_index][sq.rank() as usize];
                 }
-                ATTACK_BB[index][color_index][sq.index()] = bb;
+                self.attack_bb[index][color_index][sq.index()] = bb;
             }
         }
     }
-}
 
-fn init_lance_attack() {
-    for c in Color::iter() {
-        let color_index = color2index(c);
+    fn init_lance_attack(&mut self) {
+        for c in Color::iter() {
+            let color_index = color2index(c);
 
-        for sq in Square::iter() {
-            let block_mask = &FILE_BB[sq.file() as usize] & &!&(&RANK1_BB | &RANK9_BB);
-
-            const BITS: usize = 7;
-            for i in 0..1 << BITS {
-                let occupied = index_to_occupied(i, BITS, &block_mask);
-                unsafe {
-                    LANCE_ATTACK_BB[color_index][sq.index()][i] =
-                        &Factory::rook_attack(sq, &occupied)
-                            & &IN_FRONT_BB[color_index][sq.rank() as usize];
+            for sq in Square::iter() {
+                let block_mask = lance_block_mask(sq);
+
+                for i in 0..1 << LANCE_BLOCK_BITS {
+                    let occupied = index_to_occupied(i, LANCE_BLOCK_BITS, &block_mask);
+                    self.lance_attack_bb[lance_index(c, sq) + i] = &self.rook_attack(sq, &occupied)
+                        & &IN_FRONT_BB[color_index][sq.rank() as usize];
                 }
             }
         }
     }
-}
 
-fn init_between() {
-    for from in Square::iter() {
-        for to in Square::iter() {
-            if from == to {
-                continue;
-            }
+    fn init_between(&mut self) {
+        for from in Square::iter() {
+            for to in Square::iter() {
+                if from == to {
+                    continue;
+                }
 
-            let df = from.file() as i8 - to.file() as i8;
-            let dr = from.rank() as i8 - to.rank() as i8;
-            unsafe {
+                let df = from.file() as i8 - to.file() as i8;
+                let dr = from.rank() as i8 - to.rank() as i8;
                 if df == 0 || dr == 0 {
-                    BETWEEN_BB[from.index()][to.index()] =
-                        &Factory::rook_attack(from, &square_bb(to))
-                            & &Factory::rook_attack(to, &square_bb(from));
+                    self.between_bb[from.index()][to.index()] = &self
+                        .rook_attack(from, &square_bb(to))
+                        & &self.rook_attack(to, &square_bb(from));
                 } else if df.abs() == dr.abs() {
-                    BETWEEN_BB[from.index()][to.index()] =
-                        &Factory::bishop_attack(from, &square_bb(to))
-                            & &Factory::bishop_attack(to, &square_bb(from));
+                    self.between_bb[from.index()][to.index()] = &self
+                        .bishop_attack(from, &square_bb(to))
+                        & &self.bishop_attack(to, &square_bb(from));
                 }
             }
         }
     }
 }
+
+#[inline(always)]
+fn index_to_occupied(index: usize, bits: usize, mask: &Bitboard) -> Bitboard {
+    let mut bb = Bitboard::empty();
+    let mut mask_work = *mask;
+    for i in 0..bits {
+        let sq = mask_work.pop();
+        if index & (1 << i) != 0 {
+            bb |= sq;
+        }
+    }
+
+    bb
+}
+
+#[inline(always)]
+fn lance_block_mask(sq: Square) -> Bitboard {
+    &FILE_BB[sq.file() as usize] & &!&(&RANK1_BB | &RANK9_BB)
+}
+
+/// Checks if PEXT instruction can be used for calculating table indices.
+fn detect_pext() -> bool {
+    if cfg!(feature = "portable") {
+        return false;
+    }
+
+    #[cfg(all(target_arch = "x86_64", feature = "std"))]
+    {
+        std::is_x86_feature_detected!("bmi2")
+    }
+    #[cfg(all(target_arch = "x86_64", not(feature = "std")))]
+    {
+        cfg!(target_feature = "bmi2")
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        false
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+#[inline(always)]
+fn pext(a: u64, mask: u64) -> u64 {
+    #[target_feature(enable = "bmi2")]
+    unsafe fn pext_bmi2(a: u64, mask: u64) -> u64 {
+        core::arch::x86_64::_pext_u64(a, mask)
+    }
+
+    // SAFETY: Only called after `detect_pext` has confirmed BMI2 support.
+    unsafe { pext_bmi2(a, mask) }
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+#[inline(always)]
+fn pext(a: u64, mask: u64) -> u64 {
+    soft_pext(a, mask)
+}
+
+/// Gathers the bits of `a` selected by `mask` into the low bits, one bit at a time.
+fn soft_pext(a: u64, mask: u64) -> u64 {
+    let mut res = 0;
+    let mut m = mask;
+    let mut bit = 1;
+    while m != 0 {
+        if a & m & m.wrapping_neg() != 0 {
+            res |= bit;
+        }
+        bit <<= 1;
+        m &= m - 1;
+    }
+
+    res
+}
+
+/// Portable replacement of PEXT for a fixed mask, which looks up each byte of the occupancy.
+struct ByteTable([[u16; 256]; 8]);
+
+impl ByteTable {
+    fn new(mask: u64) -> ByteTable {
+        let mut table = [[0; 256]; 8];
+        let mut offset = 0;
+        for (i, row) in table.iter_mut().enumerate() {
+            let byte_mask = (mask >> (i * 8)) & 0xff;
+            for (byte, v) in row.iter_mut().enumerate() {
+                *v = (soft_pext(byte as u64, byte_mask) << offset) as u16;
+            }
+            offset += byte_mask.count_ones();
+        }
+
+        ByteTable(table)
+    }
+
+    #[inline(always)]
+    fn extract(&self, bits: u64) -> usize {
+        self.0
+            .iter()
+            .enumerate()
+            .map(|(i, row)| row[((bits >> (i * 8)) & 0xff) as usize] as usize)
+            .sum()
+    }
+}
+
+#[inline(always)]
+fn color2index(c: Color) -> usize {
+    c as usize
+}
+
+#[inline(always)]
+fn lance_index(c: Color, sq: Square) -> usize {
+    (color2index(c) * 81 + sq.index()) << LANCE_BLOCK_BITS
+}
+
+fn calc_beam_attack(piece_sq: Square, dirs: &[(i8, i8)], occupied: &Bitboard) -> Bitboard {
+    let mut bb = Bitboard::empty();
+    for dir in dirs {
+        let mut ptr = piece_sq;
+        while let Some(sq) = ptr.shift(dir.0, dir.1) {
+            bb |= sq;
+
+            if (occupied & sq).is_any() {
+                break;
+            }
+
+            ptr = sq;
+        }
+    }
+
+    bb
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::square::consts::*;
+
+    #[test]
+    fn init_from_threads() {
+        let handles = (0..4)
+            .map(|_| std::thread::spawn(Factory::init))
+            .collect::<Vec<_>>();
+        for h in handles {
+            h.join().unwrap();
+        }
+
+        assert_eq!(7, Factory::between(SQ_1A, SQ_9I).count());
+    }
+
+    #[test]
+    fn byte_table() {
+        let cases = [
+            (0x0000_0000_0000_00ff, 0x0000_0000_0000_00a5),
+            (0x0040_2010_0804_0200, 0x0040_0000_0804_0000),
+            (0x0000_7f00_0000_0000, 0x0000_1234_5678_9abc),
+            (0x0040_2010_0804_fe00, 0x0123_4567_89ab_cdef),
+        ];
+
+        for (i, case) in cases.iter().enumerate() {
+            let table = ByteTable::new(case.0);
+            assert_eq!(
+                soft_pext(case.1, case.0) as usize,
+                table.extract(case.1 & case.0),
+                "failed at #{i}"
+            );
+        }
+    }
+
+    #[test]
+    fn lookup_without_init() {
+        assert_eq!(16, Factory::rook_attack(SQ_5E, &Bitboard::empty()).count());
+        assert_eq!(
+            16,
+            Factory::bishop_attack(SQ_5E, &Bitboard::empty()).count()
+        );
+        assert_eq!(
+            8,
+            Factory::attacks_from(PieceType::King, Color::Black, SQ_5E).count()
+        );
+    }
+}
@@ -1,13 +1,14 @@
-use std::fmt;
-use std::iter;
-use std::ops;
+use alloc::string::String;
+use core::fmt;
+use core::iter;
+use core::ops;
 
 use super::{Color, PieceType, Square};
 
 /// Represents a board state in which each square takes two possible values, filled or empty.
 ///
 /// `Bitboard` implements [PEXT Bitboard](https://www.chessprogramming.org/BMI2#PEXTBitboards) which relies on [BMI2 instruction set](https://www.chessprogramming.org/BMI2).
-/// For environments which do not support BMI2, it will use software fallback methods. Thanks to [bitintr](https://github.com/gnzlbg/bitintr) crate.
+/// BMI2 support is detected at runtime, and a portable byte-wise table lookup is used for environments which do not support it.
 ///
 /// # Examples
 ///
@@ -38,6 +39,34 @@ impl Bitboard {
         Bitboard { p: [0, 0] }
     }
 
+    /// Returns an instance of `Bitboard` with the given squares filled.
+    ///
+    /// This is a `const fn`, so it can be used to build static tables as well.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::Bitboard;
+    /// use shogi::square::consts::*;
+    ///
+    /// const CORNERS: Bitboard = Bitboard::from_squares(&[SQ_1A, SQ_9A, SQ_1I, SQ_9I]);
+    ///
+    /// assert_eq!(4, CORNERS.count());
+    /// assert!((&CORNERS & SQ_9I).is_any());
+    /// ```
+    pub const fn from_squares(sqs: &[Square]) -> Self {
+        let mut p = [0, 0];
+        let mut i = 0;
+        while i < sqs.len() {
+            let bb = SQUARE_BB[sqs[i].index()];
+            p[0] |= bb.p[0];
+            p[1] |= bb.p[1];
+            i += 1;
+        }
+
+        Bitboard { p }
+    }
+
     /// Checks if any of its squares is filled.
     #[inline(always)]
     pub fn is_any(&self) -> bool {
@@ -78,6 +107,220 @@ impl Bitboard {
         }
     }
 
+    /// Returns the first filled square without clearing it, or `None` if the bitboard is empty.
+    #[inline(always)]
+    pub fn first(&self) -> Option<Square> {
+        if self.p[0] != 0 {
+            Square::from_index(self.p[0].trailing_zeros() as u8)
+        } else if self.p[1] != 0 {
+            Square::from_index(self.p[1].trailing_zeros() as u8 + 63)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the last filled square without clearing it, or `None` if the bitboard is empty.
+    #[inline(always)]
+    pub fn last(&self) -> Option<Square> {
+        if self.p[1] != 0 {
+            Square::from_index(126 - self.p[1].leading_zeros() as u8)
+        } else if self.p[0] != 0 {
+            Square::from_index(63 - self.p[0].leading_zeros() as u8)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the filled square if exactly one square is filled, otherwise `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::Bitboard;
+    /// use shogi::square::consts::*;
+    ///
+    /// let mut bb = Bitboard::empty();
+    /// bb |= SQ_5E;
+    /// assert_eq!(Some(SQ_5E), bb.single());
+    ///
+    /// bb |= SQ_1A;
+    /// assert_eq!(None, bb.single());
+    /// ```
+    #[inline(always)]
+    pub fn single(&self) -> Option<Square> {
+        if self.count() == 1 {
+            self.first()
+        } else {
+            None
+        }
+    }
+
+    /// Returns an iterator over the filled squares, leaving this bitboard untouched.
+    ///
+    /// `Bitboard` itself implements `Iterator` by popping its squares, which consumes the value.
+    /// This method iterates over a copy instead, so it can be called on a borrowed bitboard.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::Bitboard;
+    /// use shogi::square::consts::*;
+    ///
+    /// let mut bb = Bitboard::empty();
+    /// bb |= SQ_1A;
+    /// bb |= SQ_5E;
+    ///
+    /// let bb_ref = &bb;
+    /// assert_eq!(vec![SQ_1A, SQ_5E], bb_ref.squares().collect::<Vec<_>>());
+    /// assert_eq!(2, bb_ref.count());
+    /// ```
+    #[inline(always)]
+    pub fn squares(&self) -> impl Iterator<Item = Square> {
+        *self
+    }
+
+    /// Returns an iterator over the indices of the filled squares, leaving this bitboard untouched.
+    ///
+    /// Yields the same squares in the same order as [`squares`](#method.squares),
+    /// but as `usize` values equal to `Square::index()` for direct array indexing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::Bitboard;
+    /// use shogi::square::consts::*;
+    ///
+    /// let mut bb = Bitboard::empty();
+    /// bb |= SQ_1A;
+    /// bb |= SQ_9I;
+    ///
+    /// assert_eq!(vec![SQ_1A.index(), SQ_9I.index()], bb.indices().collect::<Vec<_>>());
+    /// ```
+    #[inline(always)]
+    pub fn indices(&self) -> impl Iterator<Item = usize> {
+        let mut p = self.p;
+        iter::from_fn(move || {
+            if p[0] != 0 {
+                let i = p[0].trailing_zeros() as usize;
+                p[0] &= p[0] - 1;
+                Some(i)
+            } else if p[1] != 0 {
+                let i = p[1].trailing_zeros() as usize + 63;
+                p[1] &= p[1] - 1;
+                Some(i)
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Returns a bitboard in which every filled square is moved by the given file and rank delta.
+    ///
+    /// Squares moved out of the board are dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::Bitboard;
+    /// use shogi::square::consts::*;
+    ///
+    /// let mut bb = Bitboard::empty();
+    /// bb |= SQ_1A;
+    /// bb |= SQ_9I;
+    ///
+    /// let mut shifted = bb.shift(1, 1);
+    /// assert_eq!(1, shifted.count());
+    /// assert_eq!(SQ_2B, shifted.pop());
+    /// ```
+    #[must_use]
+    pub fn shift(&self, df: i8, dr: i8) -> Bitboard {
+        if !(-8..=8).contains(&df) || !(-8..=8).contains(&dr) {
+            return Bitboard::empty();
+        }
+
+        // Drop squares leaving the board vertically first, so that they don't wrap into the adjacent file.
+        let mut rank_mask = 0;
+        for rank in 0..9 {
+            if (0..9).contains(&(rank + dr)) {
+                rank_mask |= RANK1_BITS << rank;
+            }
+        }
+
+        let bits = self.to_bits() & rank_mask;
+        let delta = df as i32 * 9 + dr as i32;
+        let shifted = if delta >= 0 {
+            bits << delta
+        } else {
+            bits >> -delta
+        };
+
+        Bitboard::from_bits(shifted)
+    }
+
+    /// Renders the bitboard as a grid seen from the given player's side.
+    ///
+    /// `Color::Black` gives the same output as `Display`, while `Color::White` rotates the board by 180 degrees.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::{Bitboard, Color};
+    /// use shogi::square::consts::*;
+    ///
+    /// let bb = &Bitboard::empty() | SQ_1A;
+    /// let s = bb.to_string_oriented(Color::White);
+    ///
+    /// assert_eq!(bb.to_string(), bb.to_string_oriented(Color::Black));
+    /// assert_eq!("   1   2   3   4   5   6   7   8   9", s.lines().next().unwrap());
+    /// assert_eq!("| X |   |   |   |   |   |   |   |   | a", s.lines().nth(18).unwrap());
+    /// ```
+    pub fn to_string_oriented(&self, viewpoint: Color) -> String {
+        let mut s = String::new();
+        self.render(&mut s, viewpoint)
+            .expect("writing to a String never fails");
+        s
+    }
+
+    fn render<W: fmt::Write>(&self, w: &mut W, viewpoint: Color) -> fmt::Result {
+        let flip = viewpoint == Color::White;
+        let file_at = |i: u8| if flip { i } else { 8 - i };
+        let rank_at = |i: u8| if flip { 8 - i } else { i };
+
+        for i in 0..9 {
+            write!(w, "   {}", file_at(i) + 1)?;
+        }
+        writeln!(w)?;
+        writeln!(w, "+---+---+---+---+---+---+---+---+---+")?;
+
+        for i in 0..9 {
+            let rank = rank_at(i);
+            write!(w, "|")?;
+            for j in 0..9 {
+                let sq = Square::new(file_at(j), rank).unwrap();
+                write!(w, " {} |", if (self & sq).is_empty() { " " } else { "X" })?;
+            }
+            writeln!(w, " {}", (b'a' + rank) as char)?;
+            writeln!(w, "+---+---+---+---+---+---+---+---+---+")?;
+        }
+
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn to_bits(self) -> u128 {
+        self.p[0] as u128 | (self.p[1] as u128) << 63
+    }
+
+    #[inline(always)]
+    fn from_bits(bits: u128) -> Bitboard {
+        Bitboard {
+            p: [
+                bits as u64 & 0x7fff_ffff_ffff_ffff,
+                (bits >> 63) as u64 & 0x0003_ffff,
+            ],
+        }
+    }
+
     #[inline(always)]
     fn merge(&self) -> u64 {
         self.p[0] | self.p[1]
@@ -88,18 +331,19 @@ impl Bitboard {
 // Operator implementations
 /////////////////////////////////////////////////////////////////////////////
 
-impl<'a> ops::Not for &'a Bitboard {
+impl ops::Not for &Bitboard {
     type Output = Bitboard;
 
     #[inline(always)]
     fn not(self) -> Bitboard {
+        // Bits out of the board are kept empty.
         Bitboard {
-            p: [!self.p[0], !self.p[1]],
+            p: [!self.p[0] & 0x7fff_ffff_ffff_ffff, !self.p[1] & 0x0003_ffff],
         }
     }
 }
 
-impl<'a, 'b> ops::BitAnd<&'a Bitboard> for &'b Bitboard {
+impl<'a> ops::BitAnd<&'a Bitboard> for &Bitboard {
     type Output = Bitboard;
 
     #[inline(always)]
@@ -118,7 +362,7 @@ impl<'a> ops::BitAndAssign<&'a Bitboard> for Bitboard {
     }
 }
 
-impl<'a, 'b> ops::BitOr<&'a Bitboard> for &'b Bitboard {
+impl<'a> ops::BitOr<&'a Bitboard> for &Bitboard {
     type Output = Bitboard;
 
     #[inline(always)]
@@ -137,7 +381,7 @@ impl<'a> ops::BitOrAssign<&'a Bitboard> for Bitboard {
     }
 }
 
-impl<'a, 'b> ops::BitXor<&'a Bitboard> for &'b Bitboard {
+impl<'a> ops::BitXor<&'a Bitboard> for &Bitboard {
     type Output = Bitboard;
 
     #[inline(always)]
@@ -156,7 +400,7 @@ impl<'a> ops::BitXorAssign<&'a Bitboard> for Bitboard {
     }
 }
 
-impl<'a> ops::BitAnd<Square> for &'a Bitboard {
+impl ops::BitAnd<Square> for &Bitboard {
     type Output = Bitboard;
 
     #[inline(always)]
@@ -172,7 +416,7 @@ impl ops::BitAndAssign<Square> for Bitboard {
     }
 }
 
-impl<'a> ops::BitOr<Square> for &'a Bitboard {
+impl ops::BitOr<Square> for &Bitboard {
     type Output = Bitboard;
 
     #[inline(always)]
@@ -188,7 +432,7 @@ impl ops::BitOrAssign<Square> for Bitboard {
     }
 }
 
-impl<'a> ops::BitXor<Square> for &'a Bitboard {
+impl ops::BitXor<Square> for &Bitboard {
     type Output = Bitboard;
 
     #[inline(always)]
@@ -210,23 +454,13 @@ impl ops::BitXorAssign<Square> for Bitboard {
 
 impl fmt::Display for Bitboard {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        writeln!(f, "   9   8   7   6   5   4   3   2   1")?;
-        writeln!(f, "+---+---+---+---+---+---+---+---+---+")?;
-
-        for rank in 0..9 {
-            write!(f, "|")?;
-            for file in (0..9).rev() {
-                let sq = Square::new(file, rank).unwrap();
-                write!(f, " {} |", if (self & sq).is_empty() { " " } else { "X" })?;
-            }
-            writeln!(f, " {}", (b'a' + rank) as char)?;
-            writeln!(f, "+---+---+---+---+---+---+---+---+---+")?;
-        }
-
-        Ok(())
+        self.render(f, Color::Black)
     }
 }
 
+/// Yields the filled squares by popping them one by one.
+///
+/// See [`Bitboard::squares`] for iterating without consuming the bitboard.
 impl iter::Iterator for Bitboard {
     type Item = Square;
 
@@ -244,6 +478,9 @@ impl iter::Iterator for Bitboard {
 // Constants
 /////////////////////////////////////////////////////////////////////////////
 
+// Squares on rank 1 as a single 81-bit integer.
+const RANK1_BITS: u128 = 0x0100_8040_2010_0804_0201;
+
 const SQUARE_BB: [Bitboard; 81] = [
     Bitboard { p: [1, 0] },
     Bitboard { p: [1 << 1, 0] },
@@ -339,6 +576,140 @@ pub use self::factory::Factory;
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+    use crate::square::consts::*;
+
     #[test]
     fn it_works() {}
+
+    #[test]
+    fn to_string_oriented() {
+        let bb = Bitboard::from_squares(&[SQ_9A, SQ_2H]);
+
+        let black = bb.to_string_oriented(Color::Black);
+        let black_lines: Vec<&str> = black.lines().collect();
+        assert_eq!(bb.to_string(), black);
+        assert_eq!("| X |   |   |   |   |   |   |   |   | a", black_lines[2]);
+        assert_eq!("|   |   |   |   |   |   |   | X |   | h", black_lines[16]);
+
+        let white = bb.to_string_oriented(Color::White);
+        let white_lines: Vec<&str> = white.lines().collect();
+        assert_eq!(20, white_lines.len());
+        assert_eq!("   1   2   3   4   5   6   7   8   9", white_lines[0]);
+        assert_eq!("|   | X |   |   |   |   |   |   |   | h", white_lines[4]);
+        assert_eq!("|   |   |   |   |   |   |   |   | X | a", white_lines[18]);
+    }
+
+    #[test]
+    fn indices() {
+        let cases: &[&[Square]] = &[
+            &[],
+            &[SQ_1A],
+            &[SQ_9I],
+            &[SQ_8H, SQ_8I, SQ_9A],
+            &[SQ_3C, SQ_5E, SQ_2H],
+        ];
+
+        for (i, case) in cases.iter().enumerate() {
+            let bb = Bitboard::from_squares(case);
+            let expected: Vec<usize> = bb.squares().map(|sq| sq.index()).collect();
+            assert_eq!(expected, bb.indices().collect::<Vec<_>>(), "failed at #{i}");
+            assert_eq!(case.len(), bb.indices().count(), "failed at #{i}");
+        }
+    }
+
+    #[test]
+    fn from_squares() {
+        let cases: &[&[Square]] = &[
+            &[],
+            &[SQ_1A],
+            &[SQ_9I],
+            &[SQ_7I, SQ_8A],
+            &[SQ_3C, SQ_5E, SQ_2H, SQ_3C],
+        ];
+
+        for (i, case) in cases.iter().enumerate() {
+            let mut expected = Bitboard::empty();
+            for &sq in case.iter() {
+                expected |= sq;
+            }
+
+            assert_eq!(
+                expected.squares().collect::<Vec<_>>(),
+                Bitboard::from_squares(case).squares().collect::<Vec<_>>(),
+                "failed at #{i}"
+            );
+        }
+    }
+
+    #[test]
+    fn first_last() {
+        let cases: &[(&[Square], Option<Square>, Option<Square>)] = &[
+            (&[], None, None),
+            (&[SQ_1A], Some(SQ_1A), Some(SQ_1A)),
+            (&[SQ_9I], Some(SQ_9I), Some(SQ_9I)),
+            (&[SQ_1A, SQ_9I], Some(SQ_1A), Some(SQ_9I)),
+            (&[SQ_7I, SQ_8A], Some(SQ_7I), Some(SQ_8A)),
+            (&[SQ_3C, SQ_5E, SQ_2H], Some(SQ_2H), Some(SQ_5E)),
+            (&[SQ_8B, SQ_9C], Some(SQ_8B), Some(SQ_9C)),
+        ];
+
+        for (i, case) in cases.iter().enumerate() {
+            let mut bb = Bitboard::empty();
+            for &sq in case.0 {
+                bb |= sq;
+            }
+
+            assert_eq!(case.1, bb.first(), "failed at #{i}");
+            assert_eq!(case.2, bb.last(), "failed at #{i}");
+            assert_eq!(case.0.len(), bb.count(), "failed at #{i}");
+            assert_eq!(
+                if case.0.len() == 1 { case.1 } else { None },
+                bb.single(),
+                "failed at #{i}"
+            );
+        }
+    }
+
+    #[test]
+    fn shift() {
+        let file1 = [
+            SQ_1A, SQ_1B, SQ_1C, SQ_1D, SQ_1E, SQ_1F, SQ_1G, SQ_1H, SQ_1I,
+        ];
+        let file9 = [
+            SQ_9A, SQ_9B, SQ_9C, SQ_9D, SQ_9E, SQ_9F, SQ_9G, SQ_9H, SQ_9I,
+        ];
+
+        let cases: &[(&[Square], i8, i8, &[Square])] = &[
+            (&[SQ_1A], 0, 0, &[SQ_1A]),
+            (&[SQ_1A], 1, 0, &[SQ_2A]),
+            (&[SQ_1A], 0, 1, &[SQ_1B]),
+            (&[SQ_5E], -2, -3, &[SQ_3B]),
+            (&[SQ_1A], -1, 0, &[]),
+            (&[SQ_9A], 1, 0, &[]),
+            (&[SQ_1I], 0, 1, &[]),
+            (&[SQ_2A], 0, -1, &[]),
+            (&[SQ_7E], 1, 0, &[SQ_8E]),
+            (&[SQ_8E], -1, 0, &[SQ_7E]),
+            (&[SQ_7I], 0, 1, &[]),
+            (&[SQ_8A], 0, -1, &[]),
+            (&[SQ_7I], 1, -1, &[SQ_8H]),
+            (&file1, 8, 0, &file9),
+            (&file9, -8, 0, &file1),
+            (&file1, 9, 0, &[]),
+            (&file1, 0, 8, &[SQ_1I]),
+            (&file1, 0, -8, &[SQ_1A]),
+            (&[SQ_1A, SQ_9I], 0, 9, &[]),
+        ];
+
+        for (i, case) in cases.iter().enumerate() {
+            let mut bb = Bitboard::empty();
+            for &sq in case.0 {
+                bb |= sq;
+            }
+
+            let shifted = bb.shift(case.1, case.2);
+            assert_eq!(case.3, shifted.collect::<Vec<_>>(), "failed at #{i}");
+        }
+    }
 }
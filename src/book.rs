@@ -0,0 +1,217 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+#[cfg(feature = "std")]
+use crate::SfenError;
+use crate::{Move, Position};
+
+/// Represents an opening book which suggests moves for a given position.
+///
+/// Each suggested move comes with a weight; how weights are interpreted (e.g. as frequency or preference) is up to the book.
+pub trait OpeningBook {
+    /// Returns moves registered for the given position with their weights.
+    ///
+    /// An empty list is returned if the position is not in the book.
+    fn probe(&self, pos: &Position) -> Vec<(Move, u32)>;
+}
+
+/// Returns a key identifying the position by its board, side to move and pieces in hand.
+///
+/// The ply field of SFEN is stripped so that transpositions share the same entry.
+pub fn book_key(pos: &Position) -> String {
+    let mut sfen = pos.to_sfen_current();
+    if let Some(i) = sfen.rfind(' ') {
+        sfen.truncate(i);
+    }
+    sfen
+}
+
+/// An in-memory opening book keyed by the position.
+///
+/// Positions are identified by [`book_key`], so the same position reached at different plies shares the same entry.
+///
+/// # Examples
+///
+/// ```
+/// use shogi::{HashMapBook, Move, OpeningBook, Position};
+/// use shogi::bitboard::Factory as BBFactory;
+///
+/// BBFactory::init();
+/// let mut book = HashMapBook::new();
+/// book.insert("lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1", Move::from_sfen("7g7f").unwrap(), 60).unwrap();
+/// book.insert("lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1", Move::from_sfen("2g2f").unwrap(), 40).unwrap();
+///
+/// let moves = book.probe(&Position::initial());
+/// assert_eq!(vec![(Move::from_sfen("7g7f").unwrap(), 60), (Move::from_sfen("2g2f").unwrap(), 40)], moves);
+/// ```
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Default)]
+pub struct HashMapBook {
+    entries: std::collections::HashMap<String, Vec<(Move, u32)>>,
+}
+
+#[cfg(feature = "std")]
+impl HashMapBook {
+    /// Creates a new empty book.
+    pub fn new() -> HashMapBook {
+        Default::default()
+    }
+
+    /// Registers a move for the position given as SFEN formatted string.
+    ///
+    /// Moves following `moves` in the SFEN string are applied before registering,
+    /// and nothing is registered if any of them can not be made.
+    /// If the move is already registered for the position, its weight is replaced.
+    pub fn insert(&mut self, sfen: &str, m: Move, weight: u32) -> Result<(), SfenError> {
+        let mut pos = Position::new();
+        pos.set_sfen_checked(sfen)?;
+        self.insert_position(&pos, m, weight);
+        Ok(())
+    }
+
+    /// Registers a move for the given position.
+    ///
+    /// If the move is already registered for the position, its weight is replaced.
+    pub fn insert_position(&mut self, pos: &Position, m: Move, weight: u32) {
+        let moves = self.entries.entry(book_key(pos)).or_default();
+        match moves.iter_mut().find(|entry| entry.0 == m) {
+            Some(entry) => entry.1 = weight,
+            None => moves.push((m, weight)),
+        }
+    }
+
+    /// Returns the number of positions in the book.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Checks if the book has no positions.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(feature = "std")]
+impl OpeningBook for HashMapBook {
+    fn probe(&self, pos: &Position) -> Vec<(Move, u32)> {
+        self.entries
+            .get(&book_key(pos))
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitboard::Factory as BBFactory;
+
+    fn setup() {
+        BBFactory::init();
+    }
+
+    #[test]
+    fn book_key() {
+        setup();
+
+        let cases = [
+            (
+                "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1",
+                "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b -",
+            ),
+            (
+                "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1 moves 5i5h 5a5b 5h5i 5b5a",
+                "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b -",
+            ),
+            (
+                "7k1/9/7P1/9/9/9/9/9/9 b G2r2b3g4s4n4l17p 20",
+                "7k1/9/7P1/9/9/9/9/9/9 b G2r2b3g4s4n4l17p",
+            ),
+        ];
+
+        for (i, case) in cases.iter().enumerate() {
+            let pos = Position::from_sfen(case.0).expect("failed to parse SFEN string");
+            assert_eq!(case.1, super::book_key(&pos), "failed at #{i}");
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn hash_map_book() {
+        setup();
+
+        let book_data = [
+            (
+                "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1",
+                "7g7f",
+                60,
+            ),
+            (
+                "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1",
+                "2g2f",
+                40,
+            ),
+            (
+                "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1 moves 7g7f",
+                "3c3d",
+                100,
+            ),
+            (
+                "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1",
+                "2g2f",
+                30,
+            ),
+        ];
+
+        let mut book = HashMapBook::new();
+        assert!(book.is_empty());
+        for (i, entry) in book_data.iter().enumerate() {
+            book.insert(entry.0, Move::from_sfen(entry.1).unwrap(), entry.2)
+                .unwrap_or_else(|_| panic!("failed at #{i}"));
+        }
+        assert_eq!(2, book.len());
+
+        let mut pos = Position::initial();
+        assert_eq!(
+            vec![
+                (Move::from_sfen("7g7f").unwrap(), 60),
+                (Move::from_sfen("2g2f").unwrap(), 30)
+            ],
+            book.probe(&pos)
+        );
+
+        pos.play_usi_moves("7g7f").unwrap();
+        assert_eq!(
+            vec![(Move::from_sfen("3c3d").unwrap(), 100)],
+            book.probe(&pos)
+        );
+
+        pos.play_usi_moves("3c3d").unwrap();
+        assert!(book.probe(&pos).is_empty());
+
+        assert!(book
+            .insert("invalid", Move::from_sfen("7g7f").unwrap(), 1)
+            .is_err());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn hash_map_book_illegal_moves() {
+        setup();
+
+        let mut book = HashMapBook::new();
+        assert!(matches!(
+            book.insert(
+                "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1 moves 7g7f 7f7e",
+                Move::from_sfen("3c3d").unwrap(),
+                100
+            ),
+            Err(SfenError::MoveFailed { index: 1, .. })
+        ));
+        assert!(book.is_empty());
+
+        let mut pos = Position::initial();
+        pos.play_usi_moves("7g7f").unwrap();
+        assert!(book.probe(&pos).is_empty());
+    }
+}
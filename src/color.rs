@@ -1,5 +1,8 @@
-use std::fmt;
-use std::iter;
+use core::fmt;
+use core::iter;
+use core::str::FromStr;
+
+use crate::SfenError;
 
 ///  Represents each side of player. Black player moves first.
 ///
@@ -21,10 +24,23 @@ pub enum Color {
 }
 
 impl Color {
+    /// Both colors, ordered by their indices.
+    pub const ALL: [Color; 2] = [Color::Black, Color::White];
+
     /// Returns an iterator of all variants.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::Color;
+    ///
+    /// assert_eq!(2, Color::iter().len());
+    /// assert_eq!(Some(Color::White), Color::iter().next_back());
+    /// ```
     pub fn iter() -> ColorIter {
         ColorIter {
-            current: Some(Color::Black),
+            front: 0,
+            back: Color::ALL.len(),
         }
     }
 
@@ -39,16 +55,43 @@ impl Color {
     /// assert_eq!(Color::Black, Color::White.flip());
     /// ```
     #[must_use]
-    pub fn flip(self) -> Self {
+    pub const fn flip(self) -> Self {
         match self {
             Color::Black => Color::White,
             Color::White => Color::Black,
         }
     }
 
+    /// Returns the rank delta of a step forward, toward the opponent's side.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::Color;
+    /// use shogi::square::consts::*;
+    ///
+    /// assert_eq!(-1, Color::Black.forward());
+    /// assert_eq!(Some(SQ_5D), SQ_5E.shift(0, Color::Black.forward()));
+    /// assert_eq!(Some(SQ_5F), SQ_5E.shift(0, Color::White.forward()));
+    /// ```
+    pub const fn forward(self) -> i8 {
+        match self {
+            Color::Black => -1,
+            Color::White => 1,
+        }
+    }
+
+    /// Returns `1` for Black and `-1` for White, e.g. for evaluation scores from Black's point of view.
+    pub const fn sign(self) -> i32 {
+        match self {
+            Color::Black => 1,
+            Color::White => -1,
+        }
+    }
+
     /// Converts the instance into the unique number for array indexing purpose.
     #[inline(always)]
-    pub fn index(self) -> usize {
+    pub const fn index(self) -> usize {
         self as usize
     }
 }
@@ -62,38 +105,137 @@ impl fmt::Display for Color {
     }
 }
 
+/// Converts the side to move in SFEN notation, `b` or `w`.
+impl TryFrom<char> for Color {
+    type Error = SfenError;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        match c {
+            'b' => Ok(Color::Black),
+            'w' => Ok(Color::White),
+            _ => Err(SfenError::IllegalSideToMove),
+        }
+    }
+}
+
+/// Parses the side to move in SFEN notation, `b` or `w`.
+impl FromStr for Color {
+    type Err = SfenError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "b" => Ok(Color::Black),
+            "w" => Ok(Color::White),
+            _ => Err(SfenError::IllegalSideToMove),
+        }
+    }
+}
+
 /// This struct is created by the [`iter`] method on [`Color`].
 ///
 /// [`iter`]: enum.Color.html#method.iter
 /// [`Color`]: enum.Color.html
 pub struct ColorIter {
-    current: Option<Color>,
+    front: usize,
+    back: usize,
 }
 
 impl iter::Iterator for ColorIter {
     type Item = Color;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let current = self.current;
-
-        if let Some(current) = self.current {
-            self.current = match current {
-                Color::Black => Some(Color::White),
-                Color::White => None,
-            }
+        if self.front < self.back {
+            self.front += 1;
+            Some(Color::ALL[self.front - 1])
+        } else {
+            None
         }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+        (len, Some(len))
+    }
+}
 
-        current
+impl iter::DoubleEndedIterator for ColorIter {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front < self.back {
+            self.back -= 1;
+            Some(Color::ALL[self.back])
+        } else {
+            None
+        }
     }
 }
 
+impl iter::ExactSizeIterator for ColorIter {}
+
+impl iter::FusedIterator for ColorIter {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn forward_sign() {
+        for c in Color::iter() {
+            assert_eq!(c.forward(), -c.flip().forward());
+            assert_eq!(c.sign(), -c.flip().sign());
+        }
+        assert_eq!(1, Color::Black.sign());
+        assert_eq!(1, Color::White.forward());
+    }
+
     #[test]
     fn flip() {
         assert_eq!(Color::White, Color::Black.flip());
         assert_eq!(Color::Black, Color::White.flip());
     }
+
+    #[test]
+    fn try_from() {
+        assert_eq!(Ok(Color::Black), Color::try_from('b'));
+        assert_eq!(Ok(Color::White), Color::try_from('w'));
+
+        for c in ['B', 'W', ' ', 'x'] {
+            assert_eq!(Err(SfenError::IllegalSideToMove), Color::try_from(c));
+        }
+    }
+
+    #[test]
+    fn from_str() {
+        let ok_cases = [("b", Color::Black), ("w", Color::White)];
+        let ng_cases = ["", "B", "W", "black", "bw", " b"];
+
+        for (i, case) in ok_cases.iter().enumerate() {
+            assert_eq!(Ok(case.1), case.0.parse(), "failed at #{i}");
+        }
+
+        for (i, case) in ng_cases.iter().enumerate() {
+            assert_eq!(
+                Err(SfenError::IllegalSideToMove),
+                case.parse::<Color>(),
+                "failed at #{i}"
+            );
+        }
+    }
+
+    #[test]
+    fn iter() {
+        assert_eq!(Color::ALL.to_vec(), Color::iter().collect::<Vec<_>>());
+        assert_eq!(
+            vec![Color::White, Color::Black],
+            Color::iter().rev().collect::<Vec<_>>()
+        );
+
+        let mut it = Color::iter();
+        assert_eq!(2, it.len());
+        assert_eq!(Some(Color::Black), it.next());
+        assert_eq!(1, it.len());
+        assert_eq!(Some(Color::White), it.next_back());
+        assert_eq!(0, it.len());
+        assert_eq!(None, it.next());
+        assert_eq!(None, it.next_back());
+    }
 }
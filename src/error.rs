@@ -1,5 +1,45 @@
 use thiserror::Error;
 
+use crate::{Color, PieceType, Square};
+
+/// The error type which covers all errors returned by this crate.
+///
+/// Each specific error type converts into this one, so `?` can be used across
+/// SFEN parsing, move making and other operations.
+///
+/// # Examples
+///
+/// ```
+/// use shogi::{Error, Move, Position};
+/// use shogi::bitboard::Factory as BBFactory;
+///
+/// fn play(sfen: &str, m: &str) -> Result<Position, Error> {
+///     let mut pos = Position::new();
+///     pos.set_sfen(sfen)?;
+///     pos.make_move(Move::from_sfen(m).unwrap())?;
+///     Ok(pos)
+/// }
+///
+/// BBFactory::init();
+/// assert!(play("lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1", "7g7f").is_ok());
+/// assert!(matches!(play("9/9/9/9/9/9/9/9/9 x - 1", "7g7f"), Err(Error::Sfen(_))));
+/// assert!(matches!(play("lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1", "7c7d"), Err(Error::Move(_))));
+/// ```
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum Error {
+    #[error(transparent)]
+    Sfen(#[from] SfenError),
+
+    #[error(transparent)]
+    Move(#[from] MoveError),
+
+    #[error(transparent)]
+    Position(#[from] PositionError),
+
+    #[error(transparent)]
+    Square(#[from] SquareError),
+}
+
 /// The error type for SFEN serialize/deserialize operations.
 #[derive(Error, Debug, PartialEq, Eq)]
 pub enum SfenError {
@@ -13,13 +53,22 @@ pub enum SfenError {
     IllegalSideToMove,
 
     #[error("an illegal move count notation is found")]
-    IllegalMoveCount(#[from] std::num::ParseIntError),
+    IllegalMoveCount(#[from] core::num::ParseIntError),
 
     #[error("an illegal move notation is found")]
     IllegalMove,
 
     #[error("an illegal board state notation is found")]
     IllegalBoardState,
+
+    #[error("an illegal square notation is found")]
+    IllegalSquare,
+
+    #[error("the position is illegal: {0}")]
+    IllegalPosition(#[from] PositionError),
+
+    #[error("the move at index {index} can not be made: {error}")]
+    MoveFailed { index: usize, error: MoveError },
 }
 
 /// Represents an error occurred during making a move.
@@ -52,3 +101,35 @@ pub enum MoveError {
     #[error("repetition detected")]
     Repetition,
 }
+
+/// Represents an illegal board state found by validating a position.
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum PositionError {
+    #[error("the king of {0} is missing")]
+    MissingKing(Color),
+
+    #[error("{0} has more than one king")]
+    TooManyKings(Color),
+
+    #[error("too many pieces of type {0}")]
+    TooManyPieces(PieceType),
+
+    #[error("{0} has two unpromoted pawns in the same file")]
+    Nifu(Color),
+
+    #[error("the piece at {0} can not move anymore")]
+    NonMovablePiece(Square),
+
+    #[error("the king of the side not to move is in check")]
+    OpponentInCheck,
+}
+
+/// Represents an error occurred during creating a square.
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum SquareError {
+    #[error("the file {0} is out of range")]
+    FileOutOfRange(u8),
+
+    #[error("the rank {0} is out of range")]
+    RankOutOfRange(u8),
+}
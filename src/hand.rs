@@ -16,7 +16,7 @@ use crate::{Color, Piece, PieceType};
 /// assert_eq!(3, hand.get(black_pawn));
 /// assert_eq!(0, hand.get(white_pawn));
 /// ```
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct Hand {
     inner: [u8; 14],
 }
@@ -42,12 +42,46 @@ impl Hand {
     }
 
     /// Decrements a number of the given piece.
-    pub fn decrement(&mut self, p: Piece) {
-        if let Some(i) = Hand::index(p) {
-            self.inner[i] -= 1
+    ///
+    /// The number saturates at zero. Returns `false` if there is no such piece to decrement.
+    pub fn decrement(&mut self, p: Piece) -> bool {
+        match Hand::index(p) {
+            Some(i) if self.inner[i] > 0 => {
+                self.inner[i] -= 1;
+                true
+            }
+            _ => false,
         }
     }
 
+    /// Returns the total number of pieces in the hand of the given color.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::{Color, Hand, Piece, PieceType};
+    ///
+    /// let mut hand: Hand = Default::default();
+    /// hand.set(Piece{piece_type: PieceType::Pawn, color: Color::Black}, 2);
+    /// hand.set(Piece{piece_type: PieceType::Gold, color: Color::Black}, 1);
+    ///
+    /// assert_eq!(3, hand.count(Color::Black));
+    /// assert_eq!(0, hand.count(Color::White));
+    /// ```
+    pub fn count(&self, c: Color) -> u32 {
+        let offset = if c == Color::Black { 0 } else { 7 };
+
+        self.inner[offset..offset + 7]
+            .iter()
+            .map(|&n| n as u32)
+            .sum()
+    }
+
+    /// Checks if neither player has any piece in hand.
+    pub fn is_empty(&self) -> bool {
+        self.inner.iter().all(|&n| n == 0)
+    }
+
     /// Clears all pieces.
     pub fn clear(&mut self) {
         for i in 0..self.inner.len() {
@@ -71,3 +105,67 @@ impl Hand {
         Some(base + offset)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decrement() {
+        let black_pawn = Piece {
+            piece_type: PieceType::Pawn,
+            color: Color::Black,
+        };
+        let white_king = Piece {
+            piece_type: PieceType::King,
+            color: Color::White,
+        };
+
+        let mut hand: Hand = Default::default();
+        hand.set(black_pawn, 1);
+
+        assert!(hand.decrement(black_pawn));
+        assert_eq!(0, hand.get(black_pawn));
+
+        // Decrementing an empty slot leaves it at zero.
+        assert!(!hand.decrement(black_pawn));
+        assert_eq!(0, hand.get(black_pawn));
+
+        assert!(!hand.decrement(white_king));
+        assert_eq!(0, hand.get(white_king));
+    }
+
+    #[test]
+    fn count() {
+        let mut hand: Hand = Default::default();
+        assert!(hand.is_empty());
+        assert_eq!(0, hand.count(Color::Black));
+        assert_eq!(0, hand.count(Color::White));
+
+        for (i, pt) in PieceType::iter()
+            .filter(|pt| pt.is_hand_piece())
+            .enumerate()
+        {
+            hand.set(
+                Piece {
+                    piece_type: pt,
+                    color: Color::White,
+                },
+                i as u8 + 1,
+            );
+        }
+        assert!(!hand.is_empty());
+        assert_eq!(0, hand.count(Color::Black));
+        assert_eq!(28, hand.count(Color::White));
+
+        hand.increment(Piece {
+            piece_type: PieceType::Rook,
+            color: Color::Black,
+        });
+        assert_eq!(1, hand.count(Color::Black));
+        assert_eq!(28, hand.count(Color::White));
+
+        hand.clear();
+        assert!(hand.is_empty());
+    }
+}
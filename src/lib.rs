@@ -28,26 +28,40 @@
 //! // Position can be converted back to the SFEN formatted string.
 //! assert_eq!("lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1 moves 7g7f 7c7d", pos.to_sfen());
 //! ```
+//!
+//! # Features
+//!
+//! - `std` (enabled by default): Links against the standard library. Without it the crate is `no_std` and only depends on `alloc`, and `HashMapBook` is not available.
+//! - `portable`: Always uses the portable table lookup instead of PEXT instruction for sliding piece attacks.
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 #![recursion_limit = "81"]
 
+extern crate alloc;
+
 pub mod bitboard;
+pub mod book;
 pub mod color;
 pub mod error;
 pub mod hand;
 pub mod moves;
 pub mod piece;
 pub mod piece_type;
+pub mod ponder;
 pub mod position;
 pub mod square;
 pub mod time;
 
 pub use self::bitboard::Bitboard;
+#[cfg(feature = "std")]
+pub use self::book::HashMapBook;
+pub use self::book::OpeningBook;
 pub use self::color::Color;
-pub use self::error::{MoveError, SfenError};
+pub use self::error::{Error, MoveError, PositionError, SfenError, SquareError};
 pub use self::hand::Hand;
 pub use self::moves::Move;
 pub use self::piece::Piece;
 pub use self::piece_type::PieceType;
-pub use self::position::{MoveRecord, Position};
+pub use self::ponder::PonderState;
+pub use self::position::{Disambiguation, GameResult, Handicap, MoveRecord, Position};
 pub use self::square::Square;
 pub use self::time::TimeControl;
@@ -1,5 +1,13 @@
-use crate::{PieceType, Square};
-use std::fmt;
+use crate::square::consts::SQ_1A;
+use crate::{Color, Piece, PieceType, Position, SfenError, Square};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt;
+use core::ops::Index;
+use core::slice;
+use core::str::FromStr;
 
 /// Represents a move which either is a normal move or a drop move.
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
@@ -18,7 +26,7 @@ pub enum Move {
 impl Move {
     /// Creates a new instance of `Move` from SFEN formatted string.
     pub fn from_sfen(s: &str) -> Option<Self> {
-        if s.len() != 4 && (s.len() != 5 || s.chars().nth(4).unwrap() != '+') {
+        if !s.is_ascii() || s.len() != 4 && (s.len() != 5 || s.chars().nth(4).unwrap() != '+') {
             return None;
         }
 
@@ -33,8 +41,13 @@ impl Move {
             }
 
             return None;
-        } else if first.is_uppercase() && s.chars().nth(1).unwrap() == '*' {
-            if let Some(piece_type) = first.to_lowercase().next().and_then(PieceType::from_sfen) {
+        } else if first.is_uppercase() && s.len() == 4 && s.chars().nth(1).unwrap() == '*' {
+            if let Some(piece_type) = first
+                .to_lowercase()
+                .next()
+                .and_then(PieceType::from_sfen)
+                .filter(|pt| pt.is_hand_piece())
+            {
                 if let Some(to) = Square::from_sfen(&s[2..4]) {
                     return Some(Move::Drop { to, piece_type });
                 }
@@ -45,6 +58,222 @@ impl Move {
 
         None
     }
+
+    /// Creates a new instance of `Move` from CSA formatted string such as `+7776FU`.
+    ///
+    /// Unlike SFEN, a CSA move is not self-contained: it specifies the piece type after the move instead of the promotion flag.
+    /// The position before the move is used to resolve it, and `None` is returned if the move doesn't match the position,
+    /// e.g. there is no piece of the side to move at the source square or in hand.
+    /// Other rules such as checks are not validated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::{Move, Position};
+    /// use shogi::bitboard::Factory as BBFactory;
+    /// use shogi::square::consts::*;
+    ///
+    /// BBFactory::init();
+    /// let pos = Position::from_sfen("4k4/9/9/9/9/9/2P6/9/4K4 b S 1").unwrap();
+    ///
+    /// assert_eq!(Some(Move::Normal{from: SQ_7G, to: SQ_7F, promote: false}), Move::from_csa("+7776FU", &pos));
+    /// assert_eq!(Some(Move::from_sfen("S*5e").unwrap()), Move::from_csa("+0055GI", &pos));
+    /// assert_eq!(None, Move::from_csa("-7776FU", &pos));
+    /// ```
+    pub fn from_csa(s: &str, pos: &Position) -> Option<Self> {
+        if !s.is_ascii() || s.len() != 7 {
+            return None;
+        }
+
+        let color = match &s[0..1] {
+            "+" => Color::Black,
+            "-" => Color::White,
+            _ => return None,
+        };
+        if color != pos.side_to_move() {
+            return None;
+        }
+        let to = csa_square(&s[3..5])?;
+        let piece_type = csa_piece_type(&s[5..7])?;
+
+        if &s[1..3] == "00" {
+            let pc = Piece { piece_type, color };
+            if !piece_type.is_hand_piece() || pos.hand(pc) == 0 {
+                return None;
+            }
+            return Some(Move::Drop { to, piece_type });
+        }
+
+        let from = csa_square(&s[1..3])?;
+        let pc = (*pos.piece_at(from))?;
+        if pc.color != color {
+            return None;
+        }
+
+        let promote = if pc.piece_type == piece_type {
+            false
+        } else if pc.piece_type.promote() == Some(piece_type) {
+            true
+        } else {
+            return None;
+        };
+
+        Some(Move::Normal { from, to, promote })
+    }
+
+    /// Converts the move into CSA formatted string such as `+7776FU`.
+    ///
+    /// The position before the move is needed to know the moving piece, and `None` is returned if there is no piece to move.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::{Move, Position};
+    /// use shogi::bitboard::Factory as BBFactory;
+    ///
+    /// BBFactory::init();
+    /// let pos = Position::from_sfen("4k4/9/2P6/9/9/9/9/9/4K4 b S 1").unwrap();
+    ///
+    /// assert_eq!(Some("+7372TO".to_string()), Move::from_sfen("7c7b+").unwrap().to_csa(&pos));
+    /// assert_eq!(Some("+0055GI".to_string()), Move::from_sfen("S*5e").unwrap().to_csa(&pos));
+    /// ```
+    pub fn to_csa(&self, pos: &Position) -> Option<String> {
+        let (from, to, color, piece_type) = match *self {
+            Move::Normal { from, to, promote } => {
+                let pc = (*pos.piece_at(from))?;
+                let piece_type = if promote {
+                    pc.piece_type.promote()?
+                } else {
+                    pc.piece_type
+                };
+                (
+                    format!("{}{}", from.file() + 1, from.rank() + 1),
+                    to,
+                    pc.color,
+                    piece_type,
+                )
+            }
+            Move::Drop { to, piece_type } => {
+                (String::from("00"), to, pos.side_to_move(), piece_type)
+            }
+        };
+
+        Some(format!(
+            "{}{}{}{}{}",
+            if color == Color::Black { '+' } else { '-' },
+            from,
+            to.file() + 1,
+            to.rank() + 1,
+            csa_piece_name(piece_type)
+        ))
+    }
+
+    /// Returns the same move with the promotion flag set to the given value.
+    ///
+    /// Drop moves are returned as they are.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::Move;
+    /// use shogi::square::consts::*;
+    ///
+    /// let m = Move::Normal{from: SQ_2C, to: SQ_2B, promote: false};
+    /// assert_eq!(Move::Normal{from: SQ_2C, to: SQ_2B, promote: true}, m.with_promotion(true));
+    /// ```
+    #[must_use]
+    pub fn with_promotion(self, promote: bool) -> Move {
+        match self {
+            Move::Normal { from, to, .. } => Move::Normal { from, to, promote },
+            Move::Drop { .. } => self,
+        }
+    }
+
+    /// Checks if this move is a drop move.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::{Move, PieceType};
+    /// use shogi::square::consts::*;
+    ///
+    /// let m = Move::Drop{to: SQ_5E, piece_type: PieceType::Pawn};
+    /// assert!(m.is_drop());
+    /// assert!(!m.is_promotion());
+    /// assert_eq!(SQ_5E, m.to());
+    /// assert_eq!(None, m.from());
+    /// ```
+    pub fn is_drop(&self) -> bool {
+        matches!(*self, Move::Drop { .. })
+    }
+
+    /// Checks if this move promotes the moving piece.
+    pub fn is_promotion(&self) -> bool {
+        matches!(*self, Move::Normal { promote: true, .. })
+    }
+
+    /// Returns the destination square.
+    pub fn to(&self) -> Square {
+        match *self {
+            Move::Normal { to, .. } | Move::Drop { to, .. } => to,
+        }
+    }
+
+    /// Returns the source square, or `None` for a drop move.
+    pub fn from(&self) -> Option<Square> {
+        match *self {
+            Move::Normal { from, .. } => Some(from),
+            Move::Drop { .. } => None,
+        }
+    }
+
+    fn sort_key(&self) -> (bool, usize, usize, bool) {
+        match *self {
+            Move::Normal { from, to, promote } => (false, to.index(), from.index(), promote),
+            Move::Drop { to, piece_type } => (true, to.index(), piece_type.index(), false),
+        }
+    }
+}
+
+/// Moves are totally ordered for deterministic sorting; the order has no meaning in the game.
+///
+/// Normal moves come before drop moves. Normal moves are ordered by the destination, the source and the promotion flag,
+/// and drop moves are ordered by the destination and the piece type.
+///
+/// # Examples
+///
+/// ```
+/// use shogi::{Move, PieceType};
+/// use shogi::square::consts::*;
+///
+/// let mut moves = vec![
+///     Move::Drop{to: SQ_1A, piece_type: PieceType::Gold},
+///     Move::Normal{from: SQ_2C, to: SQ_2B, promote: true},
+///     Move::Normal{from: SQ_2C, to: SQ_2B, promote: false},
+/// ];
+/// moves.sort();
+///
+/// assert_eq!(Move::Normal{from: SQ_2C, to: SQ_2B, promote: false}, moves[0]);
+/// assert!(moves[2].is_drop());
+/// ```
+impl Ord for Move {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.sort_key().cmp(&other.sort_key())
+    }
+}
+
+impl PartialOrd for Move {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl FromStr for Move {
+    type Err = SfenError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Move::from_sfen(s).ok_or(SfenError::IllegalMove)
+    }
 }
 
 impl fmt::Display for Move {
@@ -60,6 +289,175 @@ impl fmt::Display for Move {
     }
 }
 
+/// Parses a square in CSA format, which consists of file and rank digits from 1 to 9.
+fn csa_square(s: &str) -> Option<Square> {
+    let mut digits = s.bytes().map(|b| b.wrapping_sub(b'1'));
+    Square::new(digits.next()?, digits.next()?)
+}
+
+const CSA_PIECE_NAMES: [(PieceType, &str); PieceType::NUM] = [
+    (PieceType::King, "OU"),
+    (PieceType::Rook, "HI"),
+    (PieceType::Bishop, "KA"),
+    (PieceType::Gold, "KI"),
+    (PieceType::Silver, "GI"),
+    (PieceType::Knight, "KE"),
+    (PieceType::Lance, "KY"),
+    (PieceType::Pawn, "FU"),
+    (PieceType::ProRook, "RY"),
+    (PieceType::ProBishop, "UM"),
+    (PieceType::ProSilver, "NG"),
+    (PieceType::ProKnight, "NK"),
+    (PieceType::ProLance, "NY"),
+    (PieceType::ProPawn, "TO"),
+];
+
+fn csa_piece_type(s: &str) -> Option<PieceType> {
+    CSA_PIECE_NAMES
+        .iter()
+        .find(|&&(_, name)| name == s)
+        .map(|&(pt, _)| pt)
+}
+
+fn csa_piece_name(pt: PieceType) -> &'static str {
+    CSA_PIECE_NAMES
+        .iter()
+        .find(|&&(p, _)| p == pt)
+        .map(|&(_, name)| name)
+        .unwrap()
+}
+
+/// The maximum number of moves a `MoveList` can hold.
+///
+/// The largest known number of legal moves in a reachable position is 593.
+pub const MAX_MOVES: usize = 600;
+
+/// A list of moves stored in a fixed-capacity inline buffer, which does not allocate on the heap.
+///
+/// # Examples
+///
+/// ```
+/// use shogi::{Move, Position};
+/// use shogi::bitboard::Factory as BBFactory;
+/// use shogi::moves::MoveList;
+///
+/// BBFactory::init();
+/// let pos = Position::initial();
+/// let mut list = MoveList::new();
+/// pos.generate_moves(&mut list);
+///
+/// assert_eq!(30, list.len());
+/// assert!(list.iter().any(|m| *m == Move::from_sfen("7g7f").unwrap()));
+/// ```
+#[derive(Clone)]
+pub struct MoveList {
+    moves: [Move; MAX_MOVES],
+    len: usize,
+}
+
+impl MoveList {
+    /// Creates an empty list.
+    pub fn new() -> MoveList {
+        MoveList {
+            moves: [Move::Drop {
+                to: SQ_1A,
+                piece_type: PieceType::Pawn,
+            }; MAX_MOVES],
+            len: 0,
+        }
+    }
+
+    /// Appends a move to the end of the list.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the list already holds `MAX_MOVES` moves.
+    #[inline]
+    pub fn push(&mut self, m: Move) {
+        assert!(self.len < MAX_MOVES, "too many moves in MoveList");
+        self.moves[self.len] = m;
+        self.len += 1;
+    }
+
+    /// Removes all moves from the list.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Returns the number of moves in the list.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Checks if the list contains no moves.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns a slice containing all moves in the list.
+    #[inline]
+    pub fn as_slice(&self) -> &[Move] {
+        &self.moves[..self.len]
+    }
+
+    /// Returns an iterator over the moves in the list.
+    #[inline]
+    pub fn iter(&self) -> slice::Iter<'_, Move> {
+        self.as_slice().iter()
+    }
+}
+
+impl Default for MoveList {
+    fn default() -> Self {
+        MoveList::new()
+    }
+}
+
+impl fmt::Debug for MoveList {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl Index<usize> for MoveList {
+    type Output = Move;
+
+    fn index(&self, index: usize) -> &Move {
+        &self.as_slice()[index]
+    }
+}
+
+impl<'a> IntoIterator for &'a MoveList {
+    type Item = &'a Move;
+    type IntoIter = slice::Iter<'a, Move>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// A destination of generated moves, either a growing `Vec` or a fixed-capacity `MoveList`.
+pub(crate) trait MoveSink {
+    fn push_move(&mut self, m: Move);
+}
+
+impl MoveSink for Vec<Move> {
+    #[inline]
+    fn push_move(&mut self, m: Move) {
+        self.push(m);
+    }
+}
+
+impl MoveSink for MoveList {
+    #[inline]
+    fn push_move(&mut self, m: Move) {
+        self.push(m);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -94,6 +492,7 @@ mod tests {
         ];
         let ng_cases = [
             "9j1i", "9a1j", "9a1", "9aj", "j1i", "9a1i1", "9a1i-", "S+5e", "S 5e", "Z*5e", "S+9j",
+            "K*5e", "+P*5e", "P*5e+", "k*5e", "あa1",
         ];
 
         for (i, case) in ok_cases.iter().enumerate() {
@@ -107,6 +506,124 @@ mod tests {
         }
     }
 
+    #[test]
+    fn from_str() {
+        let ok_cases = [
+            (
+                "7g7f",
+                Move::Normal {
+                    from: SQ_7G,
+                    to: SQ_7F,
+                    promote: false,
+                },
+            ),
+            (
+                "8h2b+",
+                Move::Normal {
+                    from: SQ_8H,
+                    to: SQ_2B,
+                    promote: true,
+                },
+            ),
+            (
+                "P*5e",
+                Move::Drop {
+                    to: SQ_5E,
+                    piece_type: PieceType::Pawn,
+                },
+            ),
+        ];
+        let ng_cases = ["", "7g7", "7g7f*", "K*5e", "p*5e"];
+
+        for (i, case) in ok_cases.iter().enumerate() {
+            assert_eq!(Ok(case.1), case.0.parse(), "failed at #{i}");
+        }
+
+        for (i, case) in ng_cases.iter().enumerate() {
+            assert_eq!(
+                Err(SfenError::IllegalMove),
+                case.parse::<Move>(),
+                "failed at #{i}"
+            );
+        }
+    }
+
+    #[test]
+    fn accessors() {
+        let cases = [
+            (
+                Move::Normal {
+                    from: SQ_7G,
+                    to: SQ_7F,
+                    promote: false,
+                },
+                false,
+                false,
+                SQ_7F,
+                Some(SQ_7G),
+            ),
+            (
+                Move::Normal {
+                    from: SQ_2C,
+                    to: SQ_2B,
+                    promote: true,
+                },
+                false,
+                true,
+                SQ_2B,
+                Some(SQ_2C),
+            ),
+            (
+                Move::Drop {
+                    to: SQ_5E,
+                    piece_type: PieceType::Silver,
+                },
+                true,
+                false,
+                SQ_5E,
+                None,
+            ),
+        ];
+
+        for (i, case) in cases.iter().enumerate() {
+            assert_eq!(case.1, case.0.is_drop(), "failed at #{i}");
+            assert_eq!(case.2, case.0.is_promotion(), "failed at #{i}");
+            assert_eq!(case.3, case.0.to(), "failed at #{i}");
+            assert_eq!(case.4, case.0.from(), "failed at #{i}");
+        }
+    }
+
+    #[test]
+    fn with_promotion() {
+        let normal = Move::Normal {
+            from: SQ_2C,
+            to: SQ_2B,
+            promote: false,
+        };
+        let promoted = Move::Normal {
+            from: SQ_2C,
+            to: SQ_2B,
+            promote: true,
+        };
+        let drop = Move::Drop {
+            to: SQ_5E,
+            piece_type: PieceType::Silver,
+        };
+
+        let cases = [
+            (normal, true, promoted),
+            (normal, false, normal),
+            (promoted, false, normal),
+            (promoted, true, promoted),
+            (drop, true, drop),
+            (drop, false, drop),
+        ];
+
+        for (i, case) in cases.iter().enumerate() {
+            assert_eq!(case.2, case.0.with_promotion(case.1), "failed at #{i}");
+        }
+    }
+
     #[test]
     fn to_sfen() {
         let cases = [
@@ -139,4 +656,138 @@ mod tests {
             assert_eq!(case.1.to_string(), case.0, "failed at #{i}");
         }
     }
+
+    #[test]
+    fn ord() {
+        let ordered = [
+            Move::Normal {
+                from: SQ_1C,
+                to: SQ_1B,
+                promote: false,
+            },
+            Move::Normal {
+                from: SQ_1C,
+                to: SQ_1B,
+                promote: true,
+            },
+            Move::Normal {
+                from: SQ_2A,
+                to: SQ_1B,
+                promote: false,
+            },
+            Move::Normal {
+                from: SQ_1A,
+                to: SQ_1C,
+                promote: false,
+            },
+            Move::Drop {
+                to: SQ_1A,
+                piece_type: PieceType::Gold,
+            },
+            Move::Drop {
+                to: SQ_1A,
+                piece_type: PieceType::Silver,
+            },
+            Move::Drop {
+                to: SQ_9I,
+                piece_type: PieceType::Pawn,
+            },
+        ];
+
+        for (i, w) in ordered.windows(2).enumerate() {
+            assert!(w[0] < w[1], "failed at #{i}");
+            assert_eq!(Ordering::Equal, w[0].cmp(&w[0]), "failed at #{i}");
+        }
+
+        let mut moves = ordered.to_vec();
+        moves.reverse();
+        moves.sort();
+        assert_eq!(ordered.to_vec(), moves);
+    }
+
+    #[test]
+    fn csa() {
+        use crate::bitboard::Factory as BBFactory;
+
+        BBFactory::init();
+
+        let pos = Position::from_sfen("l3k4/1+R2s4/2P6/9/9/9/9/1r7/4K4 w Pb 1")
+            .expect("failed to parse SFEN string");
+        let cases = [
+            ("-9192KY", "9a9b"),
+            ("-5142OU", "5a4b"),
+            ("-5243GI", "5b4c"),
+            ("-8889RY", "8h8i+"),
+            ("-8883HI", "8h8c"),
+            ("-0055KA", "B*5e"),
+        ];
+
+        for (i, case) in cases.iter().enumerate() {
+            let m = Move::from_sfen(case.1).unwrap();
+            assert_eq!(Some(m), Move::from_csa(case.0, &pos), "failed at #{i}");
+            assert_eq!(Some(case.0.to_string()), m.to_csa(&pos), "failed at #{i}");
+        }
+
+        let ng_cases = [
+            "", "-9192KY+", "*9192KY", "+9192KY", "-9192XX", "-9A92KY", "-0092KY", "-5543FU",
+            "-5243KI", "-0055FU", "+0055FU", "-0055NG",
+            // Pieces of the side not to move.
+            "+8281RY", "+7372TO", "+5958OU",
+        ];
+        for (i, case) in ng_cases.iter().enumerate() {
+            assert_eq!(None, Move::from_csa(case, &pos), "failed at #{i}");
+        }
+
+        assert_eq!(None, Move::from_sfen("5e5d").unwrap().to_csa(&pos));
+    }
+
+    #[test]
+    fn move_list() {
+        let moves = [
+            Move::Normal {
+                from: SQ_7G,
+                to: SQ_7F,
+                promote: false,
+            },
+            Move::Drop {
+                to: SQ_5E,
+                piece_type: PieceType::Silver,
+            },
+        ];
+
+        let mut list = MoveList::new();
+        assert!(list.is_empty());
+
+        for m in moves.iter() {
+            list.push(*m);
+        }
+        assert_eq!(2, list.len());
+        assert_eq!(&moves[..], list.as_slice());
+        assert_eq!(moves[1], list[1]);
+        assert_eq!(
+            moves.to_vec(),
+            (&list).into_iter().copied().collect::<Vec<_>>()
+        );
+
+        list.clear();
+        assert!(list.is_empty());
+        assert_eq!(0, list.iter().count());
+
+        for _ in 0..MAX_MOVES {
+            list.push(moves[0]);
+        }
+        assert_eq!(MAX_MOVES, list.len());
+    }
+
+    #[test]
+    #[should_panic]
+    fn move_list_overflow() {
+        let mut list = MoveList::new();
+        for _ in 0..=MAX_MOVES {
+            list.push(Move::Drop {
+                to: SQ_5E,
+                piece_type: PieceType::Silver,
+            });
+        }
+    }
 }
@@ -1,5 +1,6 @@
-use crate::{Color, PieceType, Square};
-use std::fmt;
+use crate::{Color, PieceType, SfenError, Square};
+use alloc::string::ToString;
+use core::fmt;
 
 /// Represents a piece on the game board.
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
@@ -20,6 +21,26 @@ impl Piece {
         PieceType::from_sfen(c).map(|piece_type| Piece { piece_type, color })
     }
 
+    /// Creates a new instance of `Piece` from SFEN formatted string, which may be prefixed with `+` for promoted pieces.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::{Color, PieceType, Piece};
+    ///
+    /// assert_eq!(Some(Piece{piece_type: PieceType::ProPawn, color: Color::White}), Piece::from_sfen_str("+p"));
+    /// assert_eq!(Some(Piece{piece_type: PieceType::Rook, color: Color::Black}), Piece::from_sfen_str("R"));
+    /// assert_eq!(None, Piece::from_sfen_str("+G"));
+    /// ```
+    pub fn from_sfen_str(s: &str) -> Option<Self> {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next(), chars.next()) {
+            (Some(c), None, None) => Piece::from_sfen(c),
+            (Some('+'), Some(c), None) => Piece::from_sfen(c).and_then(|pc| pc.promote()),
+            _ => None,
+        }
+    }
+
     /// Returns an instance of `Piece` after promotion.
     ///
     /// # Examples
@@ -93,6 +114,15 @@ impl Piece {
     }
 }
 
+/// Converts a character in SFEN notation, where the case determines the color.
+impl TryFrom<char> for Piece {
+    type Error = SfenError;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        Piece::from_sfen(c).ok_or(SfenError::IllegalPieceType)
+    }
+}
+
 impl fmt::Display for Piece {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
         if self.color == Color::Black {
@@ -142,6 +172,66 @@ mod tests {
         }
     }
 
+    #[test]
+    fn try_from() {
+        let ok_cases = [
+            ('k', PieceType::King, Color::White),
+            ('p', PieceType::Pawn, Color::White),
+            ('R', PieceType::Rook, Color::Black),
+            ('S', PieceType::Silver, Color::Black),
+        ];
+        let ng_cases = ['\0', ' ', 'a', 'Z', '+'];
+
+        for (i, case) in ok_cases.iter().enumerate() {
+            assert_eq!(
+                Ok(Piece {
+                    piece_type: case.1,
+                    color: case.2
+                }),
+                Piece::try_from(case.0),
+                "failed at #{i}"
+            );
+        }
+
+        for (i, case) in ng_cases.iter().enumerate() {
+            assert_eq!(
+                Err(SfenError::IllegalPieceType),
+                Piece::try_from(*case),
+                "failed at #{i}"
+            );
+        }
+    }
+
+    #[test]
+    fn from_sfen_str() {
+        let ok_cases = [
+            ("k", PieceType::King, Color::White),
+            ("P", PieceType::Pawn, Color::Black),
+            ("+r", PieceType::ProRook, Color::White),
+            ("+B", PieceType::ProBishop, Color::Black),
+            ("+s", PieceType::ProSilver, Color::White),
+            ("+N", PieceType::ProKnight, Color::Black),
+            ("+l", PieceType::ProLance, Color::White),
+            ("+P", PieceType::ProPawn, Color::Black),
+        ];
+        let ng_cases = ["", "+", "++p", "+k", "+G", "pp", "p+", "+1", "a"];
+
+        for (i, case) in ok_cases.iter().enumerate() {
+            assert_eq!(
+                Some(Piece {
+                    piece_type: case.1,
+                    color: case.2
+                }),
+                Piece::from_sfen_str(case.0),
+                "failed at #{i}"
+            );
+        }
+
+        for (i, case) in ng_cases.iter().enumerate() {
+            assert!(Piece::from_sfen_str(case).is_none(), "failed at #{i}");
+        }
+    }
+
     #[test]
     fn to_sfen() {
         let ok_cases = [
@@ -1,5 +1,9 @@
-use std::fmt;
-use std::iter;
+use core::fmt;
+use core::iter;
+use core::str::FromStr;
+
+use crate::bitboard::Factory as BBFactory;
+use crate::{Bitboard, Color, SfenError, Square};
 
 /// Represents a kind of pieces.
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
@@ -21,6 +25,38 @@ pub enum PieceType {
 }
 
 impl PieceType {
+    /// The number of piece types.
+    pub const NUM: usize = 14;
+
+    /// All piece types, ordered by their indices.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::PieceType;
+    ///
+    /// let mut counts = [0u32; PieceType::NUM];
+    /// counts[PieceType::Pawn.index()] += 1;
+    ///
+    /// assert!(PieceType::ALL.iter().enumerate().all(|(i, pt)| pt.index() == i));
+    /// ```
+    pub const ALL: [PieceType; PieceType::NUM] = [
+        PieceType::King,
+        PieceType::Rook,
+        PieceType::Bishop,
+        PieceType::Gold,
+        PieceType::Silver,
+        PieceType::Knight,
+        PieceType::Lance,
+        PieceType::Pawn,
+        PieceType::ProRook,
+        PieceType::ProBishop,
+        PieceType::ProSilver,
+        PieceType::ProKnight,
+        PieceType::ProLance,
+        PieceType::ProPawn,
+    ];
+
     /// Returns an iterator over all variants.
     pub fn iter() -> PieceTypeIter {
         PieceTypeIter::new()
@@ -41,6 +77,28 @@ impl PieceType {
         })
     }
 
+    /// Creates a new instance of `PieceType` from SFEN formatted string, which may be prefixed with `+` for promoted types.
+    ///
+    /// The case of the letter is ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::PieceType;
+    ///
+    /// assert_eq!(Some(PieceType::ProPawn), PieceType::from_sfen_str("+p"));
+    /// assert_eq!(Some(PieceType::Rook), PieceType::from_sfen_str("r"));
+    /// assert_eq!(None, PieceType::from_sfen_str("+k"));
+    /// ```
+    pub fn from_sfen_str(s: &str) -> Option<Self> {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next(), chars.next()) {
+            (Some(c), None, None) => PieceType::from_sfen(c),
+            (Some('+'), Some(c), None) => PieceType::from_sfen(c).and_then(|pt| pt.promote()),
+            _ => None,
+        }
+    }
+
     /// Returns an instance of `PieceType` after promotion.
     ///
     /// # Examples
@@ -105,9 +163,101 @@ impl PieceType {
         )
     }
 
+    /// Returns the kanji symbol of this piece type.
+    ///
+    /// Promoted pieces are represented by their single-character forms (と, 杏, 圭, 全, 馬, 龍),
+    /// so that every piece type is rendered in the same width. The king is always represented as 玉.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::PieceType;
+    ///
+    /// assert_eq!("歩", PieceType::Pawn.kanji());
+    /// assert_eq!("杏", PieceType::ProLance.kanji());
+    /// ```
+    pub fn kanji(self) -> &'static str {
+        match self {
+            PieceType::King => "玉",
+            PieceType::Rook => "飛",
+            PieceType::Bishop => "角",
+            PieceType::Gold => "金",
+            PieceType::Silver => "銀",
+            PieceType::Knight => "桂",
+            PieceType::Lance => "香",
+            PieceType::Pawn => "歩",
+            PieceType::ProRook => "龍",
+            PieceType::ProBishop => "馬",
+            PieceType::ProSilver => "全",
+            PieceType::ProKnight => "圭",
+            PieceType::ProLance => "杏",
+            PieceType::ProPawn => "と",
+        }
+    }
+
+    /// Returns the piece types which can be in hand, in the order used for SFEN serialization and rendering.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::PieceType;
+    ///
+    /// assert_eq!(PieceType::Rook, PieceType::hand_order()[0]);
+    /// assert_eq!(PieceType::Pawn, PieceType::hand_order()[6]);
+    /// ```
+    pub const fn hand_order() -> [PieceType; 7] {
+        [
+            PieceType::Rook,
+            PieceType::Bishop,
+            PieceType::Gold,
+            PieceType::Silver,
+            PieceType::Knight,
+            PieceType::Lance,
+            PieceType::Pawn,
+        ]
+    }
+
+    /// Returns a bitboard in which squares attacked by a piece of this type, owned by the given color, at the given square are filled.
+    ///
+    /// Sliding pieces (Rook, Bishop, Lance and their promoted forms) are treated as if the board were empty.
+    /// Use [`Factory::rook_attack`](crate::bitboard::Factory::rook_attack) and its siblings to take blocking pieces into account.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::{Color, PieceType};
+    /// use shogi::square::consts::*;
+    ///
+    /// let bb = PieceType::Gold.attacks(Color::Black, SQ_5E);
+    /// assert_eq!(6, bb.count());
+    /// assert!((&bb & SQ_5D).is_any());
+    /// assert!((&bb & SQ_4F).is_empty());
+    /// ```
+    pub fn attacks(self, c: Color, sq: Square) -> Bitboard {
+        let empty = Bitboard::empty();
+        match self {
+            PieceType::Rook => BBFactory::rook_attack(sq, &empty),
+            PieceType::Bishop => BBFactory::bishop_attack(sq, &empty),
+            PieceType::Lance => BBFactory::lance_attack(c, sq, &empty),
+            PieceType::ProRook => {
+                &BBFactory::rook_attack(sq, &empty)
+                    | &BBFactory::attacks_from(PieceType::King, c, sq)
+            }
+            PieceType::ProBishop => {
+                &BBFactory::bishop_attack(sq, &empty)
+                    | &BBFactory::attacks_from(PieceType::King, c, sq)
+            }
+            PieceType::ProSilver
+            | PieceType::ProKnight
+            | PieceType::ProLance
+            | PieceType::ProPawn => BBFactory::attacks_from(PieceType::Gold, c, sq),
+            pt => BBFactory::attacks_from(pt, c, sq),
+        }
+    }
+
     /// Converts the instance into the unique number for array indexing purpose.
     #[inline(always)]
-    pub fn index(self) -> usize {
+    pub const fn index(self) -> usize {
         self as usize
     }
 }
@@ -137,18 +287,38 @@ impl fmt::Display for PieceType {
     }
 }
 
+/// Converts a character in SFEN notation ignoring case.
+impl TryFrom<char> for PieceType {
+    type Error = SfenError;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        PieceType::from_sfen(c).ok_or(SfenError::IllegalPieceType)
+    }
+}
+
+/// Parses a piece type in SFEN notation ignoring case, e.g. `p` or `+P`.
+impl FromStr for PieceType {
+    type Err = SfenError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        PieceType::from_sfen_str(s).ok_or(SfenError::IllegalPieceType)
+    }
+}
+
 /// This struct is created by the [`iter`] method on [`PieceType`].
 ///
 /// [`iter`]: enum.PieceType.html#method.iter
 /// [`PieceType`]: enum.PieceType.html
 pub struct PieceTypeIter {
-    current: Option<PieceType>,
+    front: usize,
+    back: usize,
 }
 
 impl PieceTypeIter {
     fn new() -> PieceTypeIter {
         PieceTypeIter {
-            current: Some(PieceType::King),
+            front: 0,
+            back: PieceType::NUM,
         }
     }
 }
@@ -157,35 +327,75 @@ impl iter::Iterator for PieceTypeIter {
     type Item = PieceType;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let current = self.current;
-
-        if let Some(current) = self.current {
-            self.current = match current {
-                PieceType::King => Some(PieceType::Rook),
-                PieceType::Rook => Some(PieceType::Bishop),
-                PieceType::Bishop => Some(PieceType::Gold),
-                PieceType::Gold => Some(PieceType::Silver),
-                PieceType::Silver => Some(PieceType::Knight),
-                PieceType::Knight => Some(PieceType::Lance),
-                PieceType::Lance => Some(PieceType::Pawn),
-                PieceType::Pawn => Some(PieceType::ProRook),
-                PieceType::ProRook => Some(PieceType::ProBishop),
-                PieceType::ProBishop => Some(PieceType::ProSilver),
-                PieceType::ProSilver => Some(PieceType::ProKnight),
-                PieceType::ProKnight => Some(PieceType::ProLance),
-                PieceType::ProLance => Some(PieceType::ProPawn),
-                PieceType::ProPawn => None,
-            };
+        if self.front < self.back {
+            self.front += 1;
+            Some(PieceType::ALL[self.front - 1])
+        } else {
+            None
         }
+    }
 
-        current
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+        (len, Some(len))
+    }
+}
+
+impl iter::DoubleEndedIterator for PieceTypeIter {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front < self.back {
+            self.back -= 1;
+            Some(PieceType::ALL[self.back])
+        } else {
+            None
+        }
     }
 }
 
+impl iter::ExactSizeIterator for PieceTypeIter {}
+
+impl iter::FusedIterator for PieceTypeIter {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn attacks() {
+        use crate::square::consts::*;
+
+        let cases = [
+            (PieceType::King, Color::Black, SQ_5E, 8),
+            (PieceType::Gold, Color::White, SQ_5E, 6),
+            (PieceType::Silver, Color::Black, SQ_5E, 5),
+            (PieceType::Knight, Color::Black, SQ_5E, 2),
+            (PieceType::Pawn, Color::White, SQ_5E, 1),
+            (PieceType::Rook, Color::Black, SQ_5E, 16),
+            (PieceType::Bishop, Color::Black, SQ_5E, 16),
+            (PieceType::Lance, Color::Black, SQ_5E, 4),
+            (PieceType::ProRook, Color::Black, SQ_5E, 20),
+            (PieceType::ProBishop, Color::Black, SQ_5E, 20),
+            (PieceType::ProPawn, Color::Black, SQ_5E, 6),
+            (PieceType::Knight, Color::Black, SQ_5B, 0),
+            (PieceType::Lance, Color::White, SQ_1I, 0),
+        ];
+
+        for (i, case) in cases.iter().enumerate() {
+            assert_eq!(
+                case.3,
+                case.0.attacks(case.1, case.2).count(),
+                "failed at #{i}"
+            );
+        }
+
+        let pro = PieceType::ProSilver.attacks(Color::White, SQ_5E);
+        let gold = PieceType::Gold.attacks(Color::White, SQ_5E);
+        assert_eq!(
+            gold.squares().collect::<Vec<_>>(),
+            pro.squares().collect::<Vec<_>>()
+        );
+    }
+
     #[test]
     fn from_sfen() {
         let ok_cases = [
@@ -213,6 +423,65 @@ mod tests {
         }
     }
 
+    #[test]
+    fn try_from() {
+        let ok_cases = [
+            ('k', PieceType::King),
+            ('R', PieceType::Rook),
+            ('b', PieceType::Bishop),
+            ('G', PieceType::Gold),
+            ('p', PieceType::Pawn),
+        ];
+        let ng_cases = ['\0', ' ', 'a', 'Z', '+'];
+
+        for (i, case) in ok_cases.iter().enumerate() {
+            assert_eq!(Ok(case.1), PieceType::try_from(case.0), "failed at #{i}");
+        }
+
+        for (i, case) in ng_cases.iter().enumerate() {
+            assert_eq!(
+                Err(SfenError::IllegalPieceType),
+                PieceType::try_from(*case),
+                "failed at #{i}"
+            );
+        }
+    }
+
+    #[test]
+    fn from_str() {
+        let ok_cases = [
+            ("k", PieceType::King),
+            ("R", PieceType::Rook),
+            ("p", PieceType::Pawn),
+            ("+b", PieceType::ProBishop),
+            ("+P", PieceType::ProPawn),
+        ];
+        let ng_cases = ["", "+", "a", "pp", "+k", "+G", "++p", "p+"];
+
+        for (i, case) in ok_cases.iter().enumerate() {
+            assert_eq!(Ok(case.1), case.0.parse(), "failed at #{i}");
+            assert_eq!(
+                Some(case.1),
+                PieceType::from_sfen_str(case.0),
+                "failed at #{i}"
+            );
+        }
+
+        for (i, case) in ng_cases.iter().enumerate() {
+            assert_eq!(
+                Err(SfenError::IllegalPieceType),
+                case.parse::<PieceType>(),
+                "failed at #{i}"
+            );
+            assert_eq!(None, PieceType::from_sfen_str(case), "failed at #{i}");
+        }
+
+        // Parsing is the inverse of formatting.
+        for pt in PieceType::iter() {
+            assert_eq!(Some(pt), PieceType::from_sfen_str(&pt.to_string()));
+        }
+    }
+
     #[test]
     fn to_sfen() {
         let ok_cases = [
@@ -296,4 +565,68 @@ mod tests {
             assert!(case.unpromote().is_none());
         }
     }
+
+    #[test]
+    fn kanji() {
+        let cases = [
+            (PieceType::King, "玉"),
+            (PieceType::Rook, "飛"),
+            (PieceType::Bishop, "角"),
+            (PieceType::Gold, "金"),
+            (PieceType::Silver, "銀"),
+            (PieceType::Knight, "桂"),
+            (PieceType::Lance, "香"),
+            (PieceType::Pawn, "歩"),
+            (PieceType::ProRook, "龍"),
+            (PieceType::ProBishop, "馬"),
+            (PieceType::ProSilver, "全"),
+            (PieceType::ProKnight, "圭"),
+            (PieceType::ProLance, "杏"),
+            (PieceType::ProPawn, "と"),
+        ];
+
+        for (i, case) in cases.iter().enumerate() {
+            assert_eq!(case.1, case.0.kanji(), "failed at #{i}");
+            assert_eq!(1, case.0.kanji().chars().count(), "failed at #{i}");
+        }
+    }
+
+    #[test]
+    fn iter() {
+        let mut it = PieceType::iter();
+        assert_eq!(PieceType::NUM, it.len());
+        assert_eq!(Some(PieceType::King), it.next());
+        assert_eq!(Some(PieceType::ProPawn), it.next_back());
+        assert_eq!(PieceType::NUM - 2, it.len());
+        assert_eq!(PieceType::NUM - 2, it.by_ref().count());
+        assert_eq!(None, it.next());
+        assert_eq!(None, it.next_back());
+
+        let mut reversed = PieceType::iter().rev().collect::<Vec<_>>();
+        reversed.reverse();
+        assert_eq!(PieceType::ALL.to_vec(), reversed);
+    }
+
+    #[test]
+    fn hand_order() {
+        assert_eq!(
+            PieceType::iter()
+                .filter(|pt| pt.is_hand_piece())
+                .collect::<Vec<_>>(),
+            PieceType::hand_order().to_vec()
+        );
+    }
+
+    #[test]
+    fn all() {
+        assert_eq!(PieceType::NUM, PieceType::ALL.len());
+        assert_eq!(
+            PieceType::ALL.to_vec(),
+            PieceType::iter().collect::<Vec<_>>()
+        );
+
+        for (i, pt) in PieceType::ALL.iter().enumerate() {
+            assert_eq!(i, pt.index(), "failed at #{i}");
+        }
+    }
 }
@@ -0,0 +1,145 @@
+use crate::{Move, MoveError, Position};
+
+/// Tracks a speculative move made on a position while pondering.
+///
+/// [`start`](#method.start) makes the predicted move of the opponent so that the position can be searched in advance.
+/// Once the actual move is known, [`hit`](#method.hit) keeps the predicted move on the board,
+/// while [`miss`](#method.miss) restores the position to the state before pondering, including its history used for repetition detection.
+///
+/// # Examples
+///
+/// ```
+/// use shogi::{Move, PonderState, Position};
+/// use shogi::bitboard::Factory as BBFactory;
+///
+/// BBFactory::init();
+/// let mut pos = Position::initial();
+/// pos.play_usi_moves("7g7f").unwrap();
+///
+/// // The opponent is expected to reply 3c3d.
+/// let ponder = PonderState::start(&mut pos, Move::from_sfen("3c3d").unwrap()).unwrap();
+///
+/// // ...but plays 8c8d instead.
+/// ponder.miss(&mut pos);
+/// pos.play_usi_moves("8c8d").unwrap();
+///
+/// assert_eq!("lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1 moves 7g7f 8c8d", pos.to_sfen());
+/// ```
+#[derive(Debug, Clone)]
+pub struct PonderState {
+    m: Move,
+    backup: Position,
+}
+
+impl PonderState {
+    /// Makes the given move on `pos` and starts pondering on the resulting position.
+    ///
+    /// If the move can not be made, `pos` is left unchanged and the error is returned.
+    pub fn start(pos: &mut Position, m: Move) -> Result<PonderState, MoveError> {
+        let backup = pos.clone();
+        if let Err(e) = pos.make_move(m) {
+            *pos = backup;
+            return Err(e);
+        }
+
+        Ok(PonderState { m, backup })
+    }
+
+    /// Returns the move being pondered on.
+    pub fn ponder_move(&self) -> Move {
+        self.m
+    }
+
+    /// Finishes pondering as the opponent made the predicted move, which stays on the board.
+    ///
+    /// Returns the move being pondered on.
+    pub fn hit(self) -> Move {
+        self.m
+    }
+
+    /// Finishes pondering as the opponent made a different move.
+    ///
+    /// `pos` is restored to the state before [`start`](#method.start), discarding the predicted move
+    /// along with any moves made on it since then.
+    pub fn miss(self, pos: &mut Position) {
+        *pos = self.backup;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitboard::Factory as BBFactory;
+
+    fn setup() {
+        BBFactory::init();
+    }
+
+    #[test]
+    fn hit() {
+        setup();
+
+        let mut pos = Position::initial();
+        pos.play_usi_moves("7g7f").unwrap();
+
+        let m = Move::from_sfen("3c3d").unwrap();
+        let ponder = PonderState::start(&mut pos, m).unwrap();
+        assert_eq!(m, ponder.ponder_move());
+
+        // Searching on the pondered position doesn't affect the result.
+        pos.play_usi_moves("2g2f").unwrap();
+        pos.unmake_move().unwrap();
+
+        assert_eq!(m, ponder.hit());
+        assert_eq!(
+            "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1 moves 7g7f 3c3d",
+            pos.to_sfen()
+        );
+    }
+
+    #[test]
+    fn miss() {
+        setup();
+
+        let mut pos = Position::initial();
+        pos.play_usi_moves("7g7f").unwrap();
+        let original = pos.clone();
+
+        let ponder = PonderState::start(&mut pos, Move::from_sfen("3c3d").unwrap()).unwrap();
+        pos.play_usi_moves("2g2f").unwrap();
+        ponder.miss(&mut pos);
+
+        assert_eq!(original, pos);
+        assert_eq!(original.to_sfen(), pos.to_sfen());
+        assert_eq!(original.repetition_count(), pos.repetition_count());
+    }
+
+    #[test]
+    fn start_fails() {
+        setup();
+
+        let cases = [
+            ("lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1 moves 7g7f", "2g2f"),
+            // The predicted move causes repetition.
+            (
+                "ln7/ks+R6/pp7/9/9/9/9/9/9 b Ss 1 moves S*7a S*7c 7a8b+ 7c8b S*7a S*7c 7a8b+ 7c8b S*7a S*7c 7a8b+",
+                "7c8b",
+            ),
+        ];
+
+        for (i, case) in cases.iter().enumerate() {
+            let mut pos = Position::from_sfen(case.0).expect("failed to parse SFEN string");
+            let original = pos.clone();
+
+            let res = PonderState::start(&mut pos, Move::from_sfen(case.1).unwrap());
+            assert!(res.is_err(), "failed at #{i}");
+            assert_eq!(original, pos, "failed at #{i}");
+            assert_eq!(original.to_sfen(), pos.to_sfen(), "failed at #{i}");
+            assert_eq!(
+                original.repetition_count(),
+                pos.repetition_count(),
+                "failed at #{i}"
+            );
+        }
+    }
+}
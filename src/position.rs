@@ -1,12 +1,19 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::fmt::Write as _;
+use core::hash::{Hash, Hasher};
 use itertools::Itertools;
-use std::fmt;
-use std::fmt::Write as _;
 
 use crate::bitboard::Factory as BBFactory;
-use crate::{Bitboard, Color, Hand, Move, MoveError, Piece, PieceType, SfenError, Square};
+use crate::moves::{MoveList, MoveSink};
+use crate::{
+    Bitboard, Color, Hand, Move, MoveError, Piece, PieceType, PositionError, SfenError, Square,
+};
 
 /// MoveRecord stores information necessary to undo the move.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum MoveRecord {
     Normal {
         from: Square,
@@ -34,6 +41,37 @@ impl MoveRecord {
             } => format!("{}*{}", piece_type.to_string().to_uppercase(), to),
         }
     }
+
+    /// Returns the piece placed at the destination square, after promotion if the move promoted it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::{Color, Piece, PieceType, Position};
+    /// use shogi::bitboard::Factory as BBFactory;
+    /// use shogi::square::consts::*;
+    ///
+    /// BBFactory::init();
+    /// let mut pos = Position::initial();
+    /// pos.play_usi_moves("7g7f 3c3d 8h2b+").unwrap();
+    ///
+    /// let record = pos.move_history().last().unwrap();
+    /// assert_eq!(Piece{piece_type: PieceType::ProBishop, color: Color::Black}, record.placed_piece());
+    /// assert_eq!(SQ_2B, record.destination());
+    /// ```
+    pub fn placed_piece(&self) -> Piece {
+        match *self {
+            MoveRecord::Normal { placed, .. } => placed,
+            MoveRecord::Drop { piece, .. } => piece,
+        }
+    }
+
+    /// Returns the destination square of the move.
+    pub fn destination(&self) -> Square {
+        match *self {
+            MoveRecord::Normal { to, .. } | MoveRecord::Drop { to, .. } => to,
+        }
+    }
 }
 
 impl PartialEq<Move> for MoveRecord {
@@ -60,6 +98,201 @@ impl PartialEq<Move> for MoveRecord {
     }
 }
 
+/// Represents an outcome of a game, as classified by [`Position::game_result`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum GameResult {
+    /// The side to move is checkmated by the given color.
+    Checkmate(Color),
+    /// The side to move has no legal moves without being in check, which is a loss in Shogi.
+    Stalemate(Color),
+    /// The same position has appeared four times (千日手).
+    Repetition,
+    /// The same position has appeared four times while the side to move kept checking.
+    /// The player who made the last move wins.
+    PerpetualCheckWin,
+    /// The same position has appeared four times while the player who made the last move kept checking.
+    /// The player who made the last move loses.
+    PerpetualCheckLose,
+}
+
+/// Represents a qualifier telling the moving piece apart from others of the same kind, as used in Japanese notation.
+///
+/// See [`Position::move_disambiguation`] for how the qualifier is chosen.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Disambiguation {
+    /// No other piece can make the same move.
+    None,
+    /// 打: A piece of the same kind on the board can move to the destination of the drop.
+    Drop,
+    /// The direction of the movement is enough.
+    Movement(Movement),
+    /// The relative position of the moving piece is enough.
+    Relative(Relative),
+    /// Both the relative position and the direction of the movement are needed.
+    Both(Relative, Movement),
+}
+
+impl Disambiguation {
+    /// Returns the qualifier in kanji, which is empty for `Disambiguation::None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::Disambiguation;
+    /// use shogi::position::{Movement, Relative};
+    ///
+    /// assert_eq!("右", Disambiguation::Relative(Relative::Right).kanji());
+    /// assert_eq!("左引", Disambiguation::Both(Relative::Left, Movement::Down).kanji());
+    /// ```
+    pub fn kanji(self) -> &'static str {
+        match self {
+            Disambiguation::None => "",
+            Disambiguation::Drop => "打",
+            Disambiguation::Movement(Movement::Up) => "上",
+            Disambiguation::Movement(Movement::Down) => "引",
+            Disambiguation::Movement(Movement::Sideways) => "寄",
+            Disambiguation::Relative(Relative::Left) => "左",
+            Disambiguation::Relative(Relative::Right) => "右",
+            Disambiguation::Relative(Relative::Straight) => "直",
+            Disambiguation::Both(Relative::Left, Movement::Up) => "左上",
+            Disambiguation::Both(Relative::Left, Movement::Down) => "左引",
+            Disambiguation::Both(Relative::Left, Movement::Sideways) => "左寄",
+            Disambiguation::Both(Relative::Right, Movement::Up) => "右上",
+            Disambiguation::Both(Relative::Right, Movement::Down) => "右引",
+            Disambiguation::Both(Relative::Right, Movement::Sideways) => "右寄",
+            Disambiguation::Both(Relative::Straight, _) => "直",
+        }
+    }
+}
+
+/// Represents a direction of a movement, seen from the player making the move.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Movement {
+    /// 上: Moving forward.
+    Up,
+    /// 引: Moving backward.
+    Down,
+    /// 寄: Moving sideways within the same rank.
+    Sideways,
+}
+
+/// Represents a position of the moving piece relative to the others, seen from the player making the move.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Relative {
+    /// 左: The leftmost piece.
+    Left,
+    /// 右: The rightmost piece.
+    Right,
+    /// 直: Moving straight forward.
+    Straight,
+}
+
+/// Represents a kind of handicap (駒落ち) games.
+///
+/// Pieces are removed from White's side, who moves first in handicap games.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Handicap {
+    /// 香落ち: White's lance on 1a is removed.
+    Lance,
+    /// 右香落ち: White's lance on 9a is removed.
+    RightLance,
+    /// 角落ち: White's bishop is removed.
+    Bishop,
+    /// 飛車落ち: White's rook is removed.
+    Rook,
+    /// 飛香落ち: White's rook and the lance on 1a are removed.
+    RookLance,
+    /// 二枚落ち: White's rook and bishop are removed.
+    TwoPieces,
+    /// 四枚落ち: White's rook, bishop and lances are removed.
+    FourPieces,
+    /// 六枚落ち: White's rook, bishop, lances and knights are removed.
+    SixPieces,
+    /// 八枚落ち: White's rook, bishop, lances, knights and silvers are removed.
+    EightPieces,
+    /// 十枚落ち: All White's pieces except the king and pawns are removed.
+    TenPieces,
+}
+
+impl Handicap {
+    /// Returns squares of the pieces removed from the initial position.
+    fn removed_squares(self) -> &'static [Square] {
+        use crate::square::consts::*;
+
+        match self {
+            Handicap::Lance => &[SQ_1A],
+            Handicap::RightLance => &[SQ_9A],
+            Handicap::Bishop => &[SQ_2B],
+            Handicap::Rook => &[SQ_8B],
+            Handicap::RookLance => &[SQ_8B, SQ_1A],
+            Handicap::TwoPieces => &[SQ_8B, SQ_2B],
+            Handicap::FourPieces => &[SQ_8B, SQ_2B, SQ_1A, SQ_9A],
+            Handicap::SixPieces => &[SQ_8B, SQ_2B, SQ_1A, SQ_9A, SQ_2A, SQ_8A],
+            Handicap::EightPieces => &[SQ_8B, SQ_2B, SQ_1A, SQ_9A, SQ_2A, SQ_8A, SQ_3A, SQ_7A],
+            Handicap::TenPieces => &[
+                SQ_8B, SQ_2B, SQ_1A, SQ_9A, SQ_2A, SQ_8A, SQ_3A, SQ_7A, SQ_4A, SQ_6A,
+            ],
+        }
+    }
+}
+
+/// SFEN string of the standard initial position.
+const STARTPOS_SFEN: &str = "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1";
+
+/// Token of a null move in the SFEN move list.
+const NULL_MOVE_SFEN: &str = "pass";
+
+/// Random keys for Zobrist hashing, generated at compile time.
+struct ZobristKeys {
+    board: [[[u64; 2]; PieceType::NUM]; 81],
+    hand: [[u64; PieceType::NUM]; 2],
+    side: u64,
+}
+
+impl ZobristKeys {
+    const fn new() -> ZobristKeys {
+        // SplitMix64 generator for deterministic keys.
+        const fn next(state: &mut u64) -> u64 {
+            *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+            let mut z = *state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            z ^ (z >> 31)
+        }
+
+        let mut state = 0;
+        let mut keys = ZobristKeys {
+            board: [[[0; 2]; PieceType::NUM]; 81],
+            hand: [[0; PieceType::NUM]; 2],
+            side: 0,
+        };
+
+        let mut sq = 0;
+        while sq < 81 {
+            let mut pt = 0;
+            while pt < PieceType::NUM {
+                keys.board[sq][pt][0] = next(&mut state);
+                keys.board[sq][pt][1] = next(&mut state);
+                pt += 1;
+            }
+            sq += 1;
+        }
+
+        let mut pt = 0;
+        while pt < PieceType::NUM {
+            keys.hand[0][pt] = next(&mut state);
+            keys.hand[1][pt] = next(&mut state);
+            pt += 1;
+        }
+
+        keys.side = next(&mut state);
+        keys
+    }
+}
+
+static ZOBRIST: ZobristKeys = ZobristKeys::new();
+
+#[derive(Clone, PartialEq, Eq)]
 struct PieceGrid([Option<Piece>; 81]);
 
 impl PieceGrid {
@@ -101,17 +334,20 @@ impl fmt::Debug for PieceGrid {
 ///
 /// assert_eq!("lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1 moves 7g7f", pos.to_sfen());
 /// ```
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Position {
     board: PieceGrid,
     hand: Hand,
     ply: u16,
     side_to_move: Color,
     move_history: Vec<MoveRecord>,
-    sfen_history: Vec<(String, u16)>,
+    null_moves: Vec<usize>,
+    start_sfen: String,
+    hash_history: Vec<(u64, u16)>,
     occupied_bb: Bitboard,
     color_bb: [Bitboard; 2],
-    type_bb: [Bitboard; 14],
+    type_bb: [Bitboard; PieceType::NUM],
+    king_sq: [Option<Square>; 2],
 }
 
 /////////////////////////////////////////////////////////////////////////////
@@ -120,10 +356,107 @@ pub struct Position {
 
 impl Position {
     /// Creates a new instance of `Position` with an empty board.
+    ///
+    /// Note that the board has no pieces at all, not even kings.
+    /// Use [`initial`](#method.initial) for the standard starting position, or [`set_sfen`](#method.set_sfen) to set up any other one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::Position;
+    /// use shogi::bitboard::Factory as BBFactory;
+    ///
+    /// BBFactory::init();
+    /// let pos = Position::new();
+    ///
+    /// assert_eq!("9/9/9/9/9/9/9/9/9 b - 1", pos.to_sfen());
+    /// assert!(pos.to_string().ends_with("(empty board)"));
+    /// ```
     pub fn new() -> Position {
         Default::default()
     }
 
+    /// Creates a new instance of `Position` with the standard initial position (平手).
+    ///
+    /// This is equivalent to parsing `lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1`
+    /// with [`set_sfen`](#method.set_sfen), without the overhead of string parsing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::Position;
+    /// use shogi::bitboard::Factory as BBFactory;
+    ///
+    /// BBFactory::init();
+    /// let pos = Position::initial();
+    ///
+    /// assert_eq!("lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1", pos.to_sfen());
+    /// ```
+    pub fn initial() -> Position {
+        const BACK_RANK: [PieceType; 9] = [
+            PieceType::Lance,
+            PieceType::Knight,
+            PieceType::Silver,
+            PieceType::Gold,
+            PieceType::King,
+            PieceType::Gold,
+            PieceType::Silver,
+            PieceType::Knight,
+            PieceType::Lance,
+        ];
+
+        let mut pos = Position::new();
+
+        for c in Color::iter() {
+            // Squares are described from Black's side, and rotated by 180 degrees for White.
+            let sq = |file: u8, rank: u8| match c {
+                Color::Black => Square::new(file, rank).unwrap(),
+                Color::White => Square::new(8 - file, 8 - rank).unwrap(),
+            };
+            let pc = |piece_type: PieceType| Piece {
+                piece_type,
+                color: c,
+            };
+
+            for (file, &pt) in BACK_RANK.iter().enumerate() {
+                pos.place_piece(sq(file as u8, 8), pc(pt));
+                pos.place_piece(sq(file as u8, 6), pc(PieceType::Pawn));
+            }
+            pos.place_piece(sq(1, 7), pc(PieceType::Rook));
+            pos.place_piece(sq(7, 7), pc(PieceType::Bishop));
+        }
+
+        pos.reset_history();
+        pos
+    }
+
+    /// Creates a new instance of `Position` with the initial position of the given handicap game.
+    ///
+    /// White moves first as is customary for handicap games.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::{Handicap, Position};
+    /// use shogi::bitboard::Factory as BBFactory;
+    ///
+    /// BBFactory::init();
+    /// let pos = Position::handicap(Handicap::TwoPieces);
+    ///
+    /// assert_eq!("lnsgkgsnl/9/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL w - 1", pos.to_sfen());
+    /// ```
+    pub fn handicap(kind: Handicap) -> Position {
+        let mut pos = Position::initial();
+
+        for &sq in kind.removed_squares() {
+            pos.remove_piece(sq);
+        }
+        pos.side_to_move = Color::White;
+
+        pos.reset_history();
+        pos
+    }
+
     /////////////////////////////////////////////////////////////////////////
     // Accessors
     /////////////////////////////////////////////////////////////////////////
@@ -133,11 +466,126 @@ impl Position {
         self.board.get(sq)
     }
 
+    /// Returns a piece at the given square by value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::{Color, Piece, PieceType, Position};
+    /// use shogi::bitboard::Factory as BBFactory;
+    /// use shogi::square::consts::*;
+    ///
+    /// BBFactory::init();
+    /// let pos = Position::initial();
+    ///
+    /// assert_eq!(Some(Piece{piece_type: PieceType::King, color: Color::Black}), pos.piece_on(SQ_5I));
+    /// assert_eq!(None, pos.piece_on(SQ_5E));
+    /// ```
+    pub fn piece_on(&self, sq: Square) -> Option<Piece> {
+        *self.board.get(sq)
+    }
+
     /// Returns a bitboard containing pieces of the given player.
     pub fn player_bb(&self, c: Color) -> &Bitboard {
         &self.color_bb[c.index()]
     }
 
+    /// Returns a bitboard containing all pieces on the board.
+    pub fn occupied(&self) -> &Bitboard {
+        &self.occupied_bb
+    }
+
+    /// Returns a bitboard containing pieces of the given player and type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::{Color, PieceType, Position};
+    /// use shogi::bitboard::Factory as BBFactory;
+    ///
+    /// BBFactory::init();
+    /// let pos = Position::initial();
+    ///
+    /// assert_eq!(9, pos.pieces(Color::Black, PieceType::Pawn).count());
+    /// assert_eq!(2, pos.pieces_by_type(PieceType::Rook).count());
+    /// ```
+    pub fn pieces(&self, c: Color, pt: PieceType) -> Bitboard {
+        &self.type_bb[pt.index()] & &self.color_bb[c.index()]
+    }
+
+    /// Returns a bitboard containing pieces of the given type of both players.
+    pub fn pieces_by_type(&self, pt: PieceType) -> Bitboard {
+        self.type_bb[pt.index()]
+    }
+
+    /// Returns an iterator over occupied squares and the pieces on them, in ascending order of squares.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::{Color, Piece, PieceType, Position};
+    /// use shogi::bitboard::Factory as BBFactory;
+    /// use shogi::square::consts::*;
+    ///
+    /// BBFactory::init();
+    /// let mut pos = Position::new();
+    /// pos.set_sfen("8k/9/9/9/9/9/9/9/K8 b - 1").unwrap();
+    ///
+    /// let pieces = pos.iter_pieces().collect::<Vec<_>>();
+    /// assert_eq!(2, pieces.len());
+    /// assert_eq!((SQ_1A, Piece{piece_type: PieceType::King, color: Color::White}), pieces[0]);
+    /// ```
+    pub fn iter_pieces(&self) -> PieceIter<'_> {
+        PieceIter {
+            pos: self,
+            squares: self.occupied_bb,
+        }
+    }
+
+    /// Returns a copy of the board, indexed by [`Square::index`].
+    pub fn board(&self) -> [Option<Piece>; 81] {
+        self.board.0
+    }
+
+    /// Replaces the whole board with the given one, indexed by [`Square::index`].
+    ///
+    /// Pieces in hand, the side to move and the ply are kept as they are, while the history is cleared.
+    /// The resulting position is not validated; use [`validate`](#method.validate) if needed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::{Color, Piece, PieceType, Position};
+    /// use shogi::bitboard::Factory as BBFactory;
+    /// use shogi::square::consts::*;
+    ///
+    /// BBFactory::init();
+    /// let mut board = [None; 81];
+    /// board[SQ_5I.index()] = Some(Piece{piece_type: PieceType::King, color: Color::Black});
+    /// board[SQ_5A.index()] = Some(Piece{piece_type: PieceType::King, color: Color::White});
+    ///
+    /// let mut pos = Position::new();
+    /// pos.set_board(&board);
+    ///
+    /// assert_eq!("4k4/9/9/9/9/9/9/9/4K4 b - 1", pos.to_sfen());
+    /// assert_eq!(board, pos.board());
+    /// ```
+    pub fn set_board(&mut self, board: &[Option<Piece>; 81]) {
+        self.board = PieceGrid([None; 81]);
+        self.occupied_bb = Bitboard::empty();
+        self.color_bb = Default::default();
+        self.type_bb = Default::default();
+        self.king_sq = [None; 2];
+
+        for sq in Square::iter() {
+            if let Some(pc) = board[sq.index()] {
+                self.place_piece(sq, pc);
+            }
+        }
+
+        self.reset_history();
+    }
+
     /// Returns the number of the given piece in hand.
     pub fn hand(&self, p: Piece) -> u8 {
         self.hand.get(p)
@@ -148,16 +596,139 @@ impl Position {
         self.side_to_move
     }
 
-    /// Returns the number of plies already completed by the current state.
+    /// Sets the side to make a move next, without making any move.
+    ///
+    /// The move history is kept, so the moves made so far can still be undone. Only the record of the current position
+    /// for repetition detection is updated. Note that [`to_sfen`](#method.to_sfen) still lists the moves from the initial position,
+    /// which don't lead to the new side to move; use [`to_sfen_current`](#method.to_sfen_current) to get the edited position.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::{Color, Position};
+    /// use shogi::bitboard::Factory as BBFactory;
+    ///
+    /// BBFactory::init();
+    /// let mut pos = Position::initial();
+    /// pos.play_usi_moves("7g7f").unwrap();
+    /// pos.set_side_to_move(Color::Black);
+    ///
+    /// assert_eq!(Color::Black, pos.side_to_move());
+    /// assert_eq!(1, pos.move_history().len());
+    /// assert_eq!("lnsgkgsnl/1r5b1/ppppppppp/9/9/2P6/PP1PPPPPP/1B5R1/LNSGKGSNL b - 2", pos.to_sfen_current());
+    /// ```
+    pub fn set_side_to_move(&mut self, c: Color) {
+        self.side_to_move = c;
+
+        if self.hash_history.pop().is_some() {
+            self.log_position();
+        }
+    }
+
+    /// Returns the ply number of the current state, as found in the move count field of SFEN.
+    ///
+    /// This counts half-moves: it starts from 1 and is incremented by every move of either player.
+    /// See [`move_number`](#method.move_number) for the number of full moves.
     pub fn ply(&self) -> u16 {
         self.ply
     }
 
+    /// Returns the 1-based number of the full move in progress, where a full move consists of two plies.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::Position;
+    /// use shogi::bitboard::Factory as BBFactory;
+    ///
+    /// BBFactory::init();
+    /// let mut pos = Position::initial();
+    /// assert_eq!((1, 1), (pos.ply(), pos.move_number()));
+    ///
+    /// pos.play_usi_moves("7g7f").unwrap();
+    /// assert_eq!((2, 1), (pos.ply(), pos.move_number()));
+    ///
+    /// pos.play_usi_moves("3c3d").unwrap();
+    /// assert_eq!((3, 2), (pos.ply(), pos.move_number()));
+    /// ```
+    pub fn move_number(&self) -> u16 {
+        self.ply.div_ceil(2)
+    }
+
     /// Returns a history of all moves made since the beginning of the game.
+    ///
+    /// Null moves made by [`make_null_move`](#method.make_null_move) are not included.
     pub fn move_history(&self) -> &[MoveRecord] {
         &self.move_history
     }
 
+    /// Returns the piece captured by the last move, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::{Move, Piece, Position};
+    /// use shogi::bitboard::Factory as BBFactory;
+    /// use shogi::square::consts::*;
+    ///
+    /// BBFactory::init();
+    /// let mut pos = Position::new();
+    /// pos.set_sfen("4k4/9/4p4/9/9/9/9/4R4/4K4 b - 1").unwrap();
+    /// assert_eq!(None, pos.last_capture());
+    ///
+    /// pos.make_move(Move::Normal{from: SQ_5H, to: SQ_5C, promote: false}).unwrap();
+    /// assert_eq!(Some(Piece::from_sfen('p').unwrap()), pos.last_capture());
+    /// ```
+    pub fn last_capture(&self) -> Option<Piece> {
+        if self.last_is_null_move() {
+            return None;
+        }
+
+        match self.move_history.last() {
+            Some(&MoveRecord::Normal { captured, .. }) => captured,
+            _ => None,
+        }
+    }
+
+    /// Returns a copy of the current state without the history.
+    ///
+    /// Unlike `clone`, the move history and the positions recorded for repetition detection are not copied.
+    /// The copy starts recording them from the current state, as if it were set by [`set_sfen`](#method.set_sfen).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::Position;
+    /// use shogi::bitboard::Factory as BBFactory;
+    ///
+    /// BBFactory::init();
+    /// let mut pos = Position::initial();
+    /// pos.play_usi_moves("7g7f 3c3d").unwrap();
+    ///
+    /// let snapshot = pos.snapshot();
+    /// assert_eq!(pos, snapshot);
+    /// assert!(snapshot.move_history().is_empty());
+    /// assert_eq!("lnsgkgsnl/1r5b1/pppppp1pp/6p2/9/2P6/PP1PPPPPP/1B5R1/LNSGKGSNL b - 3", snapshot.to_sfen());
+    /// ```
+    pub fn snapshot(&self) -> Position {
+        let mut pos = Position {
+            board: self.board.clone(),
+            hand: self.hand.clone(),
+            ply: self.ply,
+            side_to_move: self.side_to_move,
+            move_history: Vec::new(),
+            null_moves: Vec::new(),
+            start_sfen: String::new(),
+            hash_history: Vec::new(),
+            occupied_bb: self.occupied_bb,
+            color_bb: self.color_bb,
+            type_bb: self.type_bb,
+            king_sq: self.king_sq,
+        };
+        pos.reset_history();
+        pos
+    }
+
     /// Checks if a player with the given color can declare winning.
     ///
     /// See [the section 25 in 世界コンピュータ将棋選手権 大会ルール][csa] for more detail.
@@ -168,7 +739,7 @@ impl Position {
             return false;
         }
 
-        let king_pos = self.find_king(c);
+        let king_pos = self.king_square(c);
         if king_pos.is_none() {
             return false;
         }
@@ -190,8 +761,7 @@ impl Position {
                         _ => 1,
                     };
 
-                    let bb = &(&self.type_bb[pt.index()] & &self.color_bb[c.index()])
-                        & &BBFactory::promote_zone(c);
+                    let bb = &self.pieces(c, pt) & &BBFactory::promote_zone(c);
                     let count = bb.count() as u8;
                     let point = count * unit;
 
@@ -234,34 +804,113 @@ impl Position {
 
     /// Checks if the king with the given color is in check.
     pub fn in_check(&self, c: Color) -> bool {
-        if let Some(king_sq) = self.find_king(c) {
+        if let Some(king_sq) = self.king_square(c) {
             self.is_attacked_by(king_sq, c.flip())
         } else {
             false
         }
     }
 
+    /// Returns a bitboard of the opponent's pieces giving check to the king with the given color.
+    ///
+    /// The bitboard is empty if the king is not in check or there is no king.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::{Color, Position};
+    /// use shogi::bitboard::Factory as BBFactory;
+    /// use shogi::square::consts::*;
+    ///
+    /// BBFactory::init();
+    /// let mut pos = Position::new();
+    /// pos.set_sfen("4k4/9/9/9/4R3B/9/9/9/4K4 w - 1").unwrap();
+    ///
+    /// let checkers = pos.checkers(Color::White);
+    /// assert_eq!(vec![SQ_1E, SQ_5E], checkers.squares().collect::<Vec<_>>());
+    /// ```
+    pub fn checkers(&self, c: Color) -> Bitboard {
+        match self.king_square(c) {
+            Some(king_sq) => self.attackers_to(king_sq, c.flip(), &self.occupied_bb),
+            None => Bitboard::empty(),
+        }
+    }
+
+    /// Returns the square of the king with the given color.
+    ///
+    /// Returns `None` if there is no such king on the board, as in some tsume positions.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::{Color, Position};
+    /// use shogi::bitboard::Factory as BBFactory;
+    /// use shogi::square::consts::*;
+    ///
+    /// BBFactory::init();
+    /// let mut pos = Position::new();
+    /// pos.set_sfen("4k4/9/9/9/9/9/9/9/9 b R 1").unwrap();
+    ///
+    /// assert_eq!(None, pos.king_square(Color::Black));
+    /// assert_eq!(Some(SQ_5A), pos.king_square(Color::White));
+    /// ```
+    pub fn king_square(&self, c: Color) -> Option<Square> {
+        let sq = self.king_sq[c.index()];
+        debug_assert!(
+            self.pieces(c, PieceType::King).count() > 1
+                || sq == self.pieces(c, PieceType::King).first(),
+            "the cached king square is out of sync"
+        );
+        sq
+    }
+
     /// Returns the position of the king with the given color.
+    ///
+    /// This is the same as [`king_square`](Position::king_square).
     pub fn find_king(&self, c: Color) -> Option<Square> {
-        let mut bb = &self.type_bb[PieceType::King.index()] & &self.color_bb[c.index()];
-        if bb.is_any() {
-            Some(bb.pop())
-        } else {
-            None
-        }
+        self.king_square(c)
     }
 
-    /// Sets a piece at the given square.
+    /// Sets a piece at the given square, keeping track of the king squares.
     fn set_piece(&mut self, sq: Square, p: Option<Piece>) {
+        if let Some(old) = *self.piece_at(sq) {
+            if old.piece_type == PieceType::King && self.king_sq[old.color.index()] == Some(sq) {
+                self.king_sq[old.color.index()] = None;
+            }
+        }
+        if let Some(pc) = p {
+            if pc.piece_type == PieceType::King {
+                self.king_sq[pc.color.index()] = Some(sq);
+            }
+        }
+
         self.board.set(sq, p);
     }
 
+    /// Places a piece at the given empty square, updating bitboards as well.
+    fn place_piece(&mut self, sq: Square, pc: Piece) {
+        self.set_piece(sq, Some(pc));
+        self.occupied_bb |= sq;
+        self.color_bb[pc.color.index()] |= sq;
+        self.type_bb[pc.piece_type.index()] |= sq;
+    }
+
+    /// Removes a piece at the given square if any, updating bitboards as well.
+    fn remove_piece(&mut self, sq: Square) {
+        if let Some(pc) = *self.piece_at(sq) {
+            self.set_piece(sq, None);
+            self.occupied_bb ^= sq;
+            self.color_bb[pc.color.index()] ^= sq;
+            self.type_bb[pc.piece_type.index()] ^= sq;
+        }
+    }
+
     fn is_attacked_by(&self, sq: Square, c: Color) -> bool {
         PieceType::iter().any(|pt| self.get_attackers_of_type(pt, sq, c).is_any())
     }
 
     fn get_attackers_of_type(&self, pt: PieceType, sq: Square, c: Color) -> Bitboard {
-        let bb = &self.type_bb[pt.index()] & &self.color_bb[c.index()];
+        let bb = self.pieces(c, pt);
 
         if bb.is_empty() {
             return bb;
@@ -275,15 +924,22 @@ impl Position {
         &bb & &self.move_candidates(sq, attack_pc.flip())
     }
 
+    /// Clears the history and starts recording positions from the current state.
+    fn reset_history(&mut self) {
+        self.move_history.clear();
+        self.null_moves.clear();
+        self.start_sfen = self.generate_sfen().split(' ').take(3).join(" ");
+        self.hash_history.clear();
+        self.log_position();
+    }
+
     fn log_position(&mut self) {
-        // TODO: SFEN string is used to represent a state of position, but any transformation which uniquely distinguish positions can be used here.
-        // Consider light-weight option if generating SFEN string for each move is time-consuming.
-        let sfen = self.generate_sfen().split(' ').take(3).join(" ");
+        let hash = self.zobrist_hash();
         let in_check = self.in_check(self.side_to_move());
 
         let continuous_check = if in_check {
-            let past = if self.sfen_history.len() >= 2 {
-                let record = self.sfen_history.get(self.sfen_history.len() - 2).unwrap();
+            let past = if self.hash_history.len() >= 2 {
+                let record = self.hash_history.get(self.hash_history.len() - 2).unwrap();
                 record.1
             } else {
                 0
@@ -293,7 +949,38 @@ impl Position {
             0
         };
 
-        self.sfen_history.push((sfen, continuous_check));
+        self.hash_history.push((hash, continuous_check));
+    }
+
+    /// Computes a Zobrist hash of the board, the side to move and pieces in hand.
+    ///
+    /// Pieces in hand are hashed additively by their numbers.
+    /// The number of plies and the history do not contribute to the hash.
+    fn zobrist_hash(&self) -> u64 {
+        let mut hash = if self.side_to_move == Color::White {
+            ZOBRIST.side
+        } else {
+            0
+        };
+
+        for sq in self.occupied_bb {
+            if let Some(pc) = *self.piece_at(sq) {
+                hash ^= ZOBRIST.board[sq.index()][pc.piece_type.index()][pc.color.index()];
+            }
+        }
+
+        for c in Color::iter() {
+            for pt in PieceType::iter().filter(|pt| pt.is_hand_piece()) {
+                let n = self.hand.get(Piece {
+                    piece_type: pt,
+                    color: c,
+                });
+                hash =
+                    hash.wrapping_add(ZOBRIST.hand[c.index()][pt.index()].wrapping_mul(n as u64));
+            }
+        }
+
+        hash
     }
 
     /////////////////////////////////////////////////////////////////////////
@@ -302,9 +989,117 @@ impl Position {
 
     /// Makes the given move. Returns `Err` if the move is invalid or any special condition is met.
     pub fn make_move(&mut self, m: Move) -> Result<(), MoveError> {
+        self.apply_move(m, true)
+    }
+
+    /// Makes the given move without recording the position for repetition detection.
+    ///
+    /// This is cheaper than [`make_move`](#method.make_move) and intended for search code.
+    /// The move is still validated, but repetition rules such as sennichite and perpetual check never fire in this mode.
+    /// A move made by this method must be undone by [`unmake_move_fast`](#method.unmake_move_fast),
+    /// and mixing both modes in a single line of moves leaves the repetition history inconsistent.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::{Move, Position};
+    /// use shogi::bitboard::Factory as BBFactory;
+    ///
+    /// BBFactory::init();
+    /// let mut pos = Position::initial();
+    /// let sfen = pos.to_sfen();
+    ///
+    /// pos.make_move_fast(Move::from_sfen("7g7f").unwrap()).unwrap();
+    /// pos.unmake_move_fast().unwrap();
+    /// assert_eq!(sfen, pos.to_sfen());
+    /// ```
+    pub fn make_move_fast(&mut self, m: Move) -> Result<(), MoveError> {
+        self.apply_move(m, false)
+    }
+
+    /// Makes the given moves in order.
+    ///
+    /// On failure, returns the index of the failing move along with the error.
+    /// Moves preceding the failing one stay applied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::{Move, Position};
+    /// use shogi::bitboard::Factory as BBFactory;
+    ///
+    /// BBFactory::init();
+    /// let mut pos = Position::initial();
+    /// let moves = ["7g7f", "3c3d", "3d3e"].map(|s| Move::from_sfen(s).unwrap());
+    ///
+    /// assert_eq!(Some(2), pos.apply_moves(&moves).err().map(|(i, _)| i));
+    /// assert_eq!(2, pos.move_history().len());
+    /// ```
+    pub fn apply_moves(&mut self, moves: &[Move]) -> Result<(), (usize, MoveError)> {
+        for (i, &m) in moves.iter().enumerate() {
+            self.make_move(m).map_err(|e| (i, e))?;
+        }
+
+        Ok(())
+    }
+
+    /// Makes moves given as a whitespace-separated list of USI move notations.
+    ///
+    /// A leading `moves` keyword is skipped, so the tail of a USI `position` command can be passed as is.
+    /// Moves preceding the failing one stay applied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::Position;
+    /// use shogi::bitboard::Factory as BBFactory;
+    ///
+    /// BBFactory::init();
+    /// let mut pos = Position::initial();
+    /// pos.play_usi_moves("moves 7g7f 3c3d").unwrap();
+    ///
+    /// assert_eq!("lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1 moves 7g7f 3c3d", pos.to_sfen());
+    /// ```
+    pub fn play_usi_moves(&mut self, s: &str) -> Result<(), SfenError> {
+        let mut parts = s.split_whitespace().peekable();
+        if let Some(&"moves") = parts.peek() {
+            parts.next();
+        }
+
+        for (index, token) in parts.enumerate() {
+            if token == NULL_MOVE_SFEN {
+                self.make_null_move();
+                continue;
+            }
+
+            let m = Move::from_sfen(token).ok_or(SfenError::IllegalMove)?;
+            self.make_move(m)
+                .map_err(|error| SfenError::MoveFailed { index, error })?;
+        }
+
+        Ok(())
+    }
+
+    /// Makes the given move like [`make_move`](#method.make_move), but undoes it if the repetition rules reject it,
+    /// so that the game state is left as it was before the move on any error.
+    fn make_move_or_revert(&mut self, m: Move) -> Result<(), MoveError> {
+        self.apply_move(m, false)?;
+        self.log_position();
+
+        if let Err(e) = self.detect_repetition() {
+            self.undo_move(true)?;
+            return Err(e);
+        }
+
+        Ok(())
+    }
+
+    fn apply_move(&mut self, m: Move, record: bool) -> Result<(), MoveError> {
         let res = match m {
-            Move::Normal { from, to, promote } => self.make_normal_move(from, to, promote)?,
-            Move::Drop { to, piece_type } => self.make_drop_move(to, piece_type)?,
+            Move::Normal { from, to, promote } => {
+                self.make_normal_move(from, to, promote, record)?
+            }
+            Move::Drop { to, piece_type } => self.make_drop_move(to, piece_type, record)?,
         };
 
         self.move_history.push(res);
@@ -316,6 +1111,7 @@ impl Position {
         from: Square,
         to: Square,
         promoted: bool,
+        record: bool,
     ) -> Result<MoveRecord, MoveError> {
         let stm = self.side_to_move();
         let opponent = stm.flip();
@@ -403,8 +1199,10 @@ impl Position {
         self.side_to_move = opponent;
         self.ply += 1;
 
-        self.log_position();
-        self.detect_repetition()?;
+        if record {
+            self.log_position();
+            self.detect_repetition()?;
+        }
 
         Ok(MoveRecord::Normal {
             from,
@@ -415,7 +1213,12 @@ impl Position {
         })
     }
 
-    fn make_drop_move(&mut self, to: Square, pt: PieceType) -> Result<MoveRecord, MoveError> {
+    fn make_drop_move(
+        &mut self,
+        to: Square,
+        pt: PieceType,
+        record: bool,
+    ) -> Result<MoveRecord, MoveError> {
         let stm = self.side_to_move();
         let opponent = stm.flip();
 
@@ -437,56 +1240,12 @@ impl Position {
         }
 
         if pc.piece_type == PieceType::Pawn {
-            // Nifu check.
-            for i in 0..9 {
-                if let Some(fp) = *self.piece_at(Square::new(to.file(), i).unwrap()) {
-                    if fp == pc {
-                        return Err(MoveError::Nifu);
-                    }
-                }
+            if self.is_nifu(stm, to.file()) {
+                return Err(MoveError::Nifu);
             }
 
-            // Uchifuzume check.
-            if let Some(king_sq) = to.shift(0, if stm == Color::Black { -1 } else { 1 }) {
-                // Is the dropped pawn attacking the opponent's king?
-                if let Some(
-                    pc @ Piece {
-                        piece_type: PieceType::King,
-                        ..
-                    },
-                ) = *self.piece_at(king_sq)
-                {
-                    if pc.color == opponent {
-                        // can any opponent's piece attack the dropped pawn?
-                        let pinned = self.pinned_bb(opponent);
-
-                        let not_attacked = PieceType::iter()
-                            .filter(|&pt| pt != PieceType::King)
-                            .flat_map(|pt| self.get_attackers_of_type(pt, to, opponent))
-                            .all(|sq| (&pinned & sq).is_any());
-
-                        if not_attacked {
-                            // the dropped pawn may block bishop's moves
-                            self.occupied_bb ^= to;
-                            // can the opponent's king evade?
-                            let is_attacked = |sq| {
-                                if let Some(pc) = *self.piece_at(sq) {
-                                    if pc.color == opponent {
-                                        return true;
-                                    }
-                                }
-
-                                self.is_attacked_by(sq, stm)
-                            };
-                            let uchifuzume = self.move_candidates(king_sq, pc).all(is_attacked);
-                            self.occupied_bb ^= to;
-
-                            if uchifuzume {
-                                return Err(MoveError::Uchifuzume);
-                            }
-                        }
-                    }
-                }
+            if self.would_be_uchifuzume(to) {
+                return Err(MoveError::Uchifuzume);
             }
         }
 
@@ -508,15 +1267,115 @@ impl Position {
         self.side_to_move = opponent;
         self.ply += 1;
 
-        self.log_position();
-        self.detect_repetition()?;
+        if record {
+            self.log_position();
+            self.detect_repetition()?;
+        }
 
         Ok(MoveRecord::Drop { to, piece: pc })
     }
 
+    /// Checks if a pawn of the given color is already on the given file, i.e. dropping another pawn there is nifu.
+    ///
+    /// `file` is zero-based as returned by [`Square::file`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::{Color, Position};
+    /// use shogi::bitboard::Factory as BBFactory;
+    /// use shogi::square::consts::*;
+    ///
+    /// BBFactory::init();
+    /// let pos = Position::initial();
+    ///
+    /// assert!(pos.is_nifu(Color::Black, SQ_7G.file()));
+    ///
+    /// let mut pos = Position::new();
+    /// pos.set_sfen("4k4/9/9/9/9/9/4+P4/9/4K4 b P 1").unwrap();
+    /// assert!(!pos.is_nifu(Color::Black, SQ_5G.file()));
+    /// ```
+    pub fn is_nifu(&self, c: Color, file: u8) -> bool {
+        let pawn = Piece {
+            piece_type: PieceType::Pawn,
+            color: c,
+        };
+
+        Square::file_iter(file).any(|sq| *self.piece_at(sq) == Some(pawn))
+    }
+
+    /// Checks if dropping a pawn of the side to move at the given square checkmates the opponent, i.e. the drop is uchifuzume.
+    ///
+    /// The square is assumed to be empty. Whether the side to move has a pawn in hand is not checked.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::Position;
+    /// use shogi::bitboard::Factory as BBFactory;
+    /// use shogi::square::consts::*;
+    ///
+    /// BBFactory::init();
+    /// let mut pos = Position::new();
+    /// pos.set_sfen("kn7/9/1G7/9/9/9/9/9/4K4 b P 1").unwrap();
+    ///
+    /// assert!(pos.would_be_uchifuzume(SQ_9B));
+    /// assert!(!pos.would_be_uchifuzume(SQ_9C));
+    /// ```
+    pub fn would_be_uchifuzume(&self, to: Square) -> bool {
+        let stm = self.side_to_move();
+        let opponent = stm.flip();
+
+        // Is the dropped pawn attacking the opponent's king?
+        let king_sq = match to.shift(0, stm.forward()) {
+            Some(sq) => sq,
+            None => return false,
+        };
+        if self.king_square(opponent) != Some(king_sq) {
+            return false;
+        }
+
+        // The dropped pawn may block sliders' moves.
+        let occupied = &self.occupied_bb | to;
+
+        // Can any opponent's piece capture the dropped pawn without exposing the king?
+        for from in self.attackers_to(to, opponent, &occupied) {
+            if from != king_sq
+                && self
+                    .attackers_to(king_sq, stm, &(&occupied ^ from))
+                    .is_empty()
+            {
+                return false;
+            }
+        }
+
+        // Can the opponent's king evade?
+        let king_moves = &BBFactory::attacks_from(PieceType::King, opponent, king_sq)
+            & &!&self.color_bb[opponent.index()];
+        let occupied = &occupied ^ king_sq;
+
+        king_moves
+            .into_iter()
+            .all(|sq| self.attackers_to(sq, stm, &occupied).is_any())
+    }
+
     /// Returns a list of squares at which a piece of the given color is pinned.
     pub fn pinned_bb(&self, c: Color) -> Bitboard {
-        let ksq = self.find_king(c);
+        self.blockers_bb(c, c)
+    }
+
+    /// Returns a list of squares at which a piece of the given color blocks
+    /// its own slider from attacking the opponent king.
+    ///
+    /// Moving any of these pieces off the line gives a discovered check.
+    pub fn discovered_check_candidates(&self, attacker: Color) -> Bitboard {
+        self.blockers_bb(attacker.flip(), attacker)
+    }
+
+    /// Returns a list of squares at which a piece of `blocker` is the only piece
+    /// standing between the king of `king_color` and an enemy slider.
+    fn blockers_bb(&self, king_color: Color, blocker: Color) -> Bitboard {
+        let ksq = self.king_square(king_color);
         if ksq.is_none() {
             return Bitboard::empty();
         }
@@ -541,16 +1400,16 @@ impl Position {
             ),
             (
                 PieceType::Lance,
-                BBFactory::lance_attack(c, ksq, &Bitboard::empty()),
+                BBFactory::lance_attack(king_color, ksq, &Bitboard::empty()),
             ),
         ]
         .iter()
         .fold(Bitboard::empty(), |mut accum, &(pt, ref mask)| {
-            let bb = &(&self.type_bb[pt.index()] & &self.color_bb[c.flip().index()]) & mask;
+            let bb = &self.pieces(king_color.flip(), pt) & mask;
 
             for psq in bb {
                 let between = &BBFactory::between(ksq, psq) & &self.occupied_bb;
-                if between.count() == 1 && (&between & &self.color_bb[c.index()]).is_any() {
+                if between.count() == 1 && (&between & &self.color_bb[blocker.index()]).is_any() {
                     accum |= &between;
                 }
             }
@@ -561,6 +1420,73 @@ impl Position {
 
     /// Undoes the last move.
     pub fn unmake_move(&mut self) -> Result<(), MoveError> {
+        self.undo_move(true)
+    }
+
+    /// Undoes the last move made by [`make_move_fast`](#method.make_move_fast).
+    pub fn unmake_move_fast(&mut self) -> Result<(), MoveError> {
+        self.undo_move(false)
+    }
+
+    /// Passes the turn to the opponent without moving any piece, as used in null-move pruning.
+    ///
+    /// No legality is checked, so it is the caller's responsibility not to make a null move while in check.
+    /// The null move is recorded in the history, so that moves can be made after it and the position is
+    /// logged for repetition detection. It is written as `pass` by [`to_sfen`](#method.to_sfen), which is also accepted
+    /// by [`set_sfen`](#method.set_sfen), and as `--` by [`to_psn`](#method.to_psn).
+    /// It is not listed in [`move_history`](#method.move_history), and must be undone by
+    /// [`unmake_null_move`](#method.unmake_null_move) before undoing any preceding move.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::{Color, Position};
+    /// use shogi::bitboard::Factory as BBFactory;
+    ///
+    /// BBFactory::init();
+    /// let mut pos = Position::initial();
+    ///
+    /// pos.make_null_move();
+    /// assert_eq!(Color::White, pos.side_to_move());
+    /// assert_eq!(2, pos.ply());
+    ///
+    /// pos.unmake_null_move().unwrap();
+    /// assert_eq!(Color::Black, pos.side_to_move());
+    /// assert_eq!(1, pos.ply());
+    /// ```
+    pub fn make_null_move(&mut self) {
+        self.null_moves.push(self.move_history.len());
+        self.side_to_move = self.side_to_move.flip();
+        self.ply += 1;
+        self.log_position();
+    }
+
+    /// Undoes the null move made by [`make_null_move`](#method.make_null_move).
+    ///
+    /// Returns `Err` if the last move is not a null move.
+    pub fn unmake_null_move(&mut self) -> Result<(), MoveError> {
+        if !self.last_is_null_move() {
+            return Err(MoveError::Inconsistent("The last move is not a null move"));
+        }
+
+        self.null_moves.pop();
+        self.side_to_move = self.side_to_move.flip();
+        self.ply -= 1;
+        self.hash_history.pop();
+
+        Ok(())
+    }
+
+    /// Checks if the last move is a null move.
+    fn last_is_null_move(&self) -> bool {
+        self.null_moves.last() == Some(&self.move_history.len())
+    }
+
+    fn undo_move(&mut self, record: bool) -> Result<(), MoveError> {
+        if self.last_is_null_move() {
+            return Err(MoveError::Inconsistent("The last move is a null move"));
+        }
+
         if self.move_history.is_empty() {
             // TODO: error?
             return Ok(());
@@ -627,51 +1553,125 @@ impl Position {
             }
         };
 
-        self.side_to_move = self.side_to_move.flip();
+        self.side_to_move = last.placed_piece().color;
         self.ply -= 1;
-        self.sfen_history.pop();
+        if record {
+            self.hash_history.pop();
+        }
 
         Ok(())
     }
 
     /// Returns a list of squares to where the given piece at the given square can move.
     pub fn move_candidates(&self, sq: Square, p: Piece) -> Bitboard {
-        let bb = match p.piece_type {
-            PieceType::Rook => BBFactory::rook_attack(sq, &self.occupied_bb),
-            PieceType::Bishop => BBFactory::bishop_attack(sq, &self.occupied_bb),
-            PieceType::Lance => BBFactory::lance_attack(p.color, sq, &self.occupied_bb),
-            PieceType::ProRook => {
-                &BBFactory::rook_attack(sq, &self.occupied_bb)
-                    | &BBFactory::attacks_from(PieceType::King, p.color, sq)
-            }
-            PieceType::ProBishop => {
-                &BBFactory::bishop_attack(sq, &self.occupied_bb)
-                    | &BBFactory::attacks_from(PieceType::King, p.color, sq)
-            }
-            PieceType::ProSilver
-            | PieceType::ProKnight
-            | PieceType::ProLance
-            | PieceType::ProPawn => BBFactory::attacks_from(PieceType::Gold, p.color, sq),
-            pt => BBFactory::attacks_from(pt, p.color, sq),
-        };
+        let bb = piece_attacks(sq, p, &self.occupied_bb);
 
         &bb & &!&self.color_bb[p.color.index()]
     }
 
+    /// Returns the total number of squares to where pieces of the given color on the board can move.
+    ///
+    /// Each destination is counted once per piece regardless of promotion. Drops are not counted,
+    /// and moves leaving the king in check are counted as well.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::{Color, Position};
+    /// use shogi::bitboard::Factory as BBFactory;
+    ///
+    /// BBFactory::init();
+    /// let pos = Position::initial();
+    ///
+    /// assert_eq!(30, pos.mobility(Color::Black));
+    /// ```
+    pub fn mobility(&self, c: Color) -> u32 {
+        self.mobility_by_type(c).iter().sum()
+    }
+
+    /// Returns the number of squares to where pieces of the given color on the board can move, for each piece type.
+    ///
+    /// The result is indexed by [`PieceType::index`]. See [`mobility`](#method.mobility) for how squares are counted.
+    pub fn mobility_by_type(&self, c: Color) -> [u32; PieceType::NUM] {
+        let mut counts = [0; PieceType::NUM];
+        for sq in self.color_bb[c.index()] {
+            let pc = self.piece_at(sq).unwrap();
+            counts[pc.piece_type.index()] += self.move_candidates(sq, pc).count() as u32;
+        }
+
+        counts
+    }
+
+    /// Returns a bitboard of the pieces with the given color attacking the given square,
+    /// as if the board is occupied as `occupied`.
+    fn attackers_to(&self, sq: Square, c: Color, occupied: &Bitboard) -> Bitboard {
+        PieceType::iter().fold(Bitboard::empty(), |mut accum, pt| {
+            let bb = self.pieces(c, pt);
+
+            if bb.is_any() {
+                let pc = Piece {
+                    piece_type: pt,
+                    color: c.flip(),
+                };
+                accum |= &(&bb & &piece_attacks(sq, pc, occupied));
+            }
+
+            accum
+        })
+    }
+
+    /// Checks if the given position has the same board, pieces in hand and side to move.
+    ///
+    /// This is the equality used by the repetition rule. Unlike `==`, the ply is ignored as well as the history.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::Position;
+    /// use shogi::bitboard::Factory as BBFactory;
+    ///
+    /// BBFactory::init();
+    /// let mut pos = Position::initial();
+    /// pos.play_usi_moves("5i5h 5a5b 5h5i 5b5a").unwrap();
+    ///
+    /// assert!(pos.same_position(&Position::initial()));
+    /// assert!(pos != Position::initial());
+    /// ```
+    pub fn same_position(&self, other: &Position) -> bool {
+        self.board == other.board
+            && self.hand == other.hand
+            && self.side_to_move == other.side_to_move
+    }
+
+    /// Returns how many times the current position has appeared so far, including the current one.
+    ///
+    /// Positions are compared by the board, the side to move and pieces in hand, as the repetition rule does.
+    /// Returns 0 if no position has been set yet.
+    pub fn repetition_count(&self) -> usize {
+        match self.hash_history.last() {
+            Some(cur) => self
+                .hash_history
+                .iter()
+                .filter(|entry| entry.0 == cur.0)
+                .count(),
+            None => 0,
+        }
+    }
+
     fn detect_repetition(&self) -> Result<(), MoveError> {
-        if self.sfen_history.len() < 9 {
+        if self.hash_history.len() < 9 {
             return Ok(());
         }
 
-        let cur = self.sfen_history.last().unwrap();
+        let cur = self.hash_history.last().unwrap();
 
         let mut cnt = 0;
-        for (i, entry) in self.sfen_history.iter().rev().enumerate() {
+        for (i, entry) in self.hash_history.iter().rev().enumerate() {
             if entry.0 == cur.0 {
                 cnt += 1;
 
                 if cnt == 4 {
-                    let prev = self.sfen_history.get(self.sfen_history.len() - 2).unwrap();
+                    let prev = self.hash_history.get(self.hash_history.len() - 2).unwrap();
 
                     if cur.1 * 2 >= (i as u16) {
                         return Err(MoveError::PerpetualCheckLose);
@@ -688,1114 +1688,4335 @@ impl Position {
     }
 
     /////////////////////////////////////////////////////////////////////////
-    // SFEN serialization / deserialization
+    // Move generation
     /////////////////////////////////////////////////////////////////////////
 
-    /// Parses the given SFEN string and updates the game state.
-    pub fn set_sfen(&mut self, sfen_str: &str) -> Result<(), SfenError> {
-        let mut parts = sfen_str.split_whitespace();
-
-        // Build the initial position, all parts are required.
-        parts
-            .next()
-            .ok_or(SfenError::MissingDataFields)
-            .and_then(|s| self.parse_sfen_board(s))?;
-        parts
-            .next()
-            .ok_or(SfenError::MissingDataFields)
-            .and_then(|s| self.parse_sfen_stm(s))?;
-        parts
-            .next()
-            .ok_or(SfenError::MissingDataFields)
-            .and_then(|s| self.parse_sfen_hand(s))?;
-        parts
-            .next()
-            .ok_or(SfenError::MissingDataFields)
-            .and_then(|s| self.parse_sfen_ply(s))?;
-
-        self.sfen_history.clear();
-        self.log_position();
-
-        // Make moves following the initial position, optional.
-        if let Some("moves") = parts.next() {
-            for m in parts {
-                if let Some(m) = Move::from_sfen(m) {
-                    // Stop if any error occurrs.
-                    match self.make_move(m) {
-                        Ok(_) => {
-                            self.log_position();
-                        }
-                        Err(_) => break,
-                    }
-                } else {
-                    return Err(SfenError::IllegalMove);
-                }
-            }
-        }
-
-        Ok(())
+    /// Returns a list of all legal moves for the side to move.
+    ///
+    /// Moves leaving the king in check, nifu and uchifuzume are excluded.
+    /// Repetition is not taken into account.
+    pub fn legal_moves(&self) -> Vec<Move> {
+        let mut moves = Vec::new();
+        self.push_legal_moves(&mut moves);
+        moves
     }
 
-    /// Converts the current state into SFEN formatted string.
-    pub fn to_sfen(&self) -> String {
-        if self.sfen_history.is_empty() {
-            return self.generate_sfen();
-        }
+    /// Fills the given list with all legal moves for the side to move, without allocating on the heap.
+    ///
+    /// The list is cleared first. Generates the same moves as [`legal_moves`](#method.legal_moves).
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are more than [`MAX_MOVES`](crate::moves::MAX_MOVES) legal moves, which is only possible
+    /// in positions unreachable in a game, e.g. with extra promoted pieces set by SFEN. Use `legal_moves` for such positions.
+    pub fn generate_moves(&self, moves: &mut MoveList) {
+        moves.clear();
+        self.push_legal_moves(moves);
+    }
 
-        if self.move_history.is_empty() {
-            return format!("{} {}", self.sfen_history.first().unwrap().0, self.ply);
+    fn push_legal_moves<L: MoveSink>(&self, moves: &mut L) {
+        for from in self.color_bb[self.side_to_move().index()] {
+            self.push_normal_moves(from, &!&Bitboard::empty(), moves);
         }
+        self.push_drop_moves(&!&self.occupied_bb, moves);
+    }
 
-        let mut sfen = format!(
-            "{} {} moves",
-            &self.sfen_history.first().unwrap().0,
-            self.ply - self.move_history.len() as u16
-        );
+    /// Returns a list of legal moves capturing an opponent's piece.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::{Move, Position};
+    /// use shogi::bitboard::Factory as BBFactory;
+    /// use shogi::square::consts::*;
+    ///
+    /// BBFactory::init();
+    /// let mut pos = Position::new();
+    /// pos.set_sfen("lnsgkgsnl/1r5b1/pppppp1pp/6p2/9/2P6/PP1PPPPPP/1B5R1/LNSGKGSNL b - 1").unwrap();
+    ///
+    /// let captures = pos.captures();
+    /// assert_eq!(2, captures.len());
+    /// assert!(captures.contains(&Move::Normal{from: SQ_8H, to: SQ_2B, promote: true}));
+    /// ```
+    pub fn captures(&self) -> Vec<Move> {
+        let stm = self.side_to_move();
+        let mut moves = Vec::new();
 
-        for m in self.move_history.iter() {
-            let _ = write!(sfen, " {}", &m.to_sfen());
+        for from in self.color_bb[stm.index()] {
+            self.push_normal_moves(from, &self.color_bb[stm.flip().index()], &mut moves);
         }
 
-        sfen
+        moves
     }
 
-    fn parse_sfen_board(&mut self, s: &str) -> Result<(), SfenError> {
-        let rows = s.split('/');
-
-        self.occupied_bb = Bitboard::empty();
-        self.color_bb = Default::default();
-        self.type_bb = Default::default();
-
-        for (i, row) in rows.enumerate() {
-            if i >= 9 {
-                return Err(SfenError::IllegalBoardState);
-            }
-
-            let mut j = 0;
+    /// Returns a list of legal moves not capturing any piece, including drop moves.
+    pub fn quiets(&self) -> Vec<Move> {
+        let empty = !&self.occupied_bb;
+        let mut moves = Vec::new();
 
-            let mut is_promoted = false;
-            for c in row.chars() {
-                match c {
-                    '+' => {
-                        is_promoted = true;
-                    }
-                    n if n.is_ascii_digit() => {
-                        if let Some(n) = n.to_digit(10) {
-                            for _ in 0..n {
-                                if j >= 9 {
-                                    return Err(SfenError::IllegalBoardState);
-                                }
+        for from in self.color_bb[self.side_to_move().index()] {
+            self.push_normal_moves(from, &empty, &mut moves);
+        }
+        self.push_drop_moves(&empty, &mut moves);
 
-                                let sq = Square::new(8 - j, i as u8).unwrap();
-                                self.set_piece(sq, None);
+        moves
+    }
 
-                                j += 1;
-                            }
-                        }
-                    }
-                    s => match Piece::from_sfen(s) {
-                        Some(mut piece) => {
-                            if j >= 9 {
-                                return Err(SfenError::IllegalBoardState);
-                            }
+    /// Returns a list of legal moves getting the king of the side to move out of check.
+    ///
+    /// Only king moves, captures of the checking piece and interpositions are considered,
+    /// which is cheaper than filtering `legal_moves`. In double check, only king moves are returned.
+    /// Returns the same moves as `legal_moves` if the side to move is not in check.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::Position;
+    /// use shogi::bitboard::Factory as BBFactory;
+    ///
+    /// BBFactory::init();
+    /// let mut pos = Position::new();
+    /// pos.set_sfen("4k4/9/9/9/4R3B/9/9/9/4K4 w - 1").unwrap();
+    ///
+    /// // Double check, only the king can move.
+    /// assert_eq!(3, pos.evasion_moves().len());
+    /// ```
+    pub fn evasion_moves(&self) -> Vec<Move> {
+        let stm = self.side_to_move();
+        let checkers = self.checkers(stm);
+        let king_sq = match self.king_square(stm) {
+            Some(sq) if checkers.is_any() => sq,
+            _ => return self.legal_moves(),
+        };
 
-                            if is_promoted {
-                                if let Some(promoted) = piece.piece_type.promote() {
-                                    piece.piece_type = promoted;
-                                } else {
-                                    return Err(SfenError::IllegalPieceType);
-                                }
-                            }
+        let mut moves = Vec::new();
+        self.push_normal_moves(king_sq, &!&self.color_bb[stm.index()], &mut moves);
 
-                            let sq = Square::new(8 - j, i as u8).unwrap();
-                            self.set_piece(sq, Some(piece));
-                            self.occupied_bb |= sq;
-                            self.color_bb[piece.color.index()] |= sq;
-                            self.type_bb[piece.piece_type.index()] |= sq;
-                            j += 1;
+        if let Some(checker_sq) = checkers.single() {
+            let between = BBFactory::between(king_sq, checker_sq);
+            let targets = &between | checker_sq;
 
-                            is_promoted = false;
-                        }
-                        None => return Err(SfenError::IllegalPieceType),
-                    },
-                }
+            let mut pieces = self.color_bb[stm.index()];
+            pieces.clear_at(king_sq);
+            for from in pieces {
+                self.push_normal_moves(from, &targets, &mut moves);
             }
+            self.push_drop_moves(&between, &mut moves);
         }
 
-        Ok(())
+        moves
     }
 
-    fn parse_sfen_stm(&mut self, s: &str) -> Result<(), SfenError> {
-        self.side_to_move = match s {
-            "b" => Color::Black,
-            "w" => Color::White,
-            _ => return Err(SfenError::IllegalSideToMove),
+    /// Returns the squares where the given piece type can be legally dropped by the side to move.
+    ///
+    /// Nifu, uchifuzume and drops leaving the king in check are excluded. Returns an empty bitboard
+    /// if the piece is not in hand of the side to move.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::{PieceType, Position};
+    /// use shogi::bitboard::Factory as BBFactory;
+    /// use shogi::square::consts::*;
+    ///
+    /// BBFactory::init();
+    /// let mut pos = Position::new();
+    /// pos.set_sfen("4k4/9/9/9/9/9/9/9/4K4 b P 1").unwrap();
+    ///
+    /// let drops = pos.legal_drops(PieceType::Pawn);
+    /// assert_eq!(71, drops.count());
+    /// assert!((&drops & SQ_5B).is_any());
+    /// assert!(pos.legal_drops(PieceType::Gold).is_empty());
+    /// ```
+    pub fn legal_drops(&self, pt: PieceType) -> Bitboard {
+        let pc = Piece {
+            piece_type: pt,
+            color: self.side_to_move(),
         };
-        Ok(())
+        if !pt.is_hand_piece() || self.hand(pc) == 0 {
+            return Bitboard::empty();
+        }
+
+        self.drop_targets(pc, &!&Bitboard::empty())
     }
 
-    fn parse_sfen_hand(&mut self, s: &str) -> Result<(), SfenError> {
-        if s == "-" {
-            self.hand.clear();
-            return Ok(());
+    /// Returns a list of legal moves of the piece at the given square.
+    ///
+    /// Both promoting and non-promoting moves are included where possible. Returns an empty list
+    /// if the square is empty or the piece does not belong to the side to move.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::{Move, Position};
+    /// use shogi::bitboard::Factory as BBFactory;
+    /// use shogi::square::consts::*;
+    ///
+    /// BBFactory::init();
+    /// let pos = Position::initial();
+    ///
+    /// assert_eq!(vec![Move::Normal{from: SQ_7G, to: SQ_7F, promote: false}], pos.legal_moves_from(SQ_7G));
+    /// assert!(pos.legal_moves_from(SQ_7C).is_empty());
+    /// ```
+    pub fn legal_moves_from(&self, sq: Square) -> Vec<Move> {
+        let mut moves = Vec::new();
+
+        if (&self.color_bb[self.side_to_move().index()] & sq).is_any() {
+            self.push_normal_moves(sq, &!&Bitboard::empty(), &mut moves);
         }
 
-        let mut num_pieces: u8 = 0;
-        for c in s.chars() {
-            match c {
-                n if n.is_ascii_digit() => {
-                    if let Some(n) = n.to_digit(10) {
-                        num_pieces = num_pieces * 10 + (n as u8);
-                    }
-                }
-                s => {
-                    match Piece::from_sfen(s) {
-                        Some(p) => self
-                            .hand
-                            .set(p, if num_pieces == 0 { 1 } else { num_pieces }),
-                        None => return Err(SfenError::IllegalPieceType),
-                    };
-                    num_pieces = 0;
+        moves
+    }
+
+    /// Checks if the piece moved by the given move can promote, i.e. the piece is promotable
+    /// and either the source or the destination is in the promotion zone of its owner.
+    ///
+    /// Returns `false` for drop moves and when there is no piece at the source square.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::{Move, Position};
+    /// use shogi::bitboard::Factory as BBFactory;
+    ///
+    /// BBFactory::init();
+    /// let mut pos = Position::new();
+    /// pos.set_sfen("4k4/9/9/2P6/9/9/9/9/4K4 b - 1").unwrap();
+    ///
+    /// assert!(pos.can_promote(&Move::from_sfen("7d7c").unwrap()));
+    /// assert!(!pos.can_promote(&Move::from_sfen("5i5h").unwrap()));
+    /// ```
+    pub fn can_promote(&self, m: &Move) -> bool {
+        match *m {
+            Move::Normal { from, to, .. } => match *self.piece_at(from) {
+                Some(pc) => {
+                    pc.promote().is_some()
+                        && (from.in_promotion_zone(pc.color) || to.in_promotion_zone(pc.color))
                 }
-            }
+                None => false,
+            },
+            Move::Drop { .. } => false,
         }
-
-        Ok(())
     }
 
-    fn parse_sfen_ply(&mut self, s: &str) -> Result<(), SfenError> {
-        self.ply = s.parse()?;
-        Ok(())
+    /// Checks if the piece moved by the given move must promote, i.e. it would have no square to move to
+    /// afterwards, as a pawn or a lance reaching the last rank or a knight reaching the last two ranks.
+    ///
+    /// Returns `false` for drop moves and when there is no piece at the source square.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::{Move, Position};
+    /// use shogi::bitboard::Factory as BBFactory;
+    ///
+    /// BBFactory::init();
+    /// let mut pos = Position::new();
+    /// pos.set_sfen("4k4/2P6/9/9/9/9/9/9/4K4 b - 1").unwrap();
+    ///
+    /// assert!(pos.can_promote(&Move::from_sfen("7b7a").unwrap()));
+    /// assert!(pos.must_promote(&Move::from_sfen("7b7a").unwrap()));
+    /// ```
+    pub fn must_promote(&self, m: &Move) -> bool {
+        match *m {
+            Move::Normal { from, to, .. } => match *self.piece_at(from) {
+                Some(pc) => pc.promote().is_some() && !pc.is_placeable_at(to),
+                None => false,
+            },
+            Move::Drop { .. } => false,
+        }
     }
 
-    fn generate_sfen(&self) -> String {
-        let board = (0..9)
-            .map(|row| {
-                let mut s = String::new();
-                let mut num_spaces = 0;
-                for file in (0..9).rev() {
-                    match *self.piece_at(Square::new(file, row).unwrap()) {
-                        Some(pc) => {
-                            if num_spaces > 0 {
-                                s.push_str(&num_spaces.to_string());
-                                num_spaces = 0;
-                            }
+    /// Returns the qualifier needed to tell the given move apart from moves of other pieces of the same kind to the same square.
+    ///
+    /// The direction of the movement (上, 引 or 寄) is preferred, then the relative position of the piece (左, 右 or 直),
+    /// and both are combined if neither is enough alone. 直 is not used for promoted rooks and bishops.
+    /// Only legal moves of other pieces are taken into account.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::{Disambiguation, Move, Position};
+    /// use shogi::bitboard::Factory as BBFactory;
+    /// use shogi::position::{Movement, Relative};
+    ///
+    /// BBFactory::init();
+    /// let pos = Position::initial();
+    ///
+    /// // ５八金右
+    /// let m = Move::from_sfen("4i5h").unwrap();
+    /// assert_eq!(Disambiguation::Relative(Relative::Right), pos.move_disambiguation(&m));
+    ///
+    /// let m = Move::from_sfen("7g7f").unwrap();
+    /// assert_eq!(Disambiguation::None, pos.move_disambiguation(&m));
+    /// ```
+    pub fn move_disambiguation(&self, m: &Move) -> Disambiguation {
+        let stm = self.side_to_move();
+        let can_reach = |sq: Square, pc: Piece, to: Square| {
+            (&self.move_candidates(sq, pc) & to).is_any()
+                && (pc.color != stm || !self.leaves_in_check(Some(sq), to))
+        };
 
-                            s.push_str(&pc.to_string());
-                        }
-                        None => num_spaces += 1,
-                    }
-                }
+        let (from, to) = match *m {
+            Move::Normal { from, to, .. } => (from, to),
+            Move::Drop { to, piece_type } => {
+                let pc = Piece {
+                    piece_type,
+                    color: stm,
+                };
+                return if self.pieces(stm, piece_type).any(|sq| can_reach(sq, pc, to)) {
+                    Disambiguation::Drop
+                } else {
+                    Disambiguation::None
+                };
+            }
+        };
 
-                if num_spaces > 0 {
-                    s.push_str(&num_spaces.to_string());
-                }
+        let pc = match *self.piece_at(from) {
+            Some(pc) => pc,
+            None => return Disambiguation::None,
+        };
 
-                s
-            })
-            .join("/");
+        let mut others = self.pieces(pc.color, pc.piece_type);
+        others.clear_at(from);
+        let others = others
+            .filter(|&sq| can_reach(sq, pc, to))
+            .collect::<Vec<_>>();
+        if others.is_empty() {
+            return Disambiguation::None;
+        }
 
-        let color = if self.side_to_move == Color::Black {
-            "b"
+        let movement_from = |sq: Square| {
+            let (rank, to_rank) = (sq.relative_rank(pc.color), to.relative_rank(pc.color));
+            if to_rank < rank {
+                Movement::Up
+            } else if to_rank > rank {
+                Movement::Down
+            } else {
+                Movement::Sideways
+            }
+        };
+        let movement = movement_from(from);
+        let same_movement = others
+            .iter()
+            .copied()
+            .filter(|&sq| movement_from(sq) == movement)
+            .collect::<Vec<_>>();
+        if same_movement.is_empty() {
+            return Disambiguation::Movement(movement);
+        }
+
+        // Larger values are on the left side of the player.
+        let leftness = |sq: Square| match pc.color {
+            Color::Black => sq.file(),
+            Color::White => 8 - sq.file(),
+        };
+        let is_leftmost = |sqs: &[Square]| sqs.iter().all(|&sq| leftness(sq) < leftness(from));
+        let is_rightmost = |sqs: &[Square]| sqs.iter().all(|&sq| leftness(sq) > leftness(from));
+
+        let straight = movement == Movement::Up
+            && from.file() == to.file()
+            && pc.piece_type != PieceType::ProRook
+            && pc.piece_type != PieceType::ProBishop;
+        if straight {
+            return Disambiguation::Relative(Relative::Straight);
+        }
+
+        let relative = if is_leftmost(&same_movement) {
+            Relative::Left
+        } else if is_rightmost(&same_movement) {
+            Relative::Right
         } else {
-            "w"
+            return Disambiguation::Movement(movement);
         };
 
-        let mut hand = [Color::Black, Color::White]
-            .iter()
-            .map(|c| {
-                PieceType::iter()
-                    .filter(|pt| pt.is_hand_piece())
-                    .map(|pt| {
-                        let pc = Piece {
-                            piece_type: pt,
-                            color: *c,
-                        };
-                        let n = self.hand.get(pc);
+        let enough = match relative {
+            Relative::Left => is_leftmost(&others),
+            _ => is_rightmost(&others),
+        };
+        if enough {
+            Disambiguation::Relative(relative)
+        } else {
+            Disambiguation::Both(relative, movement)
+        }
+    }
 
-                        if n == 0 {
-                            "".to_string()
-                        } else if n == 1 {
-                            format!("{pc}")
-                        } else {
-                            format!("{n}{pc}")
-                        }
-                    })
-                    .join("")
-            })
-            .join("");
+    /// Checks if the side to move is checkmated.
+    pub fn is_checkmate(&self) -> bool {
+        self.in_check(self.side_to_move()) && self.legal_moves().is_empty()
+    }
 
-        if hand.is_empty() {
-            hand = "-".to_string();
+    /// Classifies the outcome of the game at the current position.
+    ///
+    /// Returns `None` if the game is still ongoing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::{Color, Position};
+    /// use shogi::bitboard::Factory as BBFactory;
+    /// use shogi::position::GameResult;
+    ///
+    /// BBFactory::init();
+    /// let mut pos = Position::new();
+    /// pos.set_sfen("4k4/4G4/4P4/9/9/9/9/9/9 w - 1").unwrap();
+    ///
+    /// assert_eq!(Some(GameResult::Checkmate(Color::Black)), pos.game_result());
+    /// ```
+    pub fn game_result(&self) -> Option<GameResult> {
+        match self.detect_repetition() {
+            Err(MoveError::Repetition) => return Some(GameResult::Repetition),
+            Err(MoveError::PerpetualCheckWin) => return Some(GameResult::PerpetualCheckWin),
+            Err(MoveError::PerpetualCheckLose) => return Some(GameResult::PerpetualCheckLose),
+            _ => {}
         }
 
-        format!("{} {} {} {}", board, color, hand, self.ply)
+        if !self.legal_moves().is_empty() {
+            return None;
+        }
+
+        let winner = self.side_to_move.flip();
+        if self.in_check(self.side_to_move) {
+            Some(GameResult::Checkmate(winner))
+        } else {
+            Some(GameResult::Stalemate(winner))
+        }
     }
-}
 
-/////////////////////////////////////////////////////////////////////////////
-// Trait implementations
-/////////////////////////////////////////////////////////////////////////////
+    /// Returns a move which checkmates the opponent immediately, if any.
+    ///
+    /// Pawn drops are never returned as they would be uchifuzume.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::{Move, Position, PieceType};
+    /// use shogi::bitboard::Factory as BBFactory;
+    /// use shogi::square::consts::*;
+    ///
+    /// BBFactory::init();
+    /// let mut pos = Position::new();
+    /// pos.set_sfen("8k/9/8P/9/9/9/9/9/9 b G 1").unwrap();
+    ///
+    /// assert_eq!(Some(Move::Drop{to: SQ_1B, piece_type: PieceType::Gold}), pos.is_mate_in_one());
+    /// ```
+    pub fn is_mate_in_one(&self) -> Option<Move> {
+        let mut pos = self.clone();
+
+        self.legal_moves().into_iter().find(|&m| {
+            if pos.make_move(m).is_err() {
+                // Ended by the repetition rule, which leaves the board updated.
+                pos = self.clone();
+                return false;
+            }
 
-impl Default for Position {
-    fn default() -> Position {
-        Position {
-            side_to_move: Color::Black,
-            board: PieceGrid([None; 81]),
-            hand: Default::default(),
-            ply: 1,
-            move_history: Default::default(),
-            sfen_history: Default::default(),
-            occupied_bb: Default::default(),
-            color_bb: Default::default(),
-            type_bb: Default::default(),
+            let mate = pos.is_checkmate();
+            let res = pos.unmake_move();
+            debug_assert!(res.is_ok(), "failed to unmake a move: {m}");
+
+            mate
+        })
+    }
+
+    /// Estimates the material gain of the given move by static exchange evaluation.
+    ///
+    /// Both players are assumed to keep recapturing on the destination square with their least valuable piece,
+    /// stopping whenever it is not profitable. Sliders behind the captured pieces join the exchange as they are revealed.
+    /// Drops are not considered for recaptures.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::{Move, Position};
+    /// use shogi::bitboard::Factory as BBFactory;
+    /// use shogi::square::consts::*;
+    ///
+    /// BBFactory::init();
+    /// let mut pos = Position::new();
+    /// pos.set_sfen("4k4/9/9/4g4/4p4/9/9/4R4/4K4 b - 1").unwrap();
+    ///
+    /// // The rook captures a pawn defended by a gold.
+    /// let m = Move::Normal{from: SQ_5H, to: SQ_5E, promote: false};
+    /// assert!(pos.see(m) < 0);
+    /// ```
+    pub fn see(&self, m: Move) -> i32 {
+        let (to, moved, gain0, mut occupied) = match self.see_start(m) {
+            Some(start) => start,
+            None => return 0,
+        };
+
+        let mut gain = [0; 41];
+        gain[0] = gain0;
+
+        let mut on_target = see_value(moved.piece_type);
+        let mut side = moved.color.flip();
+        let mut depth = 0;
+        while let Some(attacker_type) = self.see_next_attacker(to, side, &mut occupied) {
+            depth += 1;
+            gain[depth] = on_target - gain[depth - 1];
+            on_target = see_value(attacker_type);
+            side = side.flip();
+        }
+
+        while depth > 0 {
+            gain[depth - 1] = -(-gain[depth - 1]).max(gain[depth]);
+            depth -= 1;
         }
+
+        gain[0]
     }
-}
 
-impl fmt::Display for Position {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        writeln!(f, "   9   8   7   6   5   4   3   2   1")?;
-        writeln!(f, "+---+---+---+---+---+---+---+---+---+")?;
+    /// Checks if the static exchange evaluation of the given move is greater than or equal to `threshold`.
+    ///
+    /// This is equivalent to `self.see(m) >= threshold`, but stops the exchange as soon as the result is decided.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::{Move, Position};
+    /// use shogi::bitboard::Factory as BBFactory;
+    /// use shogi::square::consts::*;
+    ///
+    /// BBFactory::init();
+    /// let mut pos = Position::new();
+    /// pos.set_sfen("4k4/9/9/4g4/4p4/9/9/4R4/4K4 b - 1").unwrap();
+    ///
+    /// let m = Move::Normal{from: SQ_5H, to: SQ_5E, promote: false};
+    /// assert!(!pos.see_ge(m, 0));
+    /// assert!(pos.see_ge(m, -900));
+    /// ```
+    pub fn see_ge(&self, m: Move, threshold: i32) -> bool {
+        let (to, moved, gain0, mut occupied) = match self.see_start(m) {
+            Some(start) => start,
+            None => return 0 >= threshold,
+        };
 
-        for row in 0..9 {
-            write!(f, "|")?;
-            for file in (0..9).rev() {
-                if let Some(ref piece) = *self.piece_at(Square::new(file, row).unwrap()) {
-                    write!(f, "{:>3}|", piece.to_string())?;
-                } else {
-                    write!(f, "   |")?;
-                }
-            }
+        // The balance for the side to move assuming the piece on the target is captured for free.
+        let mut swap = gain0 - threshold;
+        if swap < 0 {
+            return false;
+        }
 
-            writeln!(f, " {}", (('a' as usize + row as usize) as u8) as char)?;
-            writeln!(f, "+---+---+---+---+---+---+---+---+---+")?;
+        swap = see_value(moved.piece_type) - swap;
+        if swap <= 0 {
+            return true;
         }
 
-        writeln!(
-            f,
-            "Side to move: {}",
-            if self.side_to_move == Color::Black {
-                "Black"
-            } else {
-                "White"
+        // Whether the side to move wins the exchange if it stops here.
+        let mut res = true;
+        let mut side = moved.color.flip();
+        while let Some(attacker_type) = self.see_next_attacker(to, side, &mut occupied) {
+            res = !res;
+            swap = see_value(attacker_type) - swap;
+            if swap < res as i32 {
+                break;
             }
-        )?;
+            side = side.flip();
+        }
 
-        let fmt_hand = |color: Color, f: &mut fmt::Formatter| -> fmt::Result {
-            for pt in PieceType::iter().filter(|pt| pt.is_hand_piece()) {
-                let pc = Piece {
-                    piece_type: pt,
-                    color,
-                };
-                let n = self.hand.get(pc);
+        res
+    }
 
-                if n > 0 {
-                    write!(f, "{pc}{n} ")?;
-                }
+    /// Returns the destination, the piece placed there, the initial gain and the occupancy after the given move,
+    /// or `None` if there is no piece to move.
+    fn see_start(&self, m: Move) -> Option<(Square, Piece, i32, Bitboard)> {
+        let (from, to, moved) = match m {
+            Move::Normal { from, to, promote } => {
+                let pc = (*self.piece_at(from))?;
+                let moved = if promote {
+                    pc.promote().unwrap_or(pc)
+                } else {
+                    pc
+                };
+                (Some(from), to, moved)
             }
-            Ok(())
+            Move::Drop { to, piece_type } => (
+                None,
+                to,
+                Piece {
+                    piece_type,
+                    color: self.side_to_move(),
+                },
+            ),
         };
-        write!(f, "Hand (Black): ")?;
-        fmt_hand(Color::Black, f)?;
-        writeln!(f)?;
 
-        write!(f, "Hand (White): ")?;
-        fmt_hand(Color::White, f)?;
-        writeln!(f)?;
+        let mut gain = self.piece_at(to).map_or(0, |pc| see_value(pc.piece_type));
+        if let Some(from) = from {
+            gain +=
+                see_value(moved.piece_type) - see_value(self.piece_at(from).unwrap().piece_type);
+        }
 
-        write!(f, "Ply: {}", self.ply)?;
+        let mut occupied = &self.occupied_bb | to;
+        if let Some(from) = from {
+            occupied ^= from;
+        }
 
-        Ok(())
+        Some((to, moved, gain, occupied))
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::square::consts::*;
+    /// Removes the least valuable piece of `side` attacking `to` from `occupied` and returns its type.
+    ///
+    /// Returns `None` if there is no such piece, or if it is the king and the square is still defended.
+    fn see_next_attacker(
+        &self,
+        to: Square,
+        side: Color,
+        occupied: &mut Bitboard,
+    ) -> Option<PieceType> {
+        let attackers = &self.attackers_to(to, side, occupied) & &*occupied;
+        let attacker = SEE_ORDER
+            .iter()
+            .find_map(|&pt| (&attackers & &self.type_bb[pt.index()]).first())?;
+
+        let attacker_type = self.piece_at(attacker).unwrap().piece_type;
+        *occupied ^= attacker;
+        if attacker_type == PieceType::King
+            && (&self.attackers_to(to, side.flip(), occupied) & &*occupied).is_any()
+        {
+            return None;
+        }
 
-    fn setup() {
-        BBFactory::init();
+        Some(attacker_type)
     }
 
-    #[test]
-    fn new() {
-        setup();
+    /// Appends legal moves of the piece at `from` whose destination is in `targets`,
+    /// including both promoting and non-promoting ones if possible.
+    fn push_normal_moves<L: MoveSink>(&self, from: Square, targets: &Bitboard, moves: &mut L) {
+        let stm = self.side_to_move();
+        let pc = match *self.piece_at(from) {
+            Some(pc) => pc,
+            None => return,
+        };
 
-        let pos = Position::new();
+        for to in &self.move_candidates(from, pc) & targets {
+            if self.leaves_in_check(Some(from), to) {
+                continue;
+            }
 
-        for i in 0..9 {
-            for j in 0..9 {
-                let sq = Square::new(i, j).unwrap();
-                assert_eq!(None, *pos.piece_at(sq));
+            let m = Move::Normal {
+                from,
+                to,
+                promote: false,
+            };
+            if pc.promote().is_some() && (from.in_promotion_zone(stm) || to.in_promotion_zone(stm))
+            {
+                moves.push_move(m.with_promotion(true));
+            }
+            if pc.is_placeable_at(to) {
+                moves.push_move(m);
             }
         }
     }
 
-    #[test]
-    fn in_check() {
-        setup();
+    /// Appends legal drop moves to the empty squares in `targets`.
+    fn push_drop_moves<L: MoveSink>(&self, targets: &Bitboard, moves: &mut L) {
+        let stm = self.side_to_move();
 
-        let test_cases = [
-            (
-                "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1",
-                false,
-                false,
-            ),
-            ("9/3r5/9/9/6B2/9/9/9/3K5 b P 1", true, false),
-            (
-                "ln2r1knl/2gb1+Rg2/4Pp1p1/p1pp1sp1p/1N2pN1P1/2P2PP2/PP1G1S2R/1SG6/LK6L w 2PSp 1",
-                false,
-                true,
-            ),
-            (
-                "lnsg1gsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSG1GSNL b - 1",
-                false,
-                false,
-            ),
-        ];
+        for pt in PieceType::iter().filter(|pt| pt.is_hand_piece()) {
+            let pc = Piece {
+                piece_type: pt,
+                color: stm,
+            };
+            if self.hand(pc) == 0 {
+                continue;
+            }
 
-        let mut pos = Position::new();
-        for case in test_cases.iter() {
-            pos.set_sfen(case.0).expect("failed to parse SFEN string");
-            assert_eq!(case.1, pos.in_check(Color::Black));
-            assert_eq!(case.2, pos.in_check(Color::White));
+            for to in self.drop_targets(pc, targets) {
+                moves.push_move(Move::Drop { to, piece_type: pt });
+            }
         }
     }
 
-    #[test]
-    fn find_king() {
-        setup();
+    /// Returns the empty squares in `targets` where the given piece of the side to move can be legally dropped,
+    /// regardless of whether it is in hand.
+    fn drop_targets(&self, pc: Piece, targets: &Bitboard) -> Bitboard {
+        let mut bb = Bitboard::empty();
+
+        for to in targets & &!&self.occupied_bb {
+            if !pc.is_placeable_at(to)
+                || (pc.piece_type == PieceType::Pawn
+                    && (self.is_nifu(pc.color, to.file()) || self.would_be_uchifuzume(to)))
+                || self.leaves_in_check(None, to)
+            {
+                continue;
+            }
 
-        let test_cases = [
-            (
-                "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1",
-                Some(SQ_5I),
-                Some(SQ_5A),
-            ),
-            ("9/3r5/9/9/6B2/9/9/9/3K5 b P 1", Some(SQ_6I), None),
-            (
-                "ln2r1knl/2gb1+Rg2/4Pp1p1/p1pp1sp1p/1N2pN1P1/2P2PP2/PP1G1S2R/1SG6/LK6L w 2PSp 1",
-                Some(SQ_8I),
-                Some(SQ_3A),
-            ),
-            (
-                "lnsg1gsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSG1GSNL b - 1",
-                None,
-                None,
-            ),
-        ];
+            bb |= to;
+        }
 
-        let mut pos = Position::new();
-        for case in test_cases.iter() {
-            pos.set_sfen(case.0).expect("failed to parse SFEN string");
-            assert_eq!(case.1, pos.find_king(Color::Black));
-            assert_eq!(case.2, pos.find_king(Color::White));
-        }
+        bb
     }
 
-    #[test]
-    fn player_bb() {
-        setup();
-
-        let cases: &[(&str, &[Square], &[Square])] = &[
-            (
-                "R6gk/9/8p/9/4p4/9/9/8L/B8 b - 1",
-                &[SQ_9A, SQ_1H, SQ_9I],
-                &[SQ_2A, SQ_1A, SQ_1C, SQ_5E],
-            ),
-            ("9/3r5/9/9/6B2/9/9/9/3K5 b P 1", &[SQ_3E, SQ_6I], &[SQ_6B]),
-        ];
+    /// Checks if the king of the side to move is attacked after moving a piece from `from` to `to`.
+    /// `from` is `None` for drop moves.
+    fn leaves_in_check(&self, from: Option<Square>, to: Square) -> bool {
+        let stm = self.side_to_move();
+        let mut king_sq = self.king_square(stm);
+        let mut occupied = &self.occupied_bb | to;
 
-        let mut pos = Position::new();
-        for case in cases {
-            pos.set_sfen(case.0).expect("faled to parse SFEN string");
-            let black = pos.player_bb(Color::Black);
-            let white = pos.player_bb(Color::White);
+        if let Some(from) = from {
+            occupied ^= from;
 
-            assert_eq!(case.1.len(), black.count() as usize);
-            for sq in case.1 {
-                assert!((black & *sq).is_any());
+            if king_sq == Some(from) {
+                king_sq = Some(to);
             }
+        }
 
-            assert_eq!(case.2.len(), white.count() as usize);
-            for sq in case.2 {
-                assert!((white & *sq).is_any());
+        match king_sq {
+            Some(king_sq) => {
+                let mut attackers = self.attackers_to(king_sq, stm.flip(), &occupied);
+                // The piece at `to` is captured.
+                attackers.clear_at(to);
+                attackers.is_any()
             }
+            None => false,
         }
     }
 
-    #[test]
-    fn pinned_bb() {
-        setup();
+    /////////////////////////////////////////////////////////////////////////
+    // Validation
+    /////////////////////////////////////////////////////////////////////////
 
-        let cases: &[(&str, &[Square], &[Square])] = &[(
-            "R6gk/9/8p/9/4p4/9/9/8L/B8 b - 1",
-            &[],
-            &[SQ_2A, SQ_1C, SQ_5E],
-        )];
+    /// Checks if the current board state is legal.
+    ///
+    /// A position is considered legal when:
+    ///
+    /// - each player has exactly one king,
+    /// - the number of pieces on the board and in hands does not exceed the standard set,
+    /// - no player has two unpromoted pawns in the same file,
+    /// - every piece on the board has a square to move to,
+    /// - the king of the side not to move is not in check.
+    ///
+    /// Note that tsume positions usually fail this check as they lack the king of the attacking side.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::{Color, Position, PositionError};
+    /// use shogi::bitboard::Factory as BBFactory;
+    ///
+    /// BBFactory::init();
+    /// let mut pos = Position::new();
+    ///
+    /// pos.set_sfen("lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1").unwrap();
+    /// assert_eq!(Ok(()), pos.validate());
+    ///
+    /// pos.set_sfen("4k4/9/9/9/9/9/9/9/9 b - 1").unwrap();
+    /// assert_eq!(Err(PositionError::MissingKing(Color::Black)), pos.validate());
+    /// ```
+    pub fn validate(&self) -> Result<(), PositionError> {
+        for c in Color::iter() {
+            match self.pieces(c, PieceType::King).count() {
+                0 => return Err(PositionError::MissingKing(c)),
+                1 => {}
+                _ => return Err(PositionError::TooManyKings(c)),
+            }
+        }
 
-        let mut pos = Position::new();
-        for case in cases {
-            pos.set_sfen(case.0).expect("faled to parse SFEN string");
-            let black = pos.pinned_bb(Color::Black);
-            let white = pos.pinned_bb(Color::White);
+        for pt in PieceType::iter().filter(|pt| pt.is_hand_piece()) {
+            let on_board = self.pieces_by_type(pt).count()
+                + pt.promote().map_or(0, |pt| self.pieces_by_type(pt).count());
+            let in_hand = Color::iter()
+                .map(|color| {
+                    self.hand(Piece {
+                        piece_type: pt,
+                        color,
+                    }) as usize
+                })
+                .sum::<usize>();
 
-            assert_eq!(case.1.len(), black.count());
-            for sq in case.1 {
-                assert!((&black & *sq).is_any());
+            let limit = match pt {
+                PieceType::Rook | PieceType::Bishop => 2,
+                PieceType::Pawn => 18,
+                _ => 4,
+            };
+            if on_board + in_hand > limit {
+                return Err(PositionError::TooManyPieces(pt));
             }
+        }
 
-            assert_eq!(case.2.len(), white.count());
-            for sq in case.2 {
-                assert!((&white & *sq).is_any());
+        for c in Color::iter() {
+            let mut files = [false; 9];
+            for sq in self.pieces(c, PieceType::Pawn) {
+                if files[sq.file() as usize] {
+                    return Err(PositionError::Nifu(c));
+                }
+                files[sq.file() as usize] = true;
             }
         }
+
+        if let Some((sq, _)) = self.iter_pieces().find(|&(sq, pc)| !pc.is_placeable_at(sq)) {
+            return Err(PositionError::NonMovablePiece(sq));
+        }
+
+        if self.in_check(self.side_to_move.flip()) {
+            return Err(PositionError::OpponentInCheck);
+        }
+
+        Ok(())
     }
 
-    #[test]
-    fn move_candidates() {
-        setup();
+    /////////////////////////////////////////////////////////////////////////
+    // SFEN serialization / deserialization
+    /////////////////////////////////////////////////////////////////////////
 
-        let mut pos = Position::new();
-        pos.set_sfen("lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1")
-            .expect("failed to parse SFEN string");
+    /// Parses the given SFEN string and updates the game state.
+    ///
+    /// If any of the following moves can not be made, the moves from that point are silently ignored
+    /// and the game state is left as it was before the failing move.
+    /// Use [`set_sfen_checked`](#method.set_sfen_checked) to detect such moves.
+    pub fn set_sfen(&mut self, sfen_str: &str) -> Result<(), SfenError> {
+        self.parse_sfen(sfen_str, false, false)
+    }
 
-        let mut sum = 0;
-        for sq in Square::iter() {
-            let pc = pos.piece_at(sq);
+    /// Parses the given SFEN string like [`set_sfen`](#method.set_sfen), but fails if any of the following moves can not be made.
+    ///
+    /// The returned error tells the index of the failing move in the move list.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::{Position, SfenError};
+    /// use shogi::bitboard::Factory as BBFactory;
+    ///
+    /// BBFactory::init();
+    /// let mut pos = Position::new();
+    ///
+    /// let err = pos.set_sfen_checked("lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1 moves 7g7f 3c3d 7f7f").unwrap_err();
+    /// assert!(matches!(err, SfenError::MoveFailed { index: 2, .. }));
+    /// ```
+    pub fn set_sfen_checked(&mut self, sfen_str: &str) -> Result<(), SfenError> {
+        self.parse_sfen(sfen_str, false, true)
+    }
 
-            if let Some(pc) = *pc {
-                if pc.color == pos.side_to_move() {
-                    sum += pos.move_candidates(sq, pc).count();
+    /// Parses the given SFEN string like [`set_sfen_checked`](#method.set_sfen_checked), but also rejects illegal positions.
+    ///
+    /// The position described by the SFEN string is checked by [`validate`](#method.validate) before making the following moves.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::{Color, Position, PositionError, SfenError};
+    /// use shogi::bitboard::Factory as BBFactory;
+    ///
+    /// BBFactory::init();
+    /// let mut pos = Position::new();
+    ///
+    /// assert!(pos.set_sfen_strict("lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1").is_ok());
+    /// assert_eq!(
+    ///     Err(SfenError::IllegalPosition(PositionError::TooManyKings(Color::Black))),
+    ///     pos.set_sfen_strict("8k/9/9/9/9/9/9/9/KK7 b - 1")
+    /// );
+    /// ```
+    pub fn set_sfen_strict(&mut self, sfen_str: &str) -> Result<(), SfenError> {
+        self.parse_sfen(sfen_str, true, true)
+    }
+
+    fn parse_sfen(
+        &mut self,
+        sfen_str: &str,
+        validate: bool,
+        check_moves: bool,
+    ) -> Result<(), SfenError> {
+        let mut parts = sfen_str.split_whitespace();
+
+        // Build the initial position, all parts are required.
+        parts
+            .next()
+            .ok_or(SfenError::MissingDataFields)
+            .and_then(|s| self.parse_sfen_board(s))?;
+        parts
+            .next()
+            .ok_or(SfenError::MissingDataFields)
+            .and_then(|s| self.parse_sfen_stm(s))?;
+        parts
+            .next()
+            .ok_or(SfenError::MissingDataFields)
+            .and_then(|s| self.parse_sfen_hand(s))?;
+        parts
+            .next()
+            .ok_or(SfenError::MissingDataFields)
+            .and_then(|s| self.parse_sfen_ply(s))?;
+
+        if validate {
+            self.validate()?;
+        }
+
+        self.reset_history();
+
+        // Make moves following the initial position, optional.
+        if let Some("moves") = parts.next() {
+            for (index, m) in parts.enumerate() {
+                if m == NULL_MOVE_SFEN {
+                    self.make_null_move();
+                } else if let Some(m) = Move::from_sfen(m) {
+                    // Stop if any error occurrs.
+                    if let Err(error) = self.make_move_or_revert(m) {
+                        if check_moves {
+                            return Err(SfenError::MoveFailed { index, error });
+                        }
+                        break;
+                    }
+                } else {
+                    return Err(SfenError::IllegalMove);
                 }
             }
         }
 
-        assert_eq!(30, sum);
+        Ok(())
     }
 
-    #[test]
-    fn make_normal_move() {
-        setup();
-
-        let base_sfen = "l6nl/5+P1gk/2np1S3/p1p4Pp/3P2Sp1/1PPb2P1P/P5GS1/R8/LN4bKL w GR5pnsg 1";
-        let test_cases = [
-            (SQ_2B, SQ_2C, false, true),
-            (SQ_7C, SQ_6E, false, true),
-            (SQ_3I, SQ_4H, true, true),
-            (SQ_6F, SQ_9I, true, true),
-            (SQ_2B, SQ_2C, false, true),
-            (SQ_9C, SQ_9D, false, false),
-            (SQ_9B, SQ_8B, false, false),
-            (SQ_9B, SQ_9D, false, false),
-            (SQ_2B, SQ_2C, true, false),
-        ];
-
+    /// Creates a new instance of `Position` from SFEN formatted string.
+    ///
+    /// This is a shorthand for [`new`](#method.new) followed by [`set_sfen`](#method.set_sfen).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::Position;
+    /// use shogi::bitboard::Factory as BBFactory;
+    ///
+    /// BBFactory::init();
+    /// let pos = Position::from_sfen("lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1 moves 7g7f").unwrap();
+    ///
+    /// assert_eq!(1, pos.move_history().len());
+    /// ```
+    pub fn from_sfen(sfen_str: &str) -> Result<Position, SfenError> {
         let mut pos = Position::new();
-        for case in test_cases.iter() {
-            pos.set_sfen(base_sfen)
-                .expect("failed to parse SFEN string");
-            assert_eq!(case.3, pos.make_normal_move(case.0, case.1, case.2).is_ok());
-        }
-
-        // Leaving the checked king is illegal.
-        pos.set_sfen("9/3r5/9/9/6B2/9/9/9/3K5 b P 1")
-            .expect("failed to parse SFEN string");
-        assert!(pos.make_normal_move(SQ_6I, SQ_6H, false).is_err());
-        pos.set_sfen("9/3r5/9/9/6B2/9/9/9/3K5 b P 1")
-            .expect("failed to parse SFEN string");
-        assert!(pos.make_normal_move(SQ_6I, SQ_7I, false).is_ok());
+        pos.set_sfen(sfen_str)?;
+        Ok(pos)
     }
 
-    #[test]
-    fn make_drop_move() {
-        setup();
+    /// Creates a new instance of `Position` from the arguments of USI `position` command.
+    ///
+    /// Both `startpos [moves ...]` and `sfen <sfen> [moves ...]` forms are accepted,
+    /// optionally preceded by the `position` keyword itself.
+    /// As with [`set_sfen_checked`](#method.set_sfen_checked), fails if any of the following moves can not be made.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::Position;
+    /// use shogi::bitboard::Factory as BBFactory;
+    ///
+    /// BBFactory::init();
+    /// let pos = Position::from_usi("position startpos moves 7g7f 3c3d").unwrap();
+    ///
+    /// assert_eq!("lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1 moves 7g7f 3c3d", pos.to_sfen());
+    /// ```
+    pub fn from_usi(s: &str) -> Result<Position, SfenError> {
+        let mut parts = s.split_whitespace().peekable();
+        if let Some(&"position") = parts.peek() {
+            parts.next();
+        }
 
-        let base_sfen = "l6nl/5+P1gk/2np1S3/p1p4Pp/3P2Sp1/1PPb2P1P/P5GS1/R8/LN4bKL w GR5pnsg 1";
-        let test_cases = [
-            (SQ_5E, PieceType::Pawn, true),
-            (SQ_5E, PieceType::Rook, false),
-            (SQ_9A, PieceType::Pawn, false),
-            (SQ_6F, PieceType::Pawn, false),
-            (SQ_9B, PieceType::Pawn, false),
-            (SQ_5I, PieceType::Pawn, false),
-        ];
+        let sfen = match parts.next() {
+            Some("startpos") => core::iter::once(STARTPOS_SFEN).chain(parts).join(" "),
+            Some("sfen") => parts.join(" "),
+            _ => return Err(SfenError::MissingDataFields),
+        };
 
         let mut pos = Position::new();
-        for case in test_cases.iter() {
-            pos.set_sfen(base_sfen)
-                .expect("failed to parse SFEN string");
-            assert_eq!(
-                case.2,
-                pos.make_move(Move::Drop {
-                    to: case.0,
-                    piece_type: case.1,
-                })
-                .is_ok()
-            );
-        }
+        pos.set_sfen_checked(&sfen)?;
+        Ok(pos)
     }
 
-    #[test]
-    fn nifu() {
-        setup();
+    /// Converts the current board state into SFEN formatted string, without the move history.
+    ///
+    /// Unlike [`to_sfen`](#method.to_sfen), the result always consists of the four fields of board, side to move, hands and ply,
+    /// so the same position is converted into the same string regardless of the moves leading to it.
+    /// The ply field is the current ply, i.e. the initial ply plus the number of moves made since then.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::Position;
+    /// use shogi::bitboard::Factory as BBFactory;
+    ///
+    /// BBFactory::init();
+    /// let pos = Position::from_usi("position startpos moves 7g7f 3c3d").unwrap();
+    ///
+    /// assert_eq!("lnsgkgsnl/1r5b1/pppppp1pp/6p2/9/2P6/PP1PPPPPP/1B5R1/LNSGKGSNL b - 3", pos.to_sfen_current());
+    /// ```
+    pub fn to_sfen_current(&self) -> String {
+        self.generate_sfen()
+    }
 
-        let ng_cases = [(
-            "ln1g5/1ks1g3l/1p2p1n2/p1pGs2rp/1P1N1ppp1/P1SB1P2P/1S1p1bPP1/LKG6/4R2NL \
-             w 2Pp 91",
-            SQ_6C,
-        )];
-        let ok_cases = [(
-            "ln1g5/1ks1g3l/1p2p1n2/p1pGs2rp/1P1N1ppp1/P1SB1P2P/1S1+p1bPP1/LKG6/4R2NL \
-             w 2Pp 91",
-            SQ_6C,
-        )];
+    /// Converts the current state into SFEN formatted string.
+    ///
+    /// If any moves have been made, the result consists of the initial position followed by `moves` and the move list.
+    /// Note that the ply field then shows the ply of the initial position, not the current one.
+    /// Null moves are written as `pass`.
+    /// Use [`to_sfen_current`](#method.to_sfen_current) to get the current position with the current ply.
+    pub fn to_sfen(&self) -> String {
+        if self.hash_history.is_empty() {
+            return self.generate_sfen();
+        }
 
-        let mut pos = Position::new();
-        for (i, case) in ng_cases.iter().enumerate() {
-            pos.set_sfen(case.0).expect("failed to parse SFEN string");
-            assert_eq!(
-                Some(MoveError::Nifu),
-                pos.make_move(Move::Drop {
-                    to: case.1,
-                    piece_type: PieceType::Pawn,
-                })
-                .err(),
-                "failed at #{i}"
-            );
+        if self.move_history.is_empty() && self.null_moves.is_empty() {
+            return format!("{} {}", self.start_sfen, self.ply);
         }
 
-        for (i, case) in ok_cases.iter().enumerate() {
-            pos.set_sfen(case.0).expect("failed to parse SFEN string");
-            assert!(
-                pos.make_move(Move::Drop {
-                    to: case.1,
-                    piece_type: PieceType::Pawn,
-                })
-                .is_ok(),
-                "failed at #{i}"
-            );
+        let mut sfen = format!("{} {} moves", &self.start_sfen, self.start_ply());
+
+        let mut nulls = self.null_moves.iter().peekable();
+        for i in 0..=self.move_history.len() {
+            while nulls.next_if(|&&n| n == i).is_some() {
+                let _ = write!(sfen, " {NULL_MOVE_SFEN}");
+            }
+            if let Some(m) = self.move_history.get(i) {
+                let _ = write!(sfen, " {}", &m.to_sfen());
+            }
         }
+
+        sfen
     }
 
-    #[test]
-    fn uchifuzume() {
-        setup();
+    /// Returns the ply of the initial position of the history.
+    fn start_ply(&self) -> u16 {
+        self.ply - (self.move_history.len() + self.null_moves.len()) as u16
+    }
 
-        let ng_cases = [
-            ("9/9/7sp/6ppk/9/7G1/9/9/9 b P 1", SQ_1E),
+    fn parse_sfen_board(&mut self, s: &str) -> Result<(), SfenError> {
+        let rows = s.split('/');
+
+        self.occupied_bb = Bitboard::empty();
+        self.color_bb = Default::default();
+        self.type_bb = Default::default();
+        self.king_sq = [None; 2];
+
+        for (i, row) in rows.enumerate() {
+            if i >= 9 {
+                return Err(SfenError::IllegalBoardState);
+            }
+
+            let mut j = 0;
+
+            let mut chars = row.char_indices();
+            while let Some((start, c)) = chars.next() {
+                match c {
+                    n if n.is_ascii_digit() => {
+                        if let Some(n) = n.to_digit(10) {
+                            for _ in 0..n {
+                                if j >= 9 {
+                                    return Err(SfenError::IllegalBoardState);
+                                }
+
+                                let sq = Square::new(8 - j, i as u8).unwrap();
+                                self.set_piece(sq, None);
+
+                                j += 1;
+                            }
+                        }
+                    }
+                    c => {
+                        // A promoted piece consists of `+` and the following character.
+                        let end = if c == '+' {
+                            chars.next().map_or(row.len(), |(k, c)| k + c.len_utf8())
+                        } else {
+                            start + c.len_utf8()
+                        };
+                        let piece = Piece::from_sfen_str(&row[start..end])
+                            .ok_or(SfenError::IllegalPieceType)?;
+
+                        if j >= 9 {
+                            return Err(SfenError::IllegalBoardState);
+                        }
+
+                        let sq = Square::new(8 - j, i as u8).unwrap();
+                        self.place_piece(sq, piece);
+                        j += 1;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn parse_sfen_stm(&mut self, s: &str) -> Result<(), SfenError> {
+        self.side_to_move = match s {
+            "b" => Color::Black,
+            "w" => Color::White,
+            _ => return Err(SfenError::IllegalSideToMove),
+        };
+        Ok(())
+    }
+
+    fn parse_sfen_hand(&mut self, s: &str) -> Result<(), SfenError> {
+        self.hand.clear();
+        if s == "-" {
+            return Ok(());
+        }
+
+        let mut num_pieces: u8 = 0;
+        for c in s.chars() {
+            match c {
+                n if n.is_ascii_digit() => {
+                    if let Some(n) = n.to_digit(10) {
+                        num_pieces = num_pieces * 10 + (n as u8);
+                    }
+                }
+                s => {
+                    match Piece::from_sfen(s) {
+                        Some(p) => self
+                            .hand
+                            .set(p, if num_pieces == 0 { 1 } else { num_pieces }),
+                        None => return Err(SfenError::IllegalPieceType),
+                    };
+                    num_pieces = 0;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn parse_sfen_ply(&mut self, s: &str) -> Result<(), SfenError> {
+        self.ply = s.parse()?;
+        Ok(())
+    }
+
+    fn generate_sfen(&self) -> String {
+        let board = (0..9)
+            .map(|row| {
+                let mut s = String::new();
+                let mut num_spaces = 0;
+                for file in (0..9).rev() {
+                    match *self.piece_at(Square::new(file, row).unwrap()) {
+                        Some(pc) => {
+                            if num_spaces > 0 {
+                                s.push_str(&num_spaces.to_string());
+                                num_spaces = 0;
+                            }
+
+                            s.push_str(&pc.to_string());
+                        }
+                        None => num_spaces += 1,
+                    }
+                }
+
+                if num_spaces > 0 {
+                    s.push_str(&num_spaces.to_string());
+                }
+
+                s
+            })
+            .join("/");
+
+        let color = if self.side_to_move == Color::Black {
+            "b"
+        } else {
+            "w"
+        };
+
+        let mut hand = [Color::Black, Color::White]
+            .iter()
+            .map(|c| {
+                PieceType::hand_order()
+                    .into_iter()
+                    .map(|pt| {
+                        let pc = Piece {
+                            piece_type: pt,
+                            color: *c,
+                        };
+                        let n = self.hand.get(pc);
+
+                        if n == 0 {
+                            "".to_string()
+                        } else if n == 1 {
+                            format!("{pc}")
+                        } else {
+                            format!("{n}{pc}")
+                        }
+                    })
+                    .join("")
+            })
+            .join("");
+
+        if hand.is_empty() {
+            hand = "-".to_string();
+        }
+
+        format!("{} {} {} {}", board, color, hand, self.ply)
+    }
+
+    /////////////////////////////////////////////////////////////////////////
+    // Rendering
+    /////////////////////////////////////////////////////////////////////////
+
+    /// Renders the board as seen from the given player, in the same format as `Display`.
+    ///
+    /// The board is rotated by 180 degrees when `viewpoint` is `Color::White`, and the hand of the viewing player is listed first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::{Color, Position};
+    /// use shogi::bitboard::Factory as BBFactory;
+    ///
+    /// BBFactory::init();
+    /// let mut pos = Position::new();
+    /// pos.set_sfen("lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1").unwrap();
+    ///
+    /// assert_eq!(pos.to_string(), pos.to_string_perspective(Color::Black));
+    /// assert!(pos.to_string_perspective(Color::White).starts_with("   1   2   3"));
+    /// ```
+    pub fn to_string_perspective(&self, viewpoint: Color) -> String {
+        let mut s = String::new();
+        self.render(&mut s, viewpoint, None)
+            .expect("writing to a String never fails");
+        s
+    }
+
+    /// Renders the board in the same format as `Display`, marking the given square with brackets.
+    ///
+    /// The brackets replace the grid lines surrounding the square, which keeps the alignment of the board.
+    /// Passing the destination of the last entry in [`move_history`](#method.move_history) highlights the most recent move.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::Position;
+    /// use shogi::bitboard::Factory as BBFactory;
+    /// use shogi::square::consts::*;
+    ///
+    /// BBFactory::init();
+    /// let mut pos = Position::new();
+    /// pos.set_sfen("lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1 moves 7g7f").unwrap();
+    ///
+    /// let board = pos.pretty(Some(SQ_7F));
+    /// assert_eq!(Some("|   |   [  P]   |   |   |   |   |   | f"), board.lines().nth(12));
+    /// ```
+    pub fn pretty(&self, highlight: Option<Square>) -> String {
+        let mut s = String::new();
+        self.render(&mut s, Color::Black, highlight)
+            .expect("writing to a String never fails");
+        s
+    }
+
+    fn render<W: fmt::Write>(
+        &self,
+        w: &mut W,
+        viewpoint: Color,
+        highlight: Option<Square>,
+    ) -> fmt::Result {
+        let flip = viewpoint == Color::White;
+        let file_at = |i: u8| if flip { i } else { 8 - i };
+        let rank_at = |i: u8| if flip { 8 - i } else { i };
+
+        for i in 0..9 {
+            write!(w, "   {}", file_at(i) + 1)?;
+        }
+        writeln!(w)?;
+        writeln!(w, "+---+---+---+---+---+---+---+---+---+")?;
+
+        for i in 0..9 {
+            let rank = rank_at(i);
+            let mut prev_highlighted = false;
+            for j in 0..9 {
+                let sq = Square::new(file_at(j), rank).unwrap();
+                let highlighted = highlight == Some(sq);
+                write!(
+                    w,
+                    "{}",
+                    if highlighted {
+                        '['
+                    } else if prev_highlighted {
+                        ']'
+                    } else {
+                        '|'
+                    }
+                )?;
+                if let Some(ref piece) = *self.piece_at(sq) {
+                    write!(w, "{:>3}", piece.to_string())?;
+                } else {
+                    write!(w, "   ")?;
+                }
+                prev_highlighted = highlighted;
+            }
+            write!(w, "{}", if prev_highlighted { ']' } else { '|' })?;
+
+            writeln!(w, " {}", (b'a' + rank) as char)?;
+            writeln!(w, "+---+---+---+---+---+---+---+---+---+")?;
+        }
+
+        writeln!(
+            w,
+            "Side to move: {}",
+            if self.side_to_move == Color::Black {
+                "Black"
+            } else {
+                "White"
+            }
+        )?;
+
+        let fmt_hand = |color: Color, w: &mut W| -> fmt::Result {
+            write!(
+                w,
+                "Hand ({}): ",
+                if color == Color::Black {
+                    "Black"
+                } else {
+                    "White"
+                }
+            )?;
+            for pt in PieceType::hand_order() {
+                let pc = Piece {
+                    piece_type: pt,
+                    color,
+                };
+                let n = self.hand.get(pc);
+
+                if n > 0 {
+                    write!(w, "{pc}{n} ")?;
+                }
+            }
+            writeln!(w)
+        };
+        fmt_hand(viewpoint, w)?;
+        fmt_hand(viewpoint.flip(), w)?;
+
+        write!(w, "Ply: {}", self.ply)?;
+
+        if !self.occupied_bb.is_any() {
+            write!(w, "\n(empty board)")?;
+        }
+
+        Ok(())
+    }
+
+    /// Renders the board with the single-character kanji piece symbols.
+    ///
+    /// The layout follows the board diagram of KIF format: White pieces are prefixed with `v`, empty squares are shown as `・`
+    /// and ranks are labeled with kanji numerals. Every cell is three columns wide, assuming the glyphs are rendered in double width.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::Position;
+    /// use shogi::bitboard::Factory as BBFactory;
+    ///
+    /// BBFactory::init();
+    /// let mut pos = Position::new();
+    /// pos.set_sfen("lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1").unwrap();
+    ///
+    /// let board = pos.to_unicode();
+    /// assert_eq!(Some("|v香v桂v銀v金v王v金v銀v桂v香|一"), board.lines().nth(2));
+    /// assert_eq!(Some("| 香 桂 銀 金 玉 金 銀 桂 香|九"), board.lines().nth(10));
+    /// ```
+    pub fn to_unicode(&self) -> String {
+        const RANKS: [char; 9] = ['一', '二', '三', '四', '五', '六', '七', '八', '九'];
+
+        let mut s = String::new();
+        s.push_str("  ９ ８ ７ ６ ５ ４ ３ ２ １\n");
+        s.push_str("+---------------------------+\n");
+
+        for (rank, label) in RANKS.iter().enumerate() {
+            s.push('|');
+            for file in (0..9).rev() {
+                match *self.piece_at(Square::new(file, rank as u8).unwrap()) {
+                    Some(pc) => {
+                        s.push(if pc.color == Color::Black { ' ' } else { 'v' });
+                        s.push_str(glyph(pc));
+                    }
+                    None => s.push_str(" ・"),
+                }
+            }
+            s.push('|');
+            s.push(*label);
+            s.push('\n');
+        }
+        s.push_str("+---------------------------+\n");
+
+        s.push_str(if self.side_to_move == Color::Black {
+            "Side to move: Black\n"
+        } else {
+            "Side to move: White\n"
+        });
+
+        for color in Color::iter() {
+            s.push_str(if color == Color::Black {
+                "Hand (Black): "
+            } else {
+                "Hand (White): "
+            });
+            for pt in PieceType::hand_order() {
+                let pc = Piece {
+                    piece_type: pt,
+                    color,
+                };
+                let n = self.hand.get(pc);
+
+                if n > 0 {
+                    s.push_str(&format!("{}{n} ", glyph(pc)));
+                }
+            }
+            s.push('\n');
+        }
+
+        s.push_str(&format!("Ply: {}", self.ply));
+        s
+    }
+
+    /// Converts the moves made so far into PSN (Portable Shogi Notation) move list.
+    ///
+    /// Each line has a move number followed by the moves of Black and White, e.g. `1.P-7f P-3d`.
+    /// The source square is added only when another piece of the same kind can move to the same destination,
+    /// as determined by [`move_disambiguation`](#method.move_disambiguation).
+    /// Promotions are marked with `+`, and declined promotions with `=`. Null moves are written as `--`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::Position;
+    /// use shogi::bitboard::Factory as BBFactory;
+    ///
+    /// BBFactory::init();
+    /// let mut pos = Position::initial();
+    /// pos.play_usi_moves("7g7f 3c3d 8h2b+ 3a2b B*4e").unwrap();
+    ///
+    /// assert_eq!("1.P-7f P-3d\n2.Bx2b+ Sx2b\n3.B*4e", pos.to_psn());
+    /// ```
+    pub fn to_psn(&self) -> String {
+        if self.move_history.is_empty() && self.null_moves.is_empty() {
+            return String::new();
+        }
+
+        let mut pos = Position::from_sfen(&format!("{} {}", self.start_sfen, self.start_ply()))
+            .expect("the initial position is always valid");
+
+        let mut s = String::new();
+        let mut number = 1;
+        let mut push_number = |s: &mut String, stm: Color| {
+            if stm == Color::Black {
+                if number > 1 {
+                    s.push('\n');
+                }
+                let _ = write!(s, "{number}.");
+                number += 1;
+            } else if number == 1 {
+                s.push_str("1...");
+                number += 1;
+            } else {
+                s.push(' ');
+            }
+        };
+
+        let mut nulls = self.null_moves.iter().peekable();
+        for i in 0..=self.move_history.len() {
+            while nulls.next_if(|&&n| n == i).is_some() {
+                push_number(&mut s, pos.side_to_move);
+                s.push_str("--");
+                pos.make_null_move();
+            }
+
+            let record = match self.move_history.get(i) {
+                Some(record) => record,
+                None => break,
+            };
+            push_number(&mut s, pos.side_to_move);
+
+            let m = match *record {
+                MoveRecord::Normal {
+                    from, to, promoted, ..
+                } => {
+                    let pc = pos.piece_at(from).unwrap();
+                    let _ = write!(s, "{}", psn_piece(pc.piece_type));
+                    let m = Move::Normal {
+                        from,
+                        to,
+                        promote: promoted,
+                    };
+                    if pos.move_disambiguation(&m) != Disambiguation::None {
+                        let _ = write!(s, "{from}");
+                    }
+                    let _ = write!(
+                        s,
+                        "{}{to}",
+                        if pos.piece_at(to).is_some() { 'x' } else { '-' }
+                    );
+                    if promoted {
+                        s.push('+');
+                    } else if pc.promote().is_some()
+                        && (from.in_promotion_zone(pc.color) || to.in_promotion_zone(pc.color))
+                    {
+                        s.push('=');
+                    }
+
+                    m
+                }
+                MoveRecord::Drop { to, piece } => {
+                    let _ = write!(s, "{}*{to}", psn_piece(piece.piece_type));
+
+                    Move::Drop {
+                        to,
+                        piece_type: piece.piece_type,
+                    }
+                }
+            };
+
+            pos.make_move_fast(m)
+                .expect("moves in the history are always valid");
+        }
+
+        s
+    }
+}
+
+/// Piece types ordered from the least valuable, in which pieces recapture in static exchange evaluation.
+const SEE_ORDER: [PieceType; PieceType::NUM] = [
+    PieceType::Pawn,
+    PieceType::Lance,
+    PieceType::Knight,
+    PieceType::Silver,
+    PieceType::ProPawn,
+    PieceType::ProLance,
+    PieceType::ProKnight,
+    PieceType::ProSilver,
+    PieceType::Gold,
+    PieceType::Bishop,
+    PieceType::ProBishop,
+    PieceType::Rook,
+    PieceType::ProRook,
+    PieceType::King,
+];
+
+/// Returns the material value of the given piece type used in static exchange evaluation.
+fn see_value(pt: PieceType) -> i32 {
+    match pt {
+        PieceType::Pawn => 90,
+        PieceType::Lance => 315,
+        PieceType::Knight => 405,
+        PieceType::Silver => 495,
+        PieceType::Gold
+        | PieceType::ProPawn
+        | PieceType::ProLance
+        | PieceType::ProKnight
+        | PieceType::ProSilver => 540,
+        PieceType::Bishop => 855,
+        PieceType::Rook => 990,
+        PieceType::ProBishop => 945,
+        PieceType::ProRook => 1395,
+        PieceType::King => 15000,
+    }
+}
+
+/// Returns the piece symbol used in PSN.
+fn psn_piece(pt: PieceType) -> &'static str {
+    match pt {
+        PieceType::King => "K",
+        PieceType::Rook => "R",
+        PieceType::Bishop => "B",
+        PieceType::Gold => "G",
+        PieceType::Silver => "S",
+        PieceType::Knight => "N",
+        PieceType::Lance => "L",
+        PieceType::Pawn => "P",
+        PieceType::ProRook => "+R",
+        PieceType::ProBishop => "+B",
+        PieceType::ProSilver => "+S",
+        PieceType::ProKnight => "+N",
+        PieceType::ProLance => "+L",
+        PieceType::ProPawn => "+P",
+    }
+}
+
+/// Returns the kanji symbol of the given piece, distinguishing the kings of both sides.
+fn glyph(pc: Piece) -> &'static str {
+    if pc.piece_type == PieceType::King && pc.color == Color::White {
+        "王"
+    } else {
+        pc.piece_type.kanji()
+    }
+}
+
+/// Returns a bitboard of squares attacked by the given piece at the given square,
+/// as if the board is occupied as `occupied`.
+fn piece_attacks(sq: Square, p: Piece, occupied: &Bitboard) -> Bitboard {
+    match p.piece_type {
+        PieceType::Rook => BBFactory::rook_attack(sq, occupied),
+        PieceType::Bishop => BBFactory::bishop_attack(sq, occupied),
+        PieceType::Lance => BBFactory::lance_attack(p.color, sq, occupied),
+        PieceType::ProRook => {
+            &BBFactory::rook_attack(sq, occupied)
+                | &BBFactory::attacks_from(PieceType::King, p.color, sq)
+        }
+        PieceType::ProBishop => {
+            &BBFactory::bishop_attack(sq, occupied)
+                | &BBFactory::attacks_from(PieceType::King, p.color, sq)
+        }
+        pt => pt.attacks(p.color, sq),
+    }
+}
+
+/////////////////////////////////////////////////////////////////////////////
+// Trait implementations
+/////////////////////////////////////////////////////////////////////////////
+
+/// Returns an empty board, same as [`Position::new`].
+impl Default for Position {
+    fn default() -> Position {
+        Position {
+            side_to_move: Color::Black,
+            board: PieceGrid([None; 81]),
+            hand: Default::default(),
+            ply: 1,
+            move_history: Default::default(),
+            null_moves: Default::default(),
+            start_sfen: Default::default(),
+            hash_history: Default::default(),
+            occupied_bb: Default::default(),
+            color_bb: Default::default(),
+            type_bb: Default::default(),
+            king_sq: [None; 2],
+        }
+    }
+}
+
+/// Two positions are equal if they have the same board, pieces in hand, side to move and ply.
+///
+/// The move history is not taken into account, so the same position reached by different move orders compares equal.
+impl PartialEq for Position {
+    fn eq(&self, other: &Position) -> bool {
+        self.same_position(other) && self.ply == other.ply
+    }
+}
+
+impl Eq for Position {}
+
+/// Hashes the board, pieces in hand and side to move by their Zobrist hash.
+///
+/// As with `PartialEq`, the move history is excluded.
+impl Hash for Position {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.zobrist_hash());
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.render(f, Color::Black, None)
+    }
+}
+
+/// Iterates over the occupied squares with their pieces, same as [`Position::iter_pieces`].
+///
+/// # Examples
+///
+/// ```
+/// use shogi::Position;
+/// use shogi::bitboard::Factory as BBFactory;
+///
+/// BBFactory::init();
+/// let pos = Position::initial();
+///
+/// let mut count = 0;
+/// for (sq, pc) in &pos {
+///     assert_eq!(Some(pc), *pos.piece_at(sq));
+///     count += 1;
+/// }
+/// assert_eq!(40, count);
+/// ```
+impl<'a> IntoIterator for &'a Position {
+    type Item = (Square, Piece);
+    type IntoIter = PieceIter<'a>;
+
+    fn into_iter(self) -> PieceIter<'a> {
+        self.iter_pieces()
+    }
+}
+
+/// This struct is created by the [`iter_pieces`] method on [`Position`].
+///
+/// [`iter_pieces`]: ./struct.Position.html#method.iter_pieces
+/// [`Position`]: struct.Position.html
+pub struct PieceIter<'a> {
+    pos: &'a Position,
+    squares: Bitboard,
+}
+
+impl Iterator for PieceIter<'_> {
+    type Item = (Square, Piece);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for sq in self.squares.by_ref() {
+            if let Some(pc) = *self.pos.piece_at(sq) {
+                return Some((sq, pc));
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::moves::MAX_MOVES;
+    use crate::square::consts::*;
+
+    fn setup() {
+        BBFactory::init();
+    }
+
+    #[test]
+    fn new() {
+        setup();
+
+        let pos = Position::new();
+
+        for i in 0..9 {
+            for j in 0..9 {
+                let sq = Square::new(i, j).unwrap();
+                assert_eq!(None, *pos.piece_at(sq));
+            }
+        }
+    }
+
+    #[test]
+    fn initial() {
+        setup();
+
+        let pos = Position::initial();
+        let mut expected = Position::new();
+        expected
+            .set_sfen("lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1")
+            .expect("failed to parse SFEN string");
+
+        assert_eq!(expected.to_sfen(), pos.to_sfen());
+        assert_eq!(format!("{expected}"), format!("{pos}"));
+        for c in Color::iter() {
+            assert_eq!(expected.find_king(c), pos.find_king(c));
+            assert_eq!(expected.player_bb(c).count(), pos.player_bb(c).count());
+            assert!((expected.player_bb(c) ^ pos.player_bb(c)).is_empty());
+        }
+    }
+
+    #[test]
+    fn handicap() {
+        setup();
+
+        let cases = [
+            (
+                Handicap::Lance,
+                "lnsgkgsn1/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL w - 1",
+            ),
+            (
+                Handicap::Bishop,
+                "lnsgkgsnl/1r7/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL w - 1",
+            ),
+            (
+                Handicap::Rook,
+                "lnsgkgsnl/7b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL w - 1",
+            ),
+            (
+                Handicap::TwoPieces,
+                "lnsgkgsnl/9/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL w - 1",
+            ),
+            (
+                Handicap::SixPieces,
+                "2sgkgs2/9/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL w - 1",
+            ),
+            (
+                Handicap::TenPieces,
+                "4k4/9/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL w - 1",
+            ),
+        ];
+
+        for (i, case) in cases.iter().enumerate() {
+            let pos = Position::handicap(case.0);
+            let mut expected = Position::new();
+            expected
+                .set_sfen(case.1)
+                .expect("failed to parse SFEN string");
+
+            assert_eq!(case.1, pos.to_sfen(), "failed at #{i}");
+            assert_eq!(format!("{expected}"), format!("{pos}"), "failed at #{i}");
+            assert!(
+                (expected.player_bb(Color::White) ^ pos.player_bb(Color::White)).is_empty(),
+                "failed at #{i}"
+            );
+        }
+    }
+
+    #[test]
+    fn in_check() {
+        setup();
+
+        let test_cases = [
+            (
+                "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1",
+                false,
+                false,
+            ),
+            ("9/3r5/9/9/6B2/9/9/9/3K5 b P 1", true, false),
+            (
+                "ln2r1knl/2gb1+Rg2/4Pp1p1/p1pp1sp1p/1N2pN1P1/2P2PP2/PP1G1S2R/1SG6/LK6L w 2PSp 1",
+                false,
+                true,
+            ),
+            (
+                "lnsg1gsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSG1GSNL b - 1",
+                false,
+                false,
+            ),
+        ];
+
+        let mut pos = Position::new();
+        for case in test_cases.iter() {
+            pos.set_sfen(case.0).expect("failed to parse SFEN string");
+            assert_eq!(case.1, pos.in_check(Color::Black));
+            assert_eq!(case.2, pos.in_check(Color::White));
+        }
+    }
+
+    #[test]
+    fn checkers() {
+        setup();
+
+        let cases: &[(&str, &[Square], &[Square])] = &[
+            (
+                "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1",
+                &[],
+                &[],
+            ),
+            ("4k4/9/9/9/4R3B/9/9/9/4K4 w - 1", &[], &[SQ_5E, SQ_1E]),
+            ("4k4/4n4/4N4/9/9/9/9/9/4K4 w - 1", &[], &[]),
+            ("4k4/9/3N5/9/9/9/9/4l4/4K4 b - 1", &[SQ_5H], &[SQ_6C]),
+            ("9/9/9/9/9/9/4p4/4K4/9 b - 1", &[SQ_5G], &[]),
+            ("9/9/9/9/4l4/9/4P4/4K4/9 b - 1", &[], &[]),
+            ("8k/9/9/9/9/9/9/9/r8 b - 1", &[], &[]),
+        ];
+
+        let mut pos = Position::new();
+        for (i, case) in cases.iter().enumerate() {
+            pos.set_sfen(case.0).expect("failed to parse SFEN string");
+
+            for (c, expected) in [(Color::Black, case.1), (Color::White, case.2)] {
+                let checkers = pos.checkers(c);
+                assert_eq!(expected.len(), checkers.count(), "failed at #{i}");
+                for sq in expected.iter() {
+                    assert!((&checkers & *sq).is_any(), "failed at #{i}");
+                }
+                assert_eq!(!expected.is_empty(), pos.in_check(c), "failed at #{i}");
+            }
+        }
+    }
+
+    #[test]
+    fn find_king() {
+        setup();
+
+        let test_cases = [
+            (
+                "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1",
+                Some(SQ_5I),
+                Some(SQ_5A),
+            ),
+            ("9/3r5/9/9/6B2/9/9/9/3K5 b P 1", Some(SQ_6I), None),
+            (
+                "ln2r1knl/2gb1+Rg2/4Pp1p1/p1pp1sp1p/1N2pN1P1/2P2PP2/PP1G1S2R/1SG6/LK6L w 2PSp 1",
+                Some(SQ_8I),
+                Some(SQ_3A),
+            ),
+            (
+                "lnsg1gsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSG1GSNL b - 1",
+                None,
+                None,
+            ),
+        ];
+
+        let mut pos = Position::new();
+        for case in test_cases.iter() {
+            pos.set_sfen(case.0).expect("failed to parse SFEN string");
+            assert_eq!(case.1, pos.find_king(Color::Black));
+            assert_eq!(case.2, pos.find_king(Color::White));
+        }
+    }
+
+    #[test]
+    fn king_square() {
+        setup();
+
+        let cases = [
+            (
+                "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1",
+                Some(SQ_5I),
+                Some(SQ_5A),
+            ),
+            (
+                "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1 moves 5i4h 5a6b",
+                Some(SQ_4H),
+                Some(SQ_6B),
+            ),
+            ("9/3r5/9/9/6B2/9/9/9/3K5 b P 1", Some(SQ_6I), None),
+            ("8k/9/9/9/9/9/9/9/9 b - 1", None, Some(SQ_1A)),
+        ];
+
+        for (i, case) in cases.iter().enumerate() {
+            let mut pos = Position::from_sfen(case.0).expect("failed to parse SFEN string");
+            assert_eq!(case.1, pos.king_square(Color::Black), "failed at #{i}");
+            assert_eq!(case.2, pos.king_square(Color::White), "failed at #{i}");
+
+            while !pos.move_history().is_empty() {
+                pos.unmake_move().expect("failed to unmake a move");
+            }
+            for c in Color::iter() {
+                assert_eq!(pos.find_king(c), pos.king_square(c), "failed at #{i}");
+            }
+        }
+    }
+
+    #[test]
+    fn king_square_after_moves() {
+        setup();
+
+        let mut pos = Position::new();
+        pos.set_sfen("4k4/9/9/9/9/9/9/9/4K4 b - 1")
+            .expect("failed to parse SFEN string");
+
+        let moves = ["5i5h", "5a4b", "5h4h", "4b4a"];
+        let expected = [
+            (Some(SQ_5H), Some(SQ_5A)),
+            (Some(SQ_5H), Some(SQ_4B)),
+            (Some(SQ_4H), Some(SQ_4B)),
+            (Some(SQ_4H), Some(SQ_4A)),
+        ];
+
+        for (i, m) in moves.iter().enumerate() {
+            pos.make_move(Move::from_sfen(m).unwrap())
+                .unwrap_or_else(|_| panic!("failed at #{i}"));
+            assert_eq!(
+                expected[i].0,
+                pos.king_square(Color::Black),
+                "failed at #{i}"
+            );
+            assert_eq!(
+                expected[i].1,
+                pos.king_square(Color::White),
+                "failed at #{i}"
+            );
+        }
+
+        for i in (0..moves.len()).rev() {
+            assert_eq!(
+                expected[i].0,
+                pos.king_square(Color::Black),
+                "failed at #{i}"
+            );
+            assert_eq!(
+                expected[i].1,
+                pos.king_square(Color::White),
+                "failed at #{i}"
+            );
+            pos.unmake_move()
+                .unwrap_or_else(|_| panic!("failed at #{i}"));
+        }
+        assert_eq!(Some(SQ_5I), pos.king_square(Color::Black));
+        assert_eq!(Some(SQ_5A), pos.king_square(Color::White));
+
+        // A king moving into check is restored to the original square.
+        pos.set_sfen("4k4/9/9/9/9/9/9/3r5/4K4 b - 1")
+            .expect("failed to parse SFEN string");
+        assert!(pos.make_move(Move::from_sfen("5i6i").unwrap()).is_err());
+        assert_eq!(Some(SQ_5I), pos.king_square(Color::Black));
+    }
+
+    #[test]
+    fn player_bb() {
+        setup();
+
+        let cases: &[(&str, &[Square], &[Square])] = &[
+            (
+                "R6gk/9/8p/9/4p4/9/9/8L/B8 b - 1",
+                &[SQ_9A, SQ_1H, SQ_9I],
+                &[SQ_2A, SQ_1A, SQ_1C, SQ_5E],
+            ),
+            ("9/3r5/9/9/6B2/9/9/9/3K5 b P 1", &[SQ_3E, SQ_6I], &[SQ_6B]),
+        ];
+
+        let mut pos = Position::new();
+        for case in cases {
+            pos.set_sfen(case.0).expect("faled to parse SFEN string");
+            let black = pos.player_bb(Color::Black);
+            let white = pos.player_bb(Color::White);
+
+            assert_eq!(case.1.len(), black.count() as usize);
+            for sq in case.1 {
+                assert!((black & *sq).is_any());
+            }
+
+            assert_eq!(case.2.len(), white.count() as usize);
+            for sq in case.2 {
+                assert!((white & *sq).is_any());
+            }
+        }
+    }
+
+    #[test]
+    fn pieces() {
+        setup();
+
+        let cases: &[(&str, Color, PieceType, &[Square])] = &[
+            (
+                "R6gk/9/8p/9/4p4/9/9/8L/B8 b - 1",
+                Color::White,
+                PieceType::Pawn,
+                &[SQ_1C, SQ_5E],
+            ),
+            (
+                "R6gk/9/8p/9/4p4/9/9/8L/B8 b - 1",
+                Color::Black,
+                PieceType::Pawn,
+                &[],
+            ),
+            (
+                "9/3r5/9/9/6B2/9/9/9/3K5 b P 1",
+                Color::Black,
+                PieceType::King,
+                &[SQ_6I],
+            ),
+            (
+                "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1",
+                Color::Black,
+                PieceType::Lance,
+                &[SQ_9I, SQ_1I],
+            ),
+        ];
+
+        let mut pos = Position::new();
+        for (i, case) in cases.iter().enumerate() {
+            pos.set_sfen(case.0).expect("failed to parse SFEN string");
+
+            let bb = pos.pieces(case.1, case.2);
+            assert_eq!(case.3.len(), bb.count(), "failed at #{i}");
+            for sq in case.3 {
+                assert!((&bb & *sq).is_any(), "failed at #{i}");
+            }
+
+            let both = &pos.pieces(case.1, case.2) | &pos.pieces(case.1.flip(), case.2);
+            assert_eq!(
+                both.squares().collect::<Vec<_>>(),
+                pos.pieces_by_type(case.2).squares().collect::<Vec<_>>(),
+                "failed at #{i}"
+            );
+            assert_eq!(
+                (pos.player_bb(Color::Black) | pos.player_bb(Color::White))
+                    .squares()
+                    .collect::<Vec<_>>(),
+                pos.occupied().squares().collect::<Vec<_>>(),
+                "failed at #{i}"
+            );
+        }
+    }
+
+    #[test]
+    fn pinned_bb() {
+        setup();
+
+        let cases: &[(&str, &[Square], &[Square])] = &[(
+            "R6gk/9/8p/9/4p4/9/9/8L/B8 b - 1",
+            &[],
+            &[SQ_2A, SQ_1C, SQ_5E],
+        )];
+
+        let mut pos = Position::new();
+        for case in cases {
+            pos.set_sfen(case.0).expect("faled to parse SFEN string");
+            let black = pos.pinned_bb(Color::Black);
+            let white = pos.pinned_bb(Color::White);
+
+            assert_eq!(case.1.len(), black.count());
+            for sq in case.1 {
+                assert!((&black & *sq).is_any());
+            }
+
+            assert_eq!(case.2.len(), white.count());
+            for sq in case.2 {
+                assert!((&white & *sq).is_any());
+            }
+        }
+    }
+
+    #[test]
+    fn discovered_check_candidates() {
+        setup();
+
+        let cases: &[(&str, &[Square], &[Square])] = &[
+            ("R3S3k/9/8G/9/8L/9/B8/9/K8 b - 1", &[SQ_5A, SQ_1C], &[]),
+            ("b7k/9/8l/9/4P3p/9/9/9/8K w - 1", &[], &[SQ_1E]),
+            ("R6gk/9/8p/9/4p4/9/9/8L/B8 b - 1", &[], &[]),
+        ];
+
+        let mut pos = Position::new();
+        for (i, case) in cases.iter().enumerate() {
+            pos.set_sfen(case.0).expect("failed to parse SFEN string");
+            let black = pos.discovered_check_candidates(Color::Black);
+            let white = pos.discovered_check_candidates(Color::White);
+
+            assert_eq!(case.1.len(), black.count(), "failed at #{i}");
+            for sq in case.1 {
+                assert!((&black & *sq).is_any(), "failed at #{i}");
+            }
+
+            assert_eq!(case.2.len(), white.count(), "failed at #{i}");
+            for sq in case.2 {
+                assert!((&white & *sq).is_any(), "failed at #{i}");
+            }
+        }
+    }
+
+    #[test]
+    fn move_candidates() {
+        setup();
+
+        let mut pos = Position::new();
+        pos.set_sfen("lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1")
+            .expect("failed to parse SFEN string");
+
+        let mut sum = 0;
+        for sq in Square::iter() {
+            let pc = pos.piece_at(sq);
+
+            if let Some(pc) = *pc {
+                if pc.color == pos.side_to_move() {
+                    sum += pos.move_candidates(sq, pc).count();
+                }
+            }
+        }
+
+        assert_eq!(30, sum);
+    }
+
+    #[test]
+    fn mobility() {
+        setup();
+
+        let pos = Position::initial();
+        let by_type = pos.mobility_by_type(Color::Black);
+        let expected = [
+            (PieceType::King, 3),
+            (PieceType::Rook, 6),
+            (PieceType::Bishop, 0),
+            (PieceType::Gold, 6),
+            (PieceType::Silver, 4),
+            (PieceType::Knight, 0),
+            (PieceType::Lance, 2),
+            (PieceType::Pawn, 9),
+        ];
+        for (i, case) in expected.iter().enumerate() {
+            assert_eq!(case.1, by_type[case.0.index()], "failed at #{i}");
+        }
+        assert_eq!(30, pos.mobility(Color::Black));
+        assert_eq!(30, pos.mobility(Color::White));
+
+        let cases = [
+            (
+                "l6nl/5+P1gk/2np1S3/p1p4Pp/3P2Sp1/1PPb2P1P/P5GS1/R8/LN4bKL w RGgsn5p 1",
+                Color::White,
+                32,
+            ),
+            ("9/9/9/9/9/9/9/9/9 b - 1", Color::Black, 0),
+            ("9/9/9/9/4+R4/9/9/9/9 b - 1", Color::Black, 20),
+        ];
+
+        let mut pos = Position::new();
+        for (i, case) in cases.iter().enumerate() {
+            pos.set_sfen(case.0).expect("failed to parse SFEN string");
+            assert_eq!(case.2, pos.mobility(case.1), "failed at #{i}");
+        }
+    }
+
+    #[test]
+    fn legal_moves() {
+        setup();
+
+        let cases = [
+            (
+                "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1",
+                30,
+            ),
+            (
+                "l6nl/5+P1gk/2np1S3/p1p4Pp/3P2Sp1/1PPb2P1P/P5GS1/R8/LN4bKL w RGgsn5p 1",
+                207,
+            ),
+            ("R8/2K1S1SSk/4B4/9/9/9/9/9/1L1L1L3 b RBGSNLP3g3n17p 1", 593),
+            // Only the king can move in double check.
+            ("4k4/9/9/9/4R3B/9/9/9/4K4 w - 1", 3),
+            // Mated.
+            ("8k/8G/8P/9/9/9/9/9/9 w - 1", 0),
+            // Uchifuzume is excluded.
+            ("7nk/9/7S1/6b2/9/9/9/9/9 b P 1", 79),
+        ];
+
+        let mut pos = Position::new();
+        for (i, case) in cases.iter().enumerate() {
+            pos.set_sfen(case.0).expect("failed to parse SFEN string");
+            let moves = pos.legal_moves();
+            assert_eq!(case.1, moves.len(), "failed at #{i}");
+
+            let mut list = MoveList::new();
+            pos.generate_moves(&mut list);
+            assert_eq!(&moves[..], list.as_slice(), "failed at #{i}");
+
+            for m in moves {
+                assert!(pos.make_move(m).is_ok(), "failed at #{i}: {m}");
+                assert!(pos.unmake_move().is_ok(), "failed at #{i}: {m}");
+            }
+        }
+    }
+
+    #[test]
+    fn captures_and_quiets() {
+        setup();
+
+        let cases = [
+            (
+                "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1",
+                0,
+            ),
+            (
+                "lnsgkgsnl/1r5b1/pppppp1pp/6p2/9/2P6/PP1PPPPPP/1B5R1/LNSGKGSNL b - 1",
+                2,
+            ),
+            ("4k4/9/9/9/4R3B/9/9/9/4K4 w - 1", 0),
+            (
+                "l6nl/5+P1gk/2np1S3/p1p4Pp/3P2Sp1/1PPb2P1P/P5GS1/R8/LN4bKL w RGgsn5p 1",
+                3,
+            ),
+        ];
+
+        let mut pos = Position::new();
+        for (i, case) in cases.iter().enumerate() {
+            pos.set_sfen(case.0).expect("failed to parse SFEN string");
+
+            let captures = pos.captures();
+            let quiets = pos.quiets();
+            let legal_moves = pos.legal_moves();
+
+            assert_eq!(case.1, captures.len(), "failed at #{i}");
+            assert_eq!(
+                legal_moves.len(),
+                captures.len() + quiets.len(),
+                "failed at #{i}"
+            );
+            for m in legal_moves {
+                let is_capture = match m {
+                    Move::Normal { to, .. } => pos.piece_at(to).is_some(),
+                    Move::Drop { .. } => false,
+                };
+                assert_eq!(is_capture, captures.contains(&m), "failed at #{i}");
+                assert_eq!(!is_capture, quiets.contains(&m), "failed at #{i}");
+            }
+        }
+    }
+
+    #[test]
+    fn see() {
+        setup();
+
+        let cases = [
+            // Capturing an undefended pawn.
+            ("4k4/9/9/9/4p4/4P4/9/9/4K4 b - 1", "5f5e", 90),
+            // Capturing a pawn defended by a gold.
+            ("4k4/9/9/4g4/4p4/9/9/4R4/4K4 b - 1", "5h5e", -900),
+            // The second rook behind recaptures the gold.
+            ("k8/9/9/4g4/4p4/9/9/4R4/4R3K b - 1", "5h5e", -360),
+            // A lance behind the gold joins the exchange as well.
+            ("k3l4/9/9/4g4/4p4/9/9/4R4/4R3K b - 1", "5h5e", -900),
+            // Capturing a gold with a pawn defended by a silver.
+            ("4k4/9/3s5/4g4/4P4/9/9/9/4K4 b - 1", "5e5d", 450),
+            // Promotion gained by capturing is lost when recaptured.
+            ("4k4/4g4/4p4/4P4/9/9/9/9/4K4 b - 1", "5d5c+", 0),
+            ("4k4/9/4p4/4P4/9/9/9/9/4K4 b - 1", "5d5c+", 540),
+            // The king can't recapture a defended piece.
+            ("9/9/9/9/3k5/4p4/4P4/4R4/4K4 b - 1", "5g5f", 90),
+            ("9/9/9/9/3k5/4p4/4P4/9/4K4 b - 1", "5g5f", 0),
+            // A dropped piece is captured.
+            ("4k4/9/9/4p4/9/9/9/9/4K4 b G 1", "G*5e", -540),
+            ("4k4/9/9/9/9/9/9/9/4K4 b G 1", "G*5e", 0),
+            // A silver recaptures before a tokin.
+            ("4k4/9/9/4+ps3/4p4/3GP4/9/9/4K4 b - 1", "6f5e", -45),
+        ];
+
+        let mut pos = Position::new();
+        for (i, case) in cases.iter().enumerate() {
+            pos.set_sfen(case.0).expect("failed to parse SFEN string");
+            let m = Move::from_sfen(case.1).unwrap();
+
+            assert_eq!(case.2, pos.see(m), "failed at #{i}");
+        }
+    }
+
+    #[test]
+    fn see_order() {
+        for (i, pt) in SEE_ORDER.iter().enumerate().skip(1) {
+            assert!(
+                see_value(SEE_ORDER[i - 1]) <= see_value(*pt),
+                "failed at #{i}"
+            );
+        }
+    }
+
+    #[test]
+    fn see_ge() {
+        setup();
+
+        let cases = [
+            "4k4/9/9/9/4p4/4P4/9/9/4K4 b - 1",
+            "4k4/9/9/4g4/4p4/9/9/4R4/4K4 b - 1",
+            "k8/9/9/4g4/4p4/9/9/4R4/4R3K b - 1",
+            "k3l4/9/9/4g4/4p4/9/9/4R4/4R3K b - 1",
+            "4k4/9/3s5/4g4/4P4/9/9/9/4K4 b - 1",
+            "4k4/4g4/4p4/4P4/9/9/9/9/4K4 b - 1",
+            "9/9/9/9/3k5/4p4/4P4/4R4/4K4 b - 1",
+            "9/9/9/9/3k5/4p4/4P4/9/4K4 b - 1",
+            "4k4/9/9/4p4/9/9/9/9/4K4 b G 1",
+            "4k4/9/9/4+ps3/4p4/3GP4/9/9/4K4 b - 1",
+            "l6nl/4+p+P1gk/2n2S3/p1p4Pp/3P2Sp1/1PPb2P1P/4+P1GS1/R8/LN4bKL w RG5gsnp 1",
+        ];
+
+        let mut pos = Position::new();
+        for (i, case) in cases.iter().enumerate() {
+            pos.set_sfen(case).expect("failed to parse SFEN string");
+
+            for m in pos.legal_moves() {
+                let see = pos.see(m);
+                for threshold in [see - 1, see, see + 1, -1000, 0, 1000] {
+                    assert_eq!(
+                        see >= threshold,
+                        pos.see_ge(m, threshold),
+                        "failed at #{i}: {m} {threshold}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn legal_moves_exceeding_move_list() {
+        setup();
+
+        // Unreachable in a game, but accepted by `set_sfen`.
+        let pos = Position::from_sfen("+R8/2+R6/4+R4/6+R2/8+R/1+B7/3+B5/5+B3/K6+B1 b RBGSNLP 1")
+            .expect("failed to parse SFEN string");
+
+        let legal_moves = pos.legal_moves();
+        assert!(legal_moves.len() > MAX_MOVES);
+        assert_eq!(610, legal_moves.len());
+        assert_eq!(legal_moves.len(), pos.captures().len() + pos.quiets().len());
+        assert_eq!(legal_moves.len(), pos.evasion_moves().len());
+    }
+
+    #[test]
+    fn evasion_moves() {
+        setup();
+
+        let cases = [
+            ("4k4/9/9/9/4R3B/9/9/9/4K4 w - 1", 3),
+            ("4k4/9/9/9/4R4/9/9/9/4K4 w g 1", 7),
+            ("3gkg3/9/9/9/4R4/9/9/9/4K4 w g 1", 7),
+            ("4k4/9/3N5/9/9/9/9/9/4K4 w r 1", 5),
+            ("4k4/9/9/9/9/9/9/4l4/4K4 b P 1", 5),
+            ("9/9/9/9/4l4/9/9/4K4/9 b S 1", 8),
+            ("k8/9/9/9/9/9/9/9/8K w - 1", 3),
+            (
+                "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1",
+                30,
+            ),
+        ];
+
+        let mut pos = Position::new();
+        for (i, case) in cases.iter().enumerate() {
+            pos.set_sfen(case.0).expect("failed to parse SFEN string");
+
+            let mut evasions = pos
+                .evasion_moves()
+                .iter()
+                .map(|m| m.to_string())
+                .collect::<Vec<_>>();
+            let mut legal_moves = pos
+                .legal_moves()
+                .iter()
+                .map(|m| m.to_string())
+                .collect::<Vec<_>>();
+            evasions.sort();
+            legal_moves.sort();
+
+            assert_eq!(legal_moves, evasions, "failed at #{i}");
+            assert_eq!(case.1, evasions.len(), "failed at #{i}: {evasions:?}");
+        }
+    }
+
+    #[test]
+    fn legal_drops() {
+        setup();
+
+        let cases = [
+            ("4k4/9/9/9/9/9/9/9/4K4 b P 1", PieceType::Pawn, 71),
+            // Nifu.
+            ("4k4/9/9/9/9/9/4P4/9/4K4 b P 1", PieceType::Pawn, 64),
+            // Uchifuzume.
+            ("kn7/9/1G7/9/9/9/9/9/4K4 b P 1", PieceType::Pawn, 69),
+            ("4k4/9/9/9/9/9/9/9/4K4 w n 1", PieceType::Knight, 62),
+            // Only interpositions are allowed.
+            ("4k4/9/9/9/4r4/9/9/9/4K4 b G 1", PieceType::Gold, 3),
+            ("4k4/9/9/9/9/9/9/9/4K4 b G 1", PieceType::Silver, 0),
+            ("4k4/9/9/9/9/9/9/9/4K4 w G 1", PieceType::Gold, 0),
+            ("4k4/9/9/9/9/9/9/9/4K4 b P 1", PieceType::King, 0),
+        ];
+
+        for (i, case) in cases.iter().enumerate() {
+            let pos = Position::from_sfen(case.0).expect("failed to parse SFEN string");
+
+            let drops = pos.legal_drops(case.1);
+            assert_eq!(case.2, drops.count(), "failed at #{i}");
+
+            let expected = pos
+                .legal_moves()
+                .into_iter()
+                .filter_map(|m| match m {
+                    Move::Drop { to, piece_type } if piece_type == case.1 => Some(to),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(
+                expected,
+                drops.squares().collect::<Vec<_>>(),
+                "failed at #{i}"
+            );
+        }
+    }
+
+    #[test]
+    fn legal_moves_from() {
+        setup();
+
+        let cases = [
+            ("4r4/9/9/9/9/9/9/4G4/4K4 b - 1", SQ_5H, vec!["5h5g"]),
+            (
+                "4k4/9/9/2S6/9/9/9/9/4K4 b - 1",
+                SQ_7D,
+                vec![
+                    "7d6c+", "7d6c", "7d7c+", "7d7c", "7d8c+", "7d8c", "7d6e", "7d8e",
+                ],
+            ),
+            ("4k4/2P6/9/9/9/9/9/9/4K4 b - 1", SQ_7B, vec!["7b7a+"]),
+            ("4k4/2P6/9/9/9/9/9/9/4K4 b - 1", SQ_5A, vec![]),
+            ("4k4/2P6/9/9/9/9/9/9/4K4 b - 1", SQ_5E, vec![]),
+        ];
+
+        for (i, case) in cases.iter().enumerate() {
+            let pos = Position::from_sfen(case.0).expect("failed to parse SFEN string");
+
+            let mut moves = pos
+                .legal_moves_from(case.1)
+                .iter()
+                .map(|m| m.to_string())
+                .collect::<Vec<_>>();
+            let mut expected = case.2.iter().map(|m| m.to_string()).collect::<Vec<_>>();
+            moves.sort();
+            expected.sort();
+            assert_eq!(expected, moves, "failed at #{i}");
+        }
+
+        // Grouping the moves by origin gives all legal moves except drops.
+        let pos = Position::from_sfen(
+            "ln1g3nl/1r2k1sb1/2sp1pgp1/p1p1p1p1p/1p3P3/P1PPP1P1P/1PB1S1N2/2R1K1S2/LN1G1G2L b Pp 1",
+        )
+        .expect("failed to parse SFEN string");
+        let mut grouped = Square::iter()
+            .flat_map(|sq| pos.legal_moves_from(sq))
+            .collect::<Vec<_>>();
+        let mut legal_moves = pos
+            .legal_moves()
+            .into_iter()
+            .filter(|m| matches!(m, Move::Normal { .. }))
+            .collect::<Vec<_>>();
+        grouped.sort();
+        legal_moves.sort();
+        assert_eq!(legal_moves, grouped);
+    }
+
+    #[test]
+    fn perft() {
+        setup();
+
+        fn perft(pos: &mut Position, depth: usize) -> usize {
+            if depth == 0 {
+                return 1;
+            }
+
+            let mut count = 0;
+            for m in pos.legal_moves() {
+                pos.make_move(m).expect("failed to make a move");
+                count += perft(pos, depth - 1);
+                pos.unmake_move().expect("failed to unmake a move");
+            }
+            count
+        }
+
+        let mut pos = Position::initial();
+        assert_eq!(30, perft(&mut pos, 1));
+        assert_eq!(900, perft(&mut pos, 2));
+        assert_eq!(25470, perft(&mut pos, 3));
+    }
+
+    #[test]
+    fn is_checkmate() {
+        setup();
+
+        let cases = [
+            (
+                "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1",
+                false,
+            ),
+            ("8k/8G/8P/9/9/9/9/9/9 w - 1", true),
+            ("8k/8G/9/9/9/9/9/9/9 w - 1", false),
+            ("7sk/8G/8P/9/9/9/9/9/9 w - 1", false),
+            // No legal moves, but not in check.
+            ("8k/6G2/7G1/9/9/9/9/9/9 w - 1", false),
+        ];
+
+        let mut pos = Position::new();
+        for (i, case) in cases.iter().enumerate() {
+            pos.set_sfen(case.0).expect("failed to parse SFEN string");
+            assert_eq!(case.1, pos.is_checkmate(), "failed at #{i}");
+        }
+    }
+
+    #[test]
+    fn is_mate_in_one() {
+        setup();
+
+        let cases = [
+            (
+                "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1",
+                None,
+            ),
+            (
+                "8k/9/8P/9/9/9/9/9/9 b G 1",
+                Some(Move::Drop {
+                    to: SQ_1B,
+                    piece_type: PieceType::Gold,
+                }),
+            ),
+            (
+                "7nk/9/6SG1/9/9/9/9/9/9 b - 1",
+                Some(Move::Normal {
+                    from: SQ_2C,
+                    to: SQ_2B,
+                    promote: false,
+                }),
+            ),
+            // Dropping the pawn is uchifuzume.
+            ("9/9/7sp/6ppk/9/7G1/9/9/9 b P 1", None),
+        ];
+
+        let mut pos = Position::new();
+        for (i, case) in cases.iter().enumerate() {
+            pos.set_sfen(case.0).expect("failed to parse SFEN string");
+            let sfen = pos.to_sfen();
+            assert_eq!(case.1, pos.is_mate_in_one(), "failed at #{i}");
+            assert_eq!(sfen, pos.to_sfen(), "failed at #{i}");
+        }
+    }
+
+    #[test]
+    fn make_normal_move() {
+        setup();
+
+        let base_sfen = "l6nl/5+P1gk/2np1S3/p1p4Pp/3P2Sp1/1PPb2P1P/P5GS1/R8/LN4bKL w GR5pnsg 1";
+        let test_cases = [
+            (SQ_2B, SQ_2C, false, true),
+            (SQ_7C, SQ_6E, false, true),
+            (SQ_3I, SQ_4H, true, true),
+            (SQ_6F, SQ_9I, true, true),
+            (SQ_2B, SQ_2C, false, true),
+            (SQ_9C, SQ_9D, false, false),
+            (SQ_9B, SQ_8B, false, false),
+            (SQ_9B, SQ_9D, false, false),
+            (SQ_2B, SQ_2C, true, false),
+        ];
+
+        let mut pos = Position::new();
+        for case in test_cases.iter() {
+            pos.set_sfen(base_sfen)
+                .expect("failed to parse SFEN string");
+            assert_eq!(
+                case.3,
+                pos.make_normal_move(case.0, case.1, case.2, true).is_ok()
+            );
+        }
+
+        // Leaving the checked king is illegal.
+        pos.set_sfen("9/3r5/9/9/6B2/9/9/9/3K5 b P 1")
+            .expect("failed to parse SFEN string");
+        assert!(pos.make_normal_move(SQ_6I, SQ_6H, false, true).is_err());
+        pos.set_sfen("9/3r5/9/9/6B2/9/9/9/3K5 b P 1")
+            .expect("failed to parse SFEN string");
+        assert!(pos.make_normal_move(SQ_6I, SQ_7I, false, true).is_ok());
+    }
+
+    #[test]
+    fn make_drop_move() {
+        setup();
+
+        let base_sfen = "l6nl/5+P1gk/2np1S3/p1p4Pp/3P2Sp1/1PPb2P1P/P5GS1/R8/LN4bKL w GR5pnsg 1";
+        let test_cases = [
+            (SQ_5E, PieceType::Pawn, true),
+            (SQ_5E, PieceType::Rook, false),
+            (SQ_9A, PieceType::Pawn, false),
+            (SQ_6F, PieceType::Pawn, false),
+            (SQ_9B, PieceType::Pawn, false),
+            (SQ_5I, PieceType::Pawn, false),
+        ];
+
+        let mut pos = Position::new();
+        for case in test_cases.iter() {
+            pos.set_sfen(base_sfen)
+                .expect("failed to parse SFEN string");
+            assert_eq!(
+                case.2,
+                pos.make_move(Move::Drop {
+                    to: case.0,
+                    piece_type: case.1,
+                })
+                .is_ok()
+            );
+        }
+    }
+
+    #[test]
+    fn nifu() {
+        setup();
+
+        let ng_cases = [(
+            "ln1g5/1ks1g3l/1p2p1n2/p1pGs2rp/1P1N1ppp1/P1SB1P2P/1S1p1bPP1/LKG6/4R2NL \
+             w 2Pp 91",
+            SQ_6C,
+        )];
+        let ok_cases = [(
+            "ln1g5/1ks1g3l/1p2p1n2/p1pGs2rp/1P1N1ppp1/P1SB1P2P/1S1+p1bPP1/LKG6/4R2NL \
+             w 2Pp 91",
+            SQ_6C,
+        )];
+
+        let mut pos = Position::new();
+        for (i, case) in ng_cases.iter().enumerate() {
+            pos.set_sfen(case.0).expect("failed to parse SFEN string");
+            assert_eq!(
+                Some(MoveError::Nifu),
+                pos.make_move(Move::Drop {
+                    to: case.1,
+                    piece_type: PieceType::Pawn,
+                })
+                .err(),
+                "failed at #{i}"
+            );
+        }
+
+        for (i, case) in ok_cases.iter().enumerate() {
+            pos.set_sfen(case.0).expect("failed to parse SFEN string");
+            assert!(
+                pos.make_move(Move::Drop {
+                    to: case.1,
+                    piece_type: PieceType::Pawn,
+                })
+                .is_ok(),
+                "failed at #{i}"
+            );
+        }
+    }
+
+    #[test]
+    fn uchifuzume() {
+        setup();
+
+        let ng_cases = [
+            ("9/9/7sp/6ppk/9/7G1/9/9/9 b P 1", SQ_1E),
             ("7nk/9/7S1/6b2/9/9/9/9/9 b P 1", SQ_1B),
             ("7nk/7g1/6BS1/9/9/9/9/9/9 b P 1", SQ_1B),
             ("R6gk/9/7S1/9/9/9/9/9/9 b P 1", SQ_1B),
         ];
         let ok_cases = [
-            ("9/9/7pp/6psk/9/7G1/7N1/9/9 b P 1", SQ_1E),
-            ("7nk/9/7Sg/6b2/9/9/9/9/9 b P 1", SQ_1B),
+            ("9/9/7pp/6psk/9/7G1/7N1/9/9 b P 1", SQ_1E),
+            ("7nk/9/7Sg/6b2/9/9/9/9/9 b P 1", SQ_1B),
+            (
+                "9/8p/3pG1gp1/2p2kl1N/3P1p1s1/lPP6/2SGBP3/PK1S2+p2/LN7 w RSL3Prbg2n4p 1",
+                SQ_8G,
+            ),
+            ("7k1/5G2l/6B2/9/9/9/9/9/9 b NP 1", SQ_2B),
+        ];
+
+        let mut pos = Position::new();
+        for (i, case) in ng_cases.iter().enumerate() {
+            pos.set_sfen(case.0).expect("failed to parse SFEN string");
+            assert_eq!(
+                Some(MoveError::Uchifuzume),
+                pos.make_move(Move::Drop {
+                    to: case.1,
+                    piece_type: PieceType::Pawn,
+                })
+                .err(),
+                "failed at #{i}"
+            );
+        }
+
+        for (i, case) in ok_cases.iter().enumerate() {
+            pos.set_sfen(case.0).expect("failed to parse SFEN string");
+            assert!(
+                pos.make_move(Move::Drop {
+                    to: case.1,
+                    piece_type: PieceType::Pawn,
+                })
+                .is_ok(),
+                "failed at #{i}"
+            );
+        }
+    }
+
+    #[test]
+    fn is_nifu() {
+        setup();
+
+        let cases = [
+            ("4k4/9/9/9/9/9/4P4/9/4K4 b P 1", Color::Black, 4, true),
+            ("4k4/9/9/9/9/9/4P4/9/4K4 b P 1", Color::Black, 3, false),
+            ("4k4/9/9/9/9/9/4P4/9/4K4 b P 1", Color::White, 4, false),
+            ("4k4/9/9/9/9/9/4+P4/9/4K4 b P 1", Color::Black, 4, false),
+            ("4k4/4p4/9/9/9/9/9/9/4K4 b p 1", Color::White, 4, true),
+        ];
+
+        for (i, case) in cases.iter().enumerate() {
+            let pos = Position::from_sfen(case.0).expect("failed to parse SFEN string");
+            assert_eq!(case.3, pos.is_nifu(case.1, case.2), "failed at #{i}");
+        }
+    }
+
+    #[test]
+    fn would_be_uchifuzume() {
+        setup();
+
+        let cases = [
+            ("9/9/7sp/6ppk/9/7G1/9/9/9 b P 1", SQ_1E, true),
+            ("R6gk/9/7S1/9/9/9/9/9/9 b P 1", SQ_1B, true),
+            ("kn7/9/1G7/9/9/9/9/9/4K4 b P 1", SQ_9B, true),
+            ("kn7/9/1G7/9/9/9/9/9/4K4 b P 1", SQ_9C, false),
+            ("9/9/7pp/6psk/9/7G1/7N1/9/9 b P 1", SQ_1E, false),
+            ("7nk/9/7Sg/6b2/9/9/9/9/9 b P 1", SQ_1B, false),
+        ];
+
+        for (i, case) in cases.iter().enumerate() {
+            let pos = Position::from_sfen(case.0).expect("failed to parse SFEN string");
+            assert_eq!(case.2, pos.would_be_uchifuzume(case.1), "failed at #{i}");
+        }
+    }
+
+    #[test]
+    fn repetition() {
+        setup();
+
+        let mut pos = Position::new();
+        pos.set_sfen("ln7/ks+R6/pp7/9/9/9/9/9/9 b Ss 1")
+            .expect("failed to parse SFEN string");
+
+        for _ in 0..2 {
+            assert!(pos.make_drop_move(SQ_7A, PieceType::Silver, true).is_ok());
+            assert!(pos.make_drop_move(SQ_7C, PieceType::Silver, true).is_ok());
+            assert!(pos.make_normal_move(SQ_7A, SQ_8B, true, true).is_ok());
+            assert!(pos.make_normal_move(SQ_7C, SQ_8B, false, true).is_ok());
+        }
+
+        assert!(pos.make_drop_move(SQ_7A, PieceType::Silver, true).is_ok());
+        assert!(pos.make_drop_move(SQ_7C, PieceType::Silver, true).is_ok());
+        assert!(pos.make_normal_move(SQ_7A, SQ_8B, true, true).is_ok());
+        assert_eq!(
+            Some(MoveError::Repetition),
+            pos.make_normal_move(SQ_7C, SQ_8B, false, true).err()
+        );
+    }
+
+    #[test]
+    fn make_move_fast() {
+        setup();
+
+        let moves = ["S*7a", "S*7c", "7a8b+", "7c8b"]
+            .iter()
+            .map(|m| Move::from_sfen(m).unwrap())
+            .collect::<Vec<_>>();
+
+        let mut pos = Position::new();
+        pos.set_sfen("ln7/ks+R6/pp7/9/9/9/9/9/9 b Ss 1")
+            .expect("failed to parse SFEN string");
+        let sfen = pos.to_sfen();
+
+        // Repetition is never detected in fast mode.
+        for _ in 0..4 {
+            for m in moves.iter() {
+                assert_eq!(Ok(()), pos.make_move_fast(*m));
+            }
+        }
+        assert_eq!(17, pos.ply());
+
+        // Moves are still validated.
+        assert_eq!(
+            Err(MoveError::Inconsistent("No piece found")),
+            pos.make_move_fast(moves[3])
+        );
+
+        for _ in 0..16 {
+            assert_eq!(Ok(()), pos.unmake_move_fast());
+        }
+        assert_eq!(sfen, pos.to_sfen());
+
+        // The repetition history is intact after unmaking fast moves.
+        for m in moves.iter().cycle().take(11) {
+            assert_eq!(Ok(()), pos.make_move(*m));
+        }
+        assert_eq!(Err(MoveError::Repetition), pos.make_move(moves[3]));
+    }
+
+    #[test]
+    fn repetition_count() {
+        setup();
+
+        let mut pos = Position::new();
+        assert_eq!(0, pos.repetition_count());
+
+        pos.set_sfen("ln7/ks+R6/pp7/9/9/9/9/9/9 b Ss 1")
+            .expect("failed to parse SFEN string");
+        assert_eq!(1, pos.repetition_count());
+
+        for i in 0..2 {
+            assert!(pos.make_drop_move(SQ_7A, PieceType::Silver, true).is_ok());
+            assert_eq!(i + 1, pos.repetition_count());
+            assert!(pos.make_drop_move(SQ_7C, PieceType::Silver, true).is_ok());
+            assert_eq!(i + 1, pos.repetition_count());
+            assert!(pos.make_normal_move(SQ_7A, SQ_8B, true, true).is_ok());
+            assert_eq!(i + 1, pos.repetition_count());
+            assert!(pos.make_normal_move(SQ_7C, SQ_8B, false, true).is_ok());
+            assert_eq!(i + 2, pos.repetition_count());
+        }
+
+        pos.set_sfen(
+            "ln7/ks+R6/pp7/9/9/9/9/9/9 b Ss 1 moves S*7a S*7c 7a8b+ 7c8b S*7a S*7c 7a8b+ 7c8b",
+        )
+        .expect("failed to parse SFEN string");
+        assert_eq!(3, pos.repetition_count());
+    }
+
+    #[test]
+    fn zobrist_hash() {
+        setup();
+
+        let mut pos1 = Position::new();
+        let mut pos2 = Position::new();
+
+        // Same positions reached by different move orders.
+        pos1.set_sfen(
+            "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1 moves 7g7f 3c3d 2g2f",
+        )
+        .expect("failed to parse SFEN string");
+        pos2.set_sfen(
+            "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1 moves 2g2f 3c3d 7g7f",
+        )
+        .expect("failed to parse SFEN string");
+        assert_eq!(pos1.zobrist_hash(), pos2.zobrist_hash());
+
+        // The number of plies is not a part of the hash.
+        pos1.set_sfen("7k1/9/7P1/9/9/9/9/9/9 b G2r2b3g4s4n4l17p 1")
+            .expect("failed to parse SFEN string");
+        pos2.set_sfen("7k1/9/7P1/9/9/9/9/9/9 b G2r2b3g4s4n4l17p 99")
+            .expect("failed to parse SFEN string");
+        assert_eq!(pos1.zobrist_hash(), pos2.zobrist_hash());
+
+        let different = [
+            "7k1/9/7P1/9/9/9/9/9/9 w G2r2b3g4s4n4l17p 1",
+            "7k1/9/7P1/9/9/9/9/9/9 b 2r2b4g4s4n4l17p 1",
+            "7k1/9/7P1/9/9/9/9/9/9 b G2r2b3g4s4n4l16p 1",
+            "7k1/9/7p1/9/9/9/9/9/9 b G2r2b3g4s4n4l17p 1",
+            "7k1/9/6P2/9/9/9/9/9/9 b G2r2b3g4s4n4l17p 1",
+        ];
+        for (i, case) in different.iter().enumerate() {
+            pos2.set_sfen(case).expect("failed to parse SFEN string");
+            assert_ne!(pos1.zobrist_hash(), pos2.zobrist_hash(), "failed at #{i}");
+        }
+    }
+
+    #[test]
+    fn repetition_by_sfen() {
+        setup();
+
+        // Replays the cases in `repetition` and `percetual_check` through SFEN parsing.
+        let cases = [
+            (
+                "ln7/ks+R6/pp7/9/9/9/9/9/9 b Ss 1 moves S*7a S*7c 7a8b+ 7c8b S*7a S*7c 7a8b+ 7c8b S*7a S*7c 7a8b+",
+                Move::Normal {
+                    from: SQ_7C,
+                    to: SQ_8B,
+                    promote: false,
+                },
+                MoveError::Repetition,
+            ),
+            (
+                "8l/6+P2/6+Rpk/8p/9/7S1/9/9/9 b - 1 moves 3c2b 1c2d 2b3c 2d1c 3c2b 1c2d 2b3c 2d1c 3c2b 1c2d 2b3c",
+                Move::Normal {
+                    from: SQ_2D,
+                    to: SQ_1C,
+                    promote: false,
+                },
+                MoveError::PerpetualCheckWin,
+            ),
+            (
+                "6p1k/9/8+R/9/9/9/9/9/9 w - 1 moves 1a2a 1c2c 2a1a 2c1c 1a2a 1c2c 2a1a 2c1c 1a2a 1c2c 2a1a",
+                Move::Normal {
+                    from: SQ_2C,
+                    to: SQ_1C,
+                    promote: false,
+                },
+                MoveError::PerpetualCheckLose,
+            ),
+        ];
+
+        let mut pos = Position::new();
+        for (i, case) in cases.into_iter().enumerate() {
+            pos.set_sfen(case.0).expect("failed to parse SFEN string");
+            assert_eq!(Some(case.2), pos.make_move(case.1).err(), "failed at #{i}");
+        }
+    }
+
+    #[test]
+    fn game_result() {
+        setup();
+
+        let cases = [
+            (
+                "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1",
+                None,
+            ),
+            (
+                "4k4/4G4/4P4/9/9/9/9/9/9 w - 1",
+                Some(GameResult::Checkmate(Color::Black)),
+            ),
+            (
+                "8k/6G2/7G1/9/9/9/9/9/9 w - 1",
+                Some(GameResult::Stalemate(Color::Black)),
+            ),
+        ];
+
+        let mut pos = Position::new();
+        for (i, case) in cases.iter().enumerate() {
+            pos.set_sfen(case.0).expect("failed to parse SFEN string");
+            assert_eq!(case.1, pos.game_result(), "failed at #{i}");
+        }
+
+        let cases = [
+            (
+                "ln7/ks+R6/pp7/9/9/9/9/9/9 b Ss 1 moves S*7a S*7c 7a8b+ 7c8b S*7a S*7c 7a8b+ 7c8b S*7a S*7c 7a8b+",
+                "7c8b",
+                GameResult::Repetition,
+            ),
+            (
+                "8l/6+P2/6+Rpk/8p/9/7S1/9/9/9 b - 1 moves 3c2b 1c2d 2b3c 2d1c 3c2b 1c2d 2b3c 2d1c 3c2b 1c2d 2b3c",
+                "2d1c",
+                GameResult::PerpetualCheckWin,
+            ),
+            (
+                "6p1k/9/8+R/9/9/9/9/9/9 w - 1 moves 1a2a 1c2c 2a1a 2c1c 1a2a 1c2c 2a1a 2c1c 1a2a 1c2c 2a1a",
+                "2c1c",
+                GameResult::PerpetualCheckLose,
+            ),
+        ];
+
+        for (i, case) in cases.iter().enumerate() {
+            pos.set_sfen(case.0).expect("failed to parse SFEN string");
+            assert_eq!(None, pos.game_result(), "failed at #{i}");
+
+            let _ = pos.make_move(Move::from_sfen(case.1).unwrap());
+            assert_eq!(Some(case.2), pos.game_result(), "failed at #{i}");
+        }
+    }
+
+    #[test]
+    fn percetual_check() {
+        setup();
+
+        // Case 1. Starting from a check move.
+        let mut pos = Position::new();
+        pos.set_sfen("8l/6+P2/6+Rpk/8p/9/7S1/9/9/9 b - 1")
+            .expect("failed to parse SFEN string");
+
+        for _ in 0..2 {
+            assert!(pos.make_normal_move(SQ_3C, SQ_2B, false, true).is_ok());
+            assert!(pos.make_normal_move(SQ_1C, SQ_2D, false, true).is_ok());
+            assert!(pos.make_normal_move(SQ_2B, SQ_3C, false, true).is_ok());
+            assert!(pos.make_normal_move(SQ_2D, SQ_1C, false, true).is_ok());
+        }
+        assert!(pos.make_normal_move(SQ_3C, SQ_2B, false, true).is_ok());
+        assert!(pos.make_normal_move(SQ_1C, SQ_2D, false, true).is_ok());
+        assert!(pos.make_normal_move(SQ_2B, SQ_3C, false, true).is_ok());
+        assert_eq!(
+            Some(MoveError::PerpetualCheckWin),
+            pos.make_normal_move(SQ_2D, SQ_1C, false, true).err()
+        );
+
+        // Case 2. Starting from an escape move.
+        pos.set_sfen("6p1k/9/8+R/9/9/9/9/9/9 w - 1")
+            .expect("failed to parse SFEN string");
+
+        for _ in 0..2 {
+            assert!(pos.make_normal_move(SQ_1A, SQ_2A, false, true).is_ok());
+            assert!(pos.make_normal_move(SQ_1C, SQ_2C, false, true).is_ok());
+            assert!(pos.make_normal_move(SQ_2A, SQ_1A, false, true).is_ok());
+            assert!(pos.make_normal_move(SQ_2C, SQ_1C, false, true).is_ok());
+        }
+        assert!(pos.make_normal_move(SQ_1A, SQ_2A, false, true).is_ok());
+        assert!(pos.make_normal_move(SQ_1C, SQ_2C, false, true).is_ok());
+        assert!(pos.make_normal_move(SQ_2A, SQ_1A, false, true).is_ok());
+        assert_eq!(
+            Some(MoveError::PerpetualCheckLose),
+            pos.make_normal_move(SQ_2C, SQ_1C, false, true).err()
+        );
+    }
+
+    #[test]
+    fn apply_moves() {
+        setup();
+
+        let moves = ["7g7f", "3c3d", "8h2b+", "3a2b"].map(|s| Move::from_sfen(s).unwrap());
+        let mut pos = Position::initial();
+        assert!(pos.apply_moves(&moves).is_ok());
+        assert_eq!(
+            "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1 moves 7g7f 3c3d 8h2b+ 3a2b",
+            pos.to_sfen()
+        );
+
+        let mut pos = Position::initial();
+        assert_eq!(
+            Err((
+                1,
+                MoveError::Inconsistent("The piece is not for the side to move")
+            )),
+            pos.apply_moves(&[moves[0], moves[2]])
+        );
+        assert_eq!(1, pos.move_history().len());
+    }
+
+    #[test]
+    fn play_usi_moves() {
+        setup();
+
+        let mut pos = Position::initial();
+        assert!(pos.play_usi_moves("7g7f 3c3d").is_ok());
+        assert_eq!(2, pos.move_history().len());
+
+        let mut pos = Position::initial();
+        assert_eq!(
+            Err(SfenError::MoveFailed {
+                index: 1,
+                error: MoveError::Inconsistent("The piece is not for the side to move")
+            }),
+            pos.play_usi_moves("moves 7g7f 7f7e")
+        );
+
+        let mut pos = Position::initial();
+        assert_eq!(
+            Err(SfenError::IllegalMove),
+            pos.play_usi_moves("moves 7g7f foo")
+        );
+        assert_eq!(1, pos.move_history().len());
+    }
+
+    #[test]
+    fn move_record() {
+        setup();
+
+        let pos = Position::from_sfen(
+            "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1 moves 7g7f 3c3d 8h2b+ 3a2b B*4e",
+        )
+        .expect("failed to parse SFEN string");
+        let cases = [
+            (SQ_7F, PieceType::Pawn, Color::Black),
+            (SQ_3D, PieceType::Pawn, Color::White),
+            (SQ_2B, PieceType::ProBishop, Color::Black),
+            (SQ_2B, PieceType::Silver, Color::White),
+            (SQ_4E, PieceType::Bishop, Color::Black),
+        ];
+
+        assert_eq!(cases.len(), pos.move_history().len());
+        for (i, (record, case)) in pos.move_history().iter().zip(cases.iter()).enumerate() {
+            assert_eq!(case.0, record.destination(), "failed at #{i}");
+            assert_eq!(
+                Piece {
+                    piece_type: case.1,
+                    color: case.2
+                },
+                record.placed_piece(),
+                "failed at #{i}"
+            );
+        }
+    }
+
+    #[test]
+    fn unmake_move() {
+        setup();
+
+        let mut pos = Position::new();
+        let base_sfen = "l6nl/4+p+P1gk/2n2S3/p1p4Pp/3P2Sp1/1PPb2P1P/4+P1GS1/R8/LN4bKL w RG5gsnp 1";
+        pos.set_sfen(base_sfen)
+            .expect("failed to parse SFEN string");
+        let base_state = format!("{pos}");
+        println!("{base_state}");
+        let test_cases = [
+            Move::Drop {
+                to: SQ_5E,
+                piece_type: PieceType::Pawn,
+            },
+            // No capture by unpromoted piece
+            Move::Normal {
+                from: SQ_6F,
+                to: SQ_7G,
+                promote: false,
+            },
+            // No capture by promoting piece
+            Move::Normal {
+                from: SQ_6F,
+                to: SQ_7G,
+                promote: true,
+            },
+            // No capture by promoted piece
+            Move::Normal {
+                from: SQ_5B,
+                to: SQ_5A,
+                promote: false,
+            },
+            // Capture of unpromoted piece by unpromoted piece
+            Move::Normal {
+                from: SQ_6F,
+                to: SQ_9I,
+                promote: false,
+            },
+            // Capture of unpromoted piece by promoting piece
+            Move::Normal {
+                from: SQ_6F,
+                to: SQ_9I,
+                promote: true,
+            },
+            // Capture of unpromoted piece by promoted piece
+            Move::Normal {
+                from: SQ_5B,
+                to: SQ_4C,
+                promote: false,
+            },
+            // Capture of promoted piece by unpromoted piece
+            Move::Normal {
+                from: SQ_6F,
+                to: SQ_5G,
+                promote: false,
+            },
+            // Capture of promoted piece by promoting piece
+            Move::Normal {
+                from: SQ_6F,
+                to: SQ_5G,
+                promote: true,
+            },
+            // Capture of promoted piece by promoted piece
+            Move::Normal {
+                from: SQ_5B,
+                to: SQ_4B,
+                promote: false,
+            },
+        ];
+
+        for case in test_cases.iter() {
+            pos.set_sfen(base_sfen)
+                .expect("failed to parse SFEN string");
+            pos.make_move(*case)
+                .unwrap_or_else(|_| panic!("failed to make a move: {case}"));
+            pos.unmake_move()
+                .unwrap_or_else(|_| panic!("failed to unmake a move: {case}"));
+            assert_eq!(
+                base_sfen,
+                pos.to_sfen(),
+                "{}",
+                format!("sfen unmatch for {case}").as_str()
+            );
+            assert_eq!(
+                base_state,
+                format!("{pos}"),
+                "{}",
+                format!("state unmatch for {case}").as_str()
+            );
+        }
+    }
+
+    #[test]
+    fn try_declare_winning() {
+        setup();
+
+        let mut pos = Position::new();
+
+        pos.set_sfen("lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1")
+            .expect("failed to parse SFEN string");
+        assert!(!pos.try_declare_winning(Color::Black));
+        assert!(!pos.try_declare_winning(Color::White));
+
+        pos.set_sfen("1K7/+NG+N+NGG3/P+S+P+P+PS3/9/7s1/9/+b+rppp+p+s1+p/3+p1+bk2/9 b R4L7Pgnp 1")
+            .expect("failed to parse SFEN string");
+        assert!(pos.try_declare_winning(Color::Black));
+        assert!(!pos.try_declare_winning(Color::White));
+
+        pos.set_sfen(
+            "1K6l/1+N7/+PG2+Ns1p1/2+N5p/6p2/3+b4P/4+p+p+bs1/+r1s4+lk/1g1g3+r1 w \
+             Gns2l11p 1",
+        )
+        .expect("failed to parse SFEN string");
+        assert!(!pos.try_declare_winning(Color::Black));
+        assert!(pos.try_declare_winning(Color::White));
+
+        pos.set_sfen(
+            "1K6l/1+N7/+PG2+Ns1p1/2+N5p/6p2/3+b4P/4+p+p+bs1/+r1s4+lk/1g1g3+r1 b \
+             Gns2l11p 1",
+        )
+        .expect("failed to parse SFEN string");
+        assert!(!pos.try_declare_winning(Color::Black));
+        assert!(!pos.try_declare_winning(Color::White));
+
+        pos.set_sfen(
+            "1K6l/1+N7/+PG2+Ns1p1/2+N5p/6p2/3+b4P/4+p+p+bs1/+r1s4+l1/1g1g3+r1 b \
+             Gns2l11p 1",
+        )
+        .expect("failed to parse SFEN string");
+        assert!(!pos.try_declare_winning(Color::Black));
+        assert!(!pos.try_declare_winning(Color::White));
+
+        pos.set_sfen(
+            "1K6l/1+N7/+PG2+Ns1p1/2+N5p/6p2/1k1+b4P/4+p+p+bs1/+r1s4+l1/1g1g3+r1 b \
+             Gns2l11p 1",
+        )
+        .expect("failed to parse SFEN string");
+        assert!(!pos.try_declare_winning(Color::Black));
+        assert!(!pos.try_declare_winning(Color::White));
+
+        pos.set_sfen(
+            "1K6l/1+N7/+PG2+Ns1p1/2+N5p/6p2/3+b4P/4+p+p+bs1/+r1s4+lk/1g1g3+rG w \
+             ns2l11p 1",
+        )
+        .expect("failed to parse SFEN string");
+        assert!(!pos.try_declare_winning(Color::Black));
+        assert!(!pos.try_declare_winning(Color::White));
+
+        pos.set_sfen("1K6l/1+N7/+PG2+Ns1p1/2+N5p/6p2/3+b4P/5+p+bs1/+r1s4+lk/1g1g3+rG w ns2l12p 1")
+            .expect("failed to parse SFEN string");
+        assert!(!pos.try_declare_winning(Color::Black));
+        assert!(!pos.try_declare_winning(Color::White));
+    }
+
+    #[test]
+    fn set_sfen_normal() {
+        setup();
+
+        let mut pos = Position::new();
+
+        pos.set_sfen("lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1")
+            .expect("failed to parse SFEN string");
+
+        let filled_squares = [
+            (0, 0, PieceType::Lance, Color::White),
+            (1, 0, PieceType::Knight, Color::White),
+            (2, 0, PieceType::Silver, Color::White),
+            (3, 0, PieceType::Gold, Color::White),
+            (4, 0, PieceType::King, Color::White),
+            (5, 0, PieceType::Gold, Color::White),
+            (6, 0, PieceType::Silver, Color::White),
+            (7, 0, PieceType::Knight, Color::White),
+            (8, 0, PieceType::Lance, Color::White),
+            (7, 1, PieceType::Rook, Color::White),
+            (1, 1, PieceType::Bishop, Color::White),
+            (0, 2, PieceType::Pawn, Color::White),
+            (1, 2, PieceType::Pawn, Color::White),
+            (2, 2, PieceType::Pawn, Color::White),
+            (3, 2, PieceType::Pawn, Color::White),
+            (4, 2, PieceType::Pawn, Color::White),
+            (5, 2, PieceType::Pawn, Color::White),
+            (6, 2, PieceType::Pawn, Color::White),
+            (7, 2, PieceType::Pawn, Color::White),
+            (8, 2, PieceType::Pawn, Color::White),
+            (0, 6, PieceType::Pawn, Color::Black),
+            (1, 6, PieceType::Pawn, Color::Black),
+            (2, 6, PieceType::Pawn, Color::Black),
+            (3, 6, PieceType::Pawn, Color::Black),
+            (4, 6, PieceType::Pawn, Color::Black),
+            (5, 6, PieceType::Pawn, Color::Black),
+            (6, 6, PieceType::Pawn, Color::Black),
+            (7, 6, PieceType::Pawn, Color::Black),
+            (8, 6, PieceType::Pawn, Color::Black),
+            (7, 7, PieceType::Bishop, Color::Black),
+            (1, 7, PieceType::Rook, Color::Black),
+            (0, 8, PieceType::Lance, Color::Black),
+            (1, 8, PieceType::Knight, Color::Black),
+            (2, 8, PieceType::Silver, Color::Black),
+            (3, 8, PieceType::Gold, Color::Black),
+            (4, 8, PieceType::King, Color::Black),
+            (5, 8, PieceType::Gold, Color::Black),
+            (6, 8, PieceType::Silver, Color::Black),
+            (7, 8, PieceType::Knight, Color::Black),
+            (8, 8, PieceType::Lance, Color::Black),
+        ];
+
+        let empty_squares = [
+            (0, 1, 1),
+            (2, 1, 5),
+            (8, 1, 1),
+            (0, 3, 9),
+            (0, 4, 9),
+            (0, 5, 9),
+            (0, 7, 1),
+            (2, 7, 5),
+            (8, 7, 1),
+        ];
+
+        let hand_pieces = [
+            (PieceType::Pawn, 0),
+            (PieceType::Lance, 0),
+            (PieceType::Knight, 0),
+            (PieceType::Silver, 0),
+            (PieceType::Gold, 0),
+            (PieceType::Rook, 0),
+            (PieceType::Bishop, 0),
+        ];
+
+        for case in filled_squares.iter() {
+            let (file, row, pt, c) = *case;
+            assert_eq!(
+                Some(Piece {
+                    piece_type: pt,
+                    color: c,
+                }),
+                *pos.piece_at(Square::new(file, row).unwrap())
+            );
+        }
+
+        for case in empty_squares.iter() {
+            let (file, row, len) = *case;
+            for i in file..(file + len) {
+                assert_eq!(None, *pos.piece_at(Square::new(i, row).unwrap()));
+            }
+        }
+
+        for case in hand_pieces.iter() {
+            let (pt, n) = *case;
+            assert_eq!(
+                n,
+                pos.hand(Piece {
+                    piece_type: pt,
+                    color: Color::Black,
+                })
+            );
+            assert_eq!(
+                n,
+                pos.hand(Piece {
+                    piece_type: pt,
+                    color: Color::White,
+                })
+            );
+        }
+
+        assert_eq!(Color::Black, pos.side_to_move());
+        assert_eq!(1, pos.ply());
+    }
+
+    #[test]
+    fn to_sfen() {
+        setup();
+
+        let test_cases = [
+            "7k1/9/7P1/9/9/9/9/9/9 b G2r2b3g4s4n4l17p 1",
+            "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1",
+            "lnsgk+Lpnl/1p5+B1/p1+Pps1ppp/9/9/9/P+r1PPpPPP/1R7/LNSGKGSN1 w BGP2p \
+             1024",
+        ];
+
+        let mut pos = Position::new();
+        for case in test_cases.iter() {
+            pos.set_sfen(case).expect("failed to parse SFEN string");
+            assert_eq!(*case, pos.to_sfen());
+        }
+    }
+
+    #[test]
+    fn from_sfen() {
+        setup();
+
+        let ok_cases = [
+            "7k1/9/7P1/9/9/9/9/9/9 b G2r2b3g4s4n4l17p 1",
+            "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1 moves 7g7f 3c3d",
+        ];
+        let ng_cases = ["", "7k1/9/7P1/9/9/9/9/9/9 b", "startpos"];
+
+        for (i, case) in ok_cases.iter().enumerate() {
+            let pos = Position::from_sfen(case).unwrap_or_else(|_| panic!("failed at #{i}"));
+            assert_eq!(*case, pos.to_sfen(), "failed at #{i}");
+        }
+
+        for (i, case) in ng_cases.iter().enumerate() {
+            assert!(Position::from_sfen(case).is_err(), "failed at #{i}");
+        }
+    }
+
+    #[test]
+    fn from_usi() {
+        setup();
+
+        let ok_cases = [
             (
-                "9/8p/3pG1gp1/2p2kl1N/3P1p1s1/lPP6/2SGBP3/PK1S2+p2/LN7 w RSL3Prbg2n4p 1",
-                SQ_8G,
+                "startpos",
+                "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1",
             ),
-            ("7k1/5G2l/6B2/9/9/9/9/9/9 b NP 1", SQ_2B),
+            (
+                "position startpos moves 7g7f 3c3d",
+                "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1 moves 7g7f 3c3d",
+            ),
+            (
+                "sfen 7k1/9/7P1/9/9/9/9/9/9 b G2r2b3g4s4n4l17p 1",
+                "7k1/9/7P1/9/9/9/9/9/9 b G2r2b3g4s4n4l17p 1",
+            ),
+            (
+                "position sfen 7k1/9/7P1/9/9/9/9/9/9 b G2r2b3g4s4n4l17p 1 moves G*2b",
+                "7k1/9/7P1/9/9/9/9/9/9 b G2r2b3g4s4n4l17p 1 moves G*2b",
+            ),
+        ];
+        let ng_cases = [
+            "",
+            "position",
+            "startpos moves 7g7z",
+            "sfen",
+            "sfen 7k1/9/7P1/9/9/9/9/9/9 b",
+            "7k1/9/7P1/9/9/9/9/9/9 b G2r2b3g4s4n4l17p 1",
         ];
 
-        let mut pos = Position::new();
-        for (i, case) in ng_cases.iter().enumerate() {
-            pos.set_sfen(case.0).expect("failed to parse SFEN string");
-            assert_eq!(
-                Some(MoveError::Uchifuzume),
-                pos.make_move(Move::Drop {
-                    to: case.1,
-                    piece_type: PieceType::Pawn,
-                })
-                .err(),
-                "failed at #{i}"
-            );
+        for (i, case) in ok_cases.iter().enumerate() {
+            let pos = Position::from_usi(case.0).unwrap_or_else(|_| panic!("failed at #{i}"));
+            assert_eq!(case.1, pos.to_sfen(), "failed at #{i}");
         }
 
-        for (i, case) in ok_cases.iter().enumerate() {
-            pos.set_sfen(case.0).expect("failed to parse SFEN string");
-            assert!(
-                pos.make_move(Move::Drop {
-                    to: case.1,
-                    piece_type: PieceType::Pawn,
-                })
-                .is_ok(),
-                "failed at #{i}"
-            );
+        for (i, case) in ng_cases.iter().enumerate() {
+            assert!(Position::from_usi(case).is_err(), "failed at #{i}");
         }
+
+        assert!(matches!(
+            Position::from_usi("position startpos moves 7g7f 7f7f"),
+            Err(SfenError::MoveFailed { index: 1, .. })
+        ));
     }
 
     #[test]
-    fn repetition() {
+    fn set_sfen_custom() {
         setup();
 
         let mut pos = Position::new();
-        pos.set_sfen("ln7/ks+R6/pp7/9/9/9/9/9/9 b Ss 1")
+        pos.set_sfen("lnsgk+Lpnl/1p5+B1/p1+Pps1ppp/9/9/9/P+r1PPpPPP/1R7/LNSGKGSN1 w BGP2p 1024")
             .expect("failed to parse SFEN string");
 
-        for _ in 0..2 {
-            assert!(pos.make_drop_move(SQ_7A, PieceType::Silver).is_ok());
-            assert!(pos.make_drop_move(SQ_7C, PieceType::Silver).is_ok());
-            assert!(pos.make_normal_move(SQ_7A, SQ_8B, true).is_ok());
-            assert!(pos.make_normal_move(SQ_7C, SQ_8B, false).is_ok());
+        let filled_squares = [
+            (8, 0, PieceType::Lance, Color::White),
+            (7, 0, PieceType::Knight, Color::White),
+            (6, 0, PieceType::Silver, Color::White),
+            (5, 0, PieceType::Gold, Color::White),
+            (4, 0, PieceType::King, Color::White),
+            (3, 0, PieceType::ProLance, Color::Black),
+            (2, 0, PieceType::Pawn, Color::White),
+            (1, 0, PieceType::Knight, Color::White),
+            (0, 0, PieceType::Lance, Color::White),
+            (7, 1, PieceType::Pawn, Color::White),
+            (1, 1, PieceType::ProBishop, Color::Black),
+            (8, 2, PieceType::Pawn, Color::White),
+            (6, 2, PieceType::ProPawn, Color::Black),
+            (5, 2, PieceType::Pawn, Color::White),
+            (4, 2, PieceType::Silver, Color::White),
+            (2, 2, PieceType::Pawn, Color::White),
+            (1, 2, PieceType::Pawn, Color::White),
+            (0, 2, PieceType::Pawn, Color::White),
+            (8, 6, PieceType::Pawn, Color::Black),
+            (7, 6, PieceType::ProRook, Color::White),
+            (5, 6, PieceType::Pawn, Color::Black),
+            (4, 6, PieceType::Pawn, Color::Black),
+            (3, 6, PieceType::Pawn, Color::White),
+            (2, 6, PieceType::Pawn, Color::Black),
+            (1, 6, PieceType::Pawn, Color::Black),
+            (0, 6, PieceType::Pawn, Color::Black),
+            (7, 7, PieceType::Rook, Color::Black),
+            (8, 8, PieceType::Lance, Color::Black),
+            (7, 8, PieceType::Knight, Color::Black),
+            (6, 8, PieceType::Silver, Color::Black),
+            (5, 8, PieceType::Gold, Color::Black),
+            (4, 8, PieceType::King, Color::Black),
+            (3, 8, PieceType::Gold, Color::Black),
+            (2, 8, PieceType::Silver, Color::Black),
+            (1, 8, PieceType::Knight, Color::Black),
+        ];
+
+        let empty_squares = [
+            (0, 1, 1),
+            (2, 1, 5),
+            (8, 1, 1),
+            (3, 2, 1),
+            (7, 2, 1),
+            (0, 3, 9),
+            (0, 4, 9),
+            (0, 5, 9),
+            (6, 6, 1),
+            (0, 7, 7),
+            (8, 7, 1),
+            (0, 8, 1),
+        ];
+
+        let hand_pieces = [
+            (
+                Piece {
+                    piece_type: PieceType::Pawn,
+                    color: Color::Black,
+                },
+                1,
+            ),
+            (
+                Piece {
+                    piece_type: PieceType::Gold,
+                    color: Color::Black,
+                },
+                1,
+            ),
+            (
+                Piece {
+                    piece_type: PieceType::Bishop,
+                    color: Color::Black,
+                },
+                1,
+            ),
+            (
+                Piece {
+                    piece_type: PieceType::Pawn,
+                    color: Color::White,
+                },
+                2,
+            ),
+        ];
+
+        for case in filled_squares.iter() {
+            let (file, row, pt, c) = *case;
+            assert_eq!(
+                Some(Piece {
+                    piece_type: pt,
+                    color: c,
+                }),
+                *pos.piece_at(Square::new(file, row).unwrap())
+            );
         }
 
-        assert!(pos.make_drop_move(SQ_7A, PieceType::Silver).is_ok());
-        assert!(pos.make_drop_move(SQ_7C, PieceType::Silver).is_ok());
-        assert!(pos.make_normal_move(SQ_7A, SQ_8B, true).is_ok());
-        assert_eq!(
-            Some(MoveError::Repetition),
-            pos.make_normal_move(SQ_7C, SQ_8B, false).err()
-        );
+        for case in empty_squares.iter() {
+            let (file, row, len) = *case;
+            for i in file..(file + len) {
+                assert_eq!(None, *pos.piece_at(Square::new(i, row).unwrap()));
+            }
+        }
+
+        for case in hand_pieces.iter() {
+            let (p, n) = *case;
+            assert_eq!(n, pos.hand(p));
+        }
+
+        assert_eq!(Color::White, pos.side_to_move());
+        assert_eq!(1024, pos.ply());
     }
 
     #[test]
-    fn percetual_check() {
+    fn to_string_perspective() {
         setup();
 
-        // Case 1. Starting from a check move.
         let mut pos = Position::new();
-        pos.set_sfen("8l/6+P2/6+Rpk/8p/9/7S1/9/9/9 b - 1")
-            .expect("failed to parse SFEN string");
-
-        for _ in 0..2 {
-            assert!(pos.make_normal_move(SQ_3C, SQ_2B, false).is_ok());
-            assert!(pos.make_normal_move(SQ_1C, SQ_2D, false).is_ok());
-            assert!(pos.make_normal_move(SQ_2B, SQ_3C, false).is_ok());
-            assert!(pos.make_normal_move(SQ_2D, SQ_1C, false).is_ok());
-        }
-        assert!(pos.make_normal_move(SQ_3C, SQ_2B, false).is_ok());
-        assert!(pos.make_normal_move(SQ_1C, SQ_2D, false).is_ok());
-        assert!(pos.make_normal_move(SQ_2B, SQ_3C, false).is_ok());
-        assert_eq!(
-            Some(MoveError::PerpetualCheckWin),
-            pos.make_normal_move(SQ_2D, SQ_1C, false).err()
-        );
-
-        // Case 2. Starting from an escape move.
-        pos.set_sfen("6p1k/9/8+R/9/9/9/9/9/9 w - 1")
+        pos.set_sfen("8k/9/9/9/9/9/9/9/K8 b Pr 1")
             .expect("failed to parse SFEN string");
 
-        for _ in 0..2 {
-            assert!(pos.make_normal_move(SQ_1A, SQ_2A, false).is_ok());
-            assert!(pos.make_normal_move(SQ_1C, SQ_2C, false).is_ok());
-            assert!(pos.make_normal_move(SQ_2A, SQ_1A, false).is_ok());
-            assert!(pos.make_normal_move(SQ_2C, SQ_1C, false).is_ok());
-        }
-        assert!(pos.make_normal_move(SQ_1A, SQ_2A, false).is_ok());
-        assert!(pos.make_normal_move(SQ_1C, SQ_2C, false).is_ok());
-        assert!(pos.make_normal_move(SQ_2A, SQ_1A, false).is_ok());
-        assert_eq!(
-            Some(MoveError::PerpetualCheckLose),
-            pos.make_normal_move(SQ_2C, SQ_1C, false).err()
-        );
+        assert_eq!(pos.to_string(), pos.to_string_perspective(Color::Black));
+
+        let black = pos.to_string_perspective(Color::Black);
+        let black_lines = black.lines().collect::<Vec<_>>();
+        assert_eq!("   9   8   7   6   5   4   3   2   1", black_lines[0]);
+        assert_eq!("|   |   |   |   |   |   |   |   |  k| a", black_lines[2]);
+        assert_eq!("|  K|   |   |   |   |   |   |   |   | i", black_lines[18]);
+        assert_eq!("Hand (Black): P1 ", black_lines[21]);
+        assert_eq!("Hand (White): r1 ", black_lines[22]);
+
+        let white = pos.to_string_perspective(Color::White);
+        let white_lines = white.lines().collect::<Vec<_>>();
+        assert_eq!("   1   2   3   4   5   6   7   8   9", white_lines[0]);
+        assert_eq!("|   |   |   |   |   |   |   |   |  K| i", white_lines[2]);
+        assert_eq!("|  k|   |   |   |   |   |   |   |   | a", white_lines[18]);
+        assert_eq!("Side to move: Black", white_lines[20]);
+        assert_eq!("Hand (White): r1 ", white_lines[21]);
+        assert_eq!("Hand (Black): P1 ", white_lines[22]);
+        assert_eq!("Ply: 1", white_lines[23]);
+        assert_eq!(24, white_lines.len());
+
+        let empty = Position::new().to_string();
+        assert_eq!(Some("(empty board)"), empty.lines().last());
     }
 
     #[test]
-    fn unmake_move() {
+    fn to_psn() {
         setup();
 
-        let mut pos = Position::new();
-        let base_sfen = "l6nl/4+p+P1gk/2n2S3/p1p4Pp/3P2Sp1/1PPb2P1P/4+P1GS1/R8/LN4bKL w RG5gsnp 1";
-        pos.set_sfen(base_sfen)
-            .expect("failed to parse SFEN string");
-        let base_state = format!("{pos}");
-        println!("{base_state}");
-        let test_cases = [
-            Move::Drop {
-                to: SQ_5E,
-                piece_type: PieceType::Pawn,
-            },
-            // No capture by unpromoted piece
-            Move::Normal {
-                from: SQ_6F,
-                to: SQ_7G,
-                promote: false,
-            },
-            // No capture by promoting piece
-            Move::Normal {
-                from: SQ_6F,
-                to: SQ_7G,
-                promote: true,
-            },
-            // No capture by promoted piece
-            Move::Normal {
-                from: SQ_5B,
-                to: SQ_5A,
-                promote: false,
-            },
-            // Capture of unpromoted piece by unpromoted piece
-            Move::Normal {
-                from: SQ_6F,
-                to: SQ_9I,
-                promote: false,
-            },
-            // Capture of unpromoted piece by promoting piece
-            Move::Normal {
-                from: SQ_6F,
-                to: SQ_9I,
-                promote: true,
-            },
-            // Capture of unpromoted piece by promoted piece
-            Move::Normal {
-                from: SQ_5B,
-                to: SQ_4C,
-                promote: false,
-            },
-            // Capture of promoted piece by unpromoted piece
-            Move::Normal {
-                from: SQ_6F,
-                to: SQ_5G,
-                promote: false,
-            },
-            // Capture of promoted piece by promoting piece
-            Move::Normal {
-                from: SQ_6F,
-                to: SQ_5G,
-                promote: true,
-            },
-            // Capture of promoted piece by promoted piece
-            Move::Normal {
-                from: SQ_5B,
-                to: SQ_4B,
-                promote: false,
-            },
+        let cases = [
+            (
+                "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1",
+                "",
+                "",
+            ),
+            (
+                "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1",
+                "6i5h 4a5b 4i4h",
+                "1.G6i-5h G4a-5b\n2.G4i-4h",
+            ),
+            (
+                "lnsgkgsn1/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL w - 1",
+                "3c3d 7g7f",
+                "1...P-3d\n2.P-7f",
+            ),
+            (
+                "4k4/9/9/2P6/9/9/9/9/4K4 b - 1",
+                "7d7c 5a5b 7c7b+ 5b5a 7b6a",
+                "1.P-7c= K-5b\n2.P-7b+ K-5a\n3.+P-6a",
+            ),
+            (
+                "4k4/9/4p4/9/9/9/9/4R4/4K4 b - 1",
+                "5h5c+ 5a4a",
+                "1.Rx5c+ K-4a",
+            ),
         ];
 
-        for case in test_cases.iter() {
-            pos.set_sfen(base_sfen)
-                .expect("failed to parse SFEN string");
-            pos.make_move(*case)
-                .unwrap_or_else(|_| panic!("failed to make a move: {case}"));
-            pos.unmake_move()
-                .unwrap_or_else(|_| panic!("failed to unmake a move: {case}"));
-            assert_eq!(
-                base_sfen,
-                pos.to_sfen(),
-                "{}",
-                format!("sfen unmatch for {case}").as_str()
-            );
-            assert_eq!(
-                base_state,
-                format!("{pos}"),
-                "{}",
-                format!("state unmatch for {case}").as_str()
-            );
+        for (i, case) in cases.iter().enumerate() {
+            let mut pos = Position::from_sfen(case.0).expect("failed to parse SFEN string");
+            let _ = pos.play_usi_moves(case.1);
+            assert_eq!(case.2, pos.to_psn(), "failed at #{i}");
         }
     }
 
     #[test]
-    fn try_declare_winning() {
+    fn to_unicode() {
         setup();
 
         let mut pos = Position::new();
-
-        pos.set_sfen("lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1")
+        pos.set_sfen("lnsgk+Lpnl/1p5+B1/p1+Pps1ppp/9/9/9/P+r1PPpPPP/1R7/LNSGKGSN1 w BGP2p 1024")
             .expect("failed to parse SFEN string");
-        assert!(!pos.try_declare_winning(Color::Black));
-        assert!(!pos.try_declare_winning(Color::White));
 
-        pos.set_sfen("1K7/+NG+N+NGG3/P+S+P+P+PS3/9/7s1/9/+b+rppp+p+s1+p/3+p1+bk2/9 b R4L7Pgnp 1")
-            .expect("failed to parse SFEN string");
-        assert!(pos.try_declare_winning(Color::Black));
-        assert!(!pos.try_declare_winning(Color::White));
+        let board = pos.to_unicode();
+        let lines = board.lines().collect::<Vec<_>>();
+        assert_eq!(16, lines.len());
+        assert_eq!("  ９ ８ ７ ６ ５ ４ ３ ２ １", lines[0]);
+        assert_eq!("+---------------------------+", lines[1]);
+        assert_eq!("|v香v桂v銀v金v王 杏v歩v桂v香|一", lines[2]);
+        assert_eq!("| ・v歩 ・ ・ ・ ・ ・ 馬 ・|二", lines[3]);
+        assert_eq!("|v歩 ・ とv歩v銀 ・v歩v歩v歩|三", lines[4]);
+        assert_eq!("| 歩v龍 ・ 歩 歩v歩 歩 歩 歩|七", lines[8]);
+        assert_eq!("| 香 桂 銀 金 玉 金 銀 桂 ・|九", lines[10]);
+        assert_eq!("Side to move: White", lines[12]);
+        assert_eq!("Hand (Black): 角1 金1 歩1 ", lines[13]);
+        assert_eq!("Hand (White): 歩2 ", lines[14]);
+        assert_eq!("Ply: 1024", lines[15]);
+    }
 
-        pos.set_sfen(
-            "1K6l/1+N7/+PG2+Ns1p1/2+N5p/6p2/3+b4P/4+p+p+bs1/+r1s4+lk/1g1g3+r1 w \
-             Gns2l11p 1",
-        )
-        .expect("failed to parse SFEN string");
-        assert!(!pos.try_declare_winning(Color::Black));
-        assert!(pos.try_declare_winning(Color::White));
+    #[test]
+    fn pretty() {
+        setup();
 
-        pos.set_sfen(
-            "1K6l/1+N7/+PG2+Ns1p1/2+N5p/6p2/3+b4P/4+p+p+bs1/+r1s4+lk/1g1g3+r1 b \
-             Gns2l11p 1",
-        )
-        .expect("failed to parse SFEN string");
-        assert!(!pos.try_declare_winning(Color::Black));
-        assert!(!pos.try_declare_winning(Color::White));
+        let mut pos = Position::new();
+        pos.set_sfen("8k/9/9/9/9/9/9/9/K8 b - 1")
+            .expect("failed to parse SFEN string");
 
-        pos.set_sfen(
-            "1K6l/1+N7/+PG2+Ns1p1/2+N5p/6p2/3+b4P/4+p+p+bs1/+r1s4+l1/1g1g3+r1 b \
-             Gns2l11p 1",
-        )
-        .expect("failed to parse SFEN string");
-        assert!(!pos.try_declare_winning(Color::Black));
-        assert!(!pos.try_declare_winning(Color::White));
+        assert_eq!(pos.to_string(), pos.pretty(None));
 
-        pos.set_sfen(
-            "1K6l/1+N7/+PG2+Ns1p1/2+N5p/6p2/1k1+b4P/4+p+p+bs1/+r1s4+l1/1g1g3+r1 b \
-             Gns2l11p 1",
-        )
-        .expect("failed to parse SFEN string");
-        assert!(!pos.try_declare_winning(Color::Black));
-        assert!(!pos.try_declare_winning(Color::White));
+        let cases = [
+            (SQ_1A, 2, "|   |   |   |   |   |   |   |   [  k] a"),
+            (SQ_9I, 18, "[  K]   |   |   |   |   |   |   |   | i"),
+            (SQ_5E, 10, "|   |   |   |   [   ]   |   |   |   | e"),
+        ];
 
-        pos.set_sfen(
-            "1K6l/1+N7/+PG2+Ns1p1/2+N5p/6p2/3+b4P/4+p+p+bs1/+r1s4+lk/1g1g3+rG w \
-             ns2l11p 1",
-        )
-        .expect("failed to parse SFEN string");
-        assert!(!pos.try_declare_winning(Color::Black));
-        assert!(!pos.try_declare_winning(Color::White));
+        for (i, case) in cases.iter().enumerate() {
+            let board = pos.pretty(Some(case.0));
+            let lines = board.lines().collect::<Vec<_>>();
+            assert_eq!(case.2, lines[case.1], "failed at #{i}");
+            assert_eq!(
+                1,
+                lines.iter().filter(|l| l.contains('[')).count(),
+                "failed at #{i}"
+            );
+        }
+    }
 
-        pos.set_sfen("1K6l/1+N7/+PG2+Ns1p1/2+N5p/6p2/3+b4P/5+p+bs1/+r1s4+lk/1g1g3+rG w ns2l12p 1")
+    #[test]
+    fn iter_pieces() {
+        setup();
+
+        let mut pos = Position::new();
+        pos.set_sfen("lnsgk+Lpnl/1p5+B1/p1+Pps1ppp/9/9/9/P+r1PPpPPP/1R7/LNSGKGSN1 w BGP2p 1024")
             .expect("failed to parse SFEN string");
-        assert!(!pos.try_declare_winning(Color::Black));
-        assert!(!pos.try_declare_winning(Color::White));
+
+        let expected = Square::iter()
+            .filter_map(|sq| pos.piece_at(sq).map(|pc| (sq, pc)))
+            .collect::<Vec<_>>();
+        assert_eq!(expected, pos.iter_pieces().collect::<Vec<_>>());
+        assert_eq!(expected, (&pos).into_iter().collect::<Vec<_>>());
+        assert_eq!(35, expected.len());
+
+        assert_eq!(0, Position::new().iter_pieces().count());
     }
 
     #[test]
-    fn set_sfen_normal() {
+    fn validate() {
         setup();
 
+        let ok_cases = [
+            "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1",
+            "ln1g5/1ks1g3l/1p2p1n2/p1pGs2rp/1P1N1ppp1/P1SB1P2P/1S1+p1bPP1/LKG6/4R2NL w 2Pp 91",
+            "8k/9/9/9/9/9/9/9/K8 b 2r2b4g4s4n4l18p 1",
+        ];
+        let ng_cases = [
+            (
+                "8k/9/9/9/9/9/9/9/9 b - 1",
+                PositionError::MissingKing(Color::Black),
+            ),
+            (
+                "7kk/9/9/9/9/9/9/9/K8 b - 1",
+                PositionError::TooManyKings(Color::White),
+            ),
+            (
+                "8k/9/9/9/9/9/9/9/K8 b 2r2b4g4s4n4l18pP 1",
+                PositionError::TooManyPieces(PieceType::Pawn),
+            ),
+            (
+                "8k/9/9/9/9/4+R4/9/9/K8 b 2r 1",
+                PositionError::TooManyPieces(PieceType::Rook),
+            ),
+            (
+                "8k/9/9/4P4/9/4P4/9/9/K8 b - 1",
+                PositionError::Nifu(Color::Black),
+            ),
+            (
+                "8k/9/9/9/9/9/9/9/K3l4 b - 1",
+                PositionError::NonMovablePiece(SQ_5I),
+            ),
+            ("8k/9/9/9/9/9/9/9/K7R b - 1", PositionError::OpponentInCheck),
+        ];
+
         let mut pos = Position::new();
+        for (i, case) in ok_cases.iter().enumerate() {
+            pos.set_sfen(case).expect("failed to parse SFEN string");
+            assert_eq!(Ok(()), pos.validate(), "failed at #{i}");
+        }
 
-        pos.set_sfen("lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1")
-            .expect("failed to parse SFEN string");
+        for (i, (sfen, err)) in ng_cases.into_iter().enumerate() {
+            pos.set_sfen(sfen).expect("failed to parse SFEN string");
+            assert_eq!(Err(err), pos.validate(), "failed at #{i}");
+        }
+    }
 
-        let filled_squares = [
-            (0, 0, PieceType::Lance, Color::White),
-            (1, 0, PieceType::Knight, Color::White),
-            (2, 0, PieceType::Silver, Color::White),
-            (3, 0, PieceType::Gold, Color::White),
-            (4, 0, PieceType::King, Color::White),
-            (5, 0, PieceType::Gold, Color::White),
-            (6, 0, PieceType::Silver, Color::White),
-            (7, 0, PieceType::Knight, Color::White),
-            (8, 0, PieceType::Lance, Color::White),
-            (7, 1, PieceType::Rook, Color::White),
-            (1, 1, PieceType::Bishop, Color::White),
-            (0, 2, PieceType::Pawn, Color::White),
-            (1, 2, PieceType::Pawn, Color::White),
-            (2, 2, PieceType::Pawn, Color::White),
-            (3, 2, PieceType::Pawn, Color::White),
-            (4, 2, PieceType::Pawn, Color::White),
-            (5, 2, PieceType::Pawn, Color::White),
-            (6, 2, PieceType::Pawn, Color::White),
-            (7, 2, PieceType::Pawn, Color::White),
-            (8, 2, PieceType::Pawn, Color::White),
-            (0, 6, PieceType::Pawn, Color::Black),
-            (1, 6, PieceType::Pawn, Color::Black),
-            (2, 6, PieceType::Pawn, Color::Black),
-            (3, 6, PieceType::Pawn, Color::Black),
-            (4, 6, PieceType::Pawn, Color::Black),
-            (5, 6, PieceType::Pawn, Color::Black),
-            (6, 6, PieceType::Pawn, Color::Black),
-            (7, 6, PieceType::Pawn, Color::Black),
-            (8, 6, PieceType::Pawn, Color::Black),
-            (7, 7, PieceType::Bishop, Color::Black),
-            (1, 7, PieceType::Rook, Color::Black),
-            (0, 8, PieceType::Lance, Color::Black),
-            (1, 8, PieceType::Knight, Color::Black),
-            (2, 8, PieceType::Silver, Color::Black),
-            (3, 8, PieceType::Gold, Color::Black),
-            (4, 8, PieceType::King, Color::Black),
-            (5, 8, PieceType::Gold, Color::Black),
-            (6, 8, PieceType::Silver, Color::Black),
-            (7, 8, PieceType::Knight, Color::Black),
-            (8, 8, PieceType::Lance, Color::Black),
+    #[test]
+    fn set_sfen_strict() {
+        setup();
+
+        let ok_cases = [
+            "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1",
+            "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1 moves 7g7f 3c3d",
+        ];
+        let ng_cases = [
+            (
+                "8k/9/9/9/9/9/9/9/KK7 b - 1",
+                SfenError::IllegalPosition(PositionError::TooManyKings(Color::Black)),
+            ),
+            (
+                "8k/9/9/4P4/9/4P4/9/9/K8 b - 1",
+                SfenError::IllegalPosition(PositionError::Nifu(Color::Black)),
+            ),
+            (
+                "P7k/9/9/9/9/9/9/9/K8 b - 1",
+                SfenError::IllegalPosition(PositionError::NonMovablePiece(SQ_9A)),
+            ),
+            ("8k/9/9/9/9/9/9/9/K8 b", SfenError::MissingDataFields),
         ];
 
-        let empty_squares = [
-            (0, 1, 1),
-            (2, 1, 5),
-            (8, 1, 1),
-            (0, 3, 9),
-            (0, 4, 9),
-            (0, 5, 9),
-            (0, 7, 1),
-            (2, 7, 5),
-            (8, 7, 1),
+        let mut pos = Position::new();
+        for (i, case) in ok_cases.iter().enumerate() {
+            assert!(pos.set_sfen_strict(case).is_ok(), "failed at #{i}");
+            assert_eq!(*case, pos.to_sfen(), "failed at #{i}");
+        }
+
+        for (i, (sfen, err)) in ng_cases.into_iter().enumerate() {
+            assert_eq!(Err(err), pos.set_sfen_strict(sfen), "failed at #{i}");
+        }
+
+        // The lenient version still accepts illegal positions.
+        assert!(pos.set_sfen("8k/9/9/9/9/9/9/9/KK7 b - 1").is_ok());
+    }
+
+    #[test]
+    fn set_sfen_checked() {
+        setup();
+
+        let sfen = "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1";
+        let ng_cases = [
+            (
+                "moves 7g7f 3c3d 3d3e",
+                SfenError::MoveFailed {
+                    index: 2,
+                    error: MoveError::Inconsistent("The piece is not for the side to move"),
+                },
+            ),
+            (
+                "moves 7g7f 3c3d R*5e",
+                SfenError::MoveFailed {
+                    index: 2,
+                    error: MoveError::Inconsistent("The piece is not in the hand"),
+                },
+            ),
+            ("moves 7g7f 3c3z", SfenError::IllegalMove),
         ];
 
-        let hand_pieces = [
-            (PieceType::Pawn, 0),
-            (PieceType::Lance, 0),
-            (PieceType::Knight, 0),
-            (PieceType::Silver, 0),
-            (PieceType::Gold, 0),
-            (PieceType::Rook, 0),
-            (PieceType::Bishop, 0),
+        let mut pos = Position::new();
+        let full = format!("{sfen} moves 7g7f 3c3d 8h2b+");
+        assert_eq!(Ok(()), pos.set_sfen_checked(&full));
+        assert_eq!(full, pos.to_sfen());
+
+        for (i, (moves, err)) in ng_cases.into_iter().enumerate() {
+            let s = format!("{sfen} {moves}");
+            assert_eq!(Err(err), pos.set_sfen_checked(&s), "failed at #{i}");
+        }
+
+        // The lenient version truncates the moves instead.
+        pos.set_sfen(&format!("{sfen} moves 7g7f 3c3d 3d3e 2g2f"))
+            .expect("failed to parse SFEN string");
+        assert_eq!(format!("{sfen} moves 7g7f 3c3d"), pos.to_sfen());
+    }
+
+    #[test]
+    fn set_sfen_repetition() {
+        setup();
+
+        let prefix = "ln7/ks+R6/pp7/9/9/9/9/9/9 b Ss 1 moves S*7a S*7c 7a8b+ 7c8b S*7a S*7c 7a8b+ 7c8b S*7a S*7c 7a8b+";
+        let full = format!("{prefix} 7c8b");
+        let expected = Position::from_sfen(prefix).expect("failed to parse SFEN string");
+
+        // The move causing repetition is not made.
+        let mut pos = Position::new();
+        pos.set_sfen(&full).expect("failed to parse SFEN string");
+        assert_eq!(prefix, pos.to_sfen());
+        assert_eq!(expected.to_sfen_current(), pos.to_sfen_current());
+        assert_eq!(expected.repetition_count(), pos.repetition_count());
+        assert_eq!(
+            Err(MoveError::Repetition),
+            pos.make_move(Move::from_sfen("7c8b").unwrap())
+        );
+
+        assert!(matches!(
+            pos.set_sfen_checked(&full),
+            Err(SfenError::MoveFailed {
+                index: 11,
+                error: MoveError::Repetition
+            })
+        ));
+        assert_eq!(prefix, pos.to_sfen());
+    }
+
+    #[test]
+    fn to_sfen_current() {
+        setup();
+
+        let cases = [
+            (
+                "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1",
+                "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1",
+            ),
+            (
+                "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1 moves 7g7f 3c3d 8h2b+",
+                "lnsgkgsnl/1r5+B1/pppppp1pp/6p2/9/2P6/PP1PPPPPP/7R1/LNSGKGSNL w B 4",
+            ),
+            (
+                "7k1/9/7P1/9/9/9/9/9/9 b G2r2b3g4s4n4l17p 1 moves G*2b",
+                "7k1/7G1/7P1/9/9/9/9/9/9 w 2r2b3g4s4n4l17p 2",
+            ),
         ];
 
-        for case in filled_squares.iter() {
-            let (file, row, pt, c) = *case;
-            assert_eq!(
-                Some(Piece {
-                    piece_type: pt,
-                    color: c,
-                }),
-                *pos.piece_at(Square::new(file, row).unwrap())
-            );
+        let mut pos = Position::new();
+        for (i, case) in cases.iter().enumerate() {
+            pos.set_sfen(case.0).expect("failed to parse SFEN string");
+            assert_eq!(case.1, pos.to_sfen_current(), "failed at #{i}");
         }
+    }
+
+    #[test]
+    fn sfen_ply_round_trip() {
+        setup();
+
+        let cases = [
+            (
+                "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1",
+                &["7g7f", "3c3d", "8h2b+", "3a2b"][..],
+            ),
+            (
+                "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL w - 10",
+                &["3c3d", "7g7f"][..],
+            ),
+        ];
 
-        for case in empty_squares.iter() {
-            let (file, row, len) = *case;
-            for i in file..(file + len) {
-                assert_eq!(None, *pos.piece_at(Square::new(i, row).unwrap()));
+        let mut pos = Position::new();
+        for (i, case) in cases.iter().enumerate() {
+            pos.set_sfen(case.0).expect("failed to parse SFEN string");
+            let initial_ply = pos.ply();
+            for m in case.1 {
+                pos.make_move(Move::from_sfen(m).unwrap())
+                    .unwrap_or_else(|_| panic!("failed at #{i}"));
             }
-        }
 
-        for case in hand_pieces.iter() {
-            let (pt, n) = *case;
+            let full = pos.to_sfen();
+            let current = pos.to_sfen_current();
             assert_eq!(
-                n,
-                pos.hand(Piece {
-                    piece_type: pt,
-                    color: Color::Black,
-                })
+                format!("{} moves {}", case.0, case.1.join(" ")),
+                full,
+                "failed at #{i}"
+            );
+            assert!(
+                current.ends_with(&format!(" {}", initial_ply as usize + case.1.len())),
+                "failed at #{i}"
             );
+
+            // Both forms describe the same position.
+            let mut from_full = Position::new();
+            from_full
+                .set_sfen(&full)
+                .expect("failed to parse SFEN string");
+            let mut from_current = Position::new();
+            from_current
+                .set_sfen(&current)
+                .expect("failed to parse SFEN string");
+            assert_eq!(current, from_full.to_sfen_current(), "failed at #{i}");
+            assert_eq!(current, from_current.to_sfen(), "failed at #{i}");
+            assert_eq!(pos.ply(), from_current.ply(), "failed at #{i}");
             assert_eq!(
-                n,
-                pos.hand(Piece {
-                    piece_type: pt,
-                    color: Color::White,
-                })
+                from_full.zobrist_hash(),
+                from_current.zobrist_hash(),
+                "failed at #{i}"
             );
         }
-
-        assert_eq!(Color::Black, pos.side_to_move());
-        assert_eq!(1, pos.ply());
     }
 
     #[test]
-    fn to_sfen() {
+    fn move_disambiguation() {
         setup();
 
-        let test_cases = [
-            "7k1/9/7P1/9/9/9/9/9/9 b G2r2b3g4s4n4l17p 1",
-            "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1",
-            "lnsgk+Lpnl/1p5+B1/p1+Pps1ppp/9/9/9/P+r1PPpPPP/1R7/LNSGKGSN1 w BGP2p \
-             1024",
+        let initial = "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1";
+        let cases = [
+            (initial, "7g7f", Disambiguation::None),
+            (initial, "4i5h", Disambiguation::Relative(Relative::Right)),
+            (initial, "6i5h", Disambiguation::Relative(Relative::Left)),
+            (
+                "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL w - 1",
+                "4a5b",
+                Disambiguation::Relative(Relative::Left),
+            ),
+            (
+                "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL w - 1",
+                "6a5b",
+                Disambiguation::Relative(Relative::Right),
+            ),
+            (
+                "4k4/9/9/9/9/9/4G4/9/3G1K3 b - 1",
+                "5g5h",
+                Disambiguation::Movement(Movement::Down),
+            ),
+            (
+                "4k4/9/9/9/9/9/4G4/9/3G1K3 b - 1",
+                "6i5h",
+                Disambiguation::Movement(Movement::Up),
+            ),
+            (
+                "4k4/9/9/9/9/9/9/3G5/5G2K b - 1",
+                "6h5h",
+                Disambiguation::Movement(Movement::Sideways),
+            ),
+            (
+                "4k4/9/9/9/9/9/9/9/3SS3K b - 1",
+                "5i5h",
+                Disambiguation::Relative(Relative::Straight),
+            ),
+            (
+                "4k4/9/9/9/9/9/9/9/3SS3K b - 1",
+                "6i5h",
+                Disambiguation::Relative(Relative::Left),
+            ),
+            (
+                "4k4/9/9/9/9/9/3S5/9/3S1S2K b - 1",
+                "6i5h",
+                Disambiguation::Both(Relative::Left, Movement::Up),
+            ),
+            (
+                "4k4/9/9/9/9/9/3S5/9/3S1S2K b - 1",
+                "4i5h",
+                Disambiguation::Relative(Relative::Right),
+            ),
+            (
+                "4k4/9/9/9/9/9/3S5/9/3S1S2K b - 1",
+                "6g5h",
+                Disambiguation::Movement(Movement::Down),
+            ),
+            (
+                "4k4/9/9/9/9/9/9/9/1K2+R+R3 b - 1",
+                "5i5h",
+                Disambiguation::Relative(Relative::Left),
+            ),
+            (
+                "4k4/9/9/9/9/9/9/9/3G1K3 b G 1",
+                "G*5h",
+                Disambiguation::Drop,
+            ),
+            (
+                "4k4/9/9/9/9/9/9/9/3G1K3 b G 1",
+                "G*5e",
+                Disambiguation::None,
+            ),
+            // The pinned gold can't move to the same square.
+            (
+                "4r4/9/9/9/9/9/9/4G4/4K1G2 b - 1",
+                "3i4h",
+                Disambiguation::None,
+            ),
         ];
 
         let mut pos = Position::new();
-        for case in test_cases.iter() {
-            pos.set_sfen(case).expect("failed to parse SFEN string");
-            assert_eq!(*case, pos.to_sfen());
+        for (i, case) in cases.iter().enumerate() {
+            pos.set_sfen(case.0).expect("failed to parse SFEN string");
+            let m = Move::from_sfen(case.1).unwrap();
+            assert_eq!(case.2, pos.move_disambiguation(&m), "failed at #{i}");
         }
     }
 
     #[test]
-    fn set_sfen_custom() {
+    fn can_must_promote() {
         setup();
 
         let mut pos = Position::new();
-        pos.set_sfen("lnsgk+Lpnl/1p5+B1/p1+Pps1ppp/9/9/9/P+r1PPpPPP/1R7/LNSGKGSN1 w BGP2p 1024")
+        pos.set_sfen("4k4/2P6/1L7/6N2/1+R7/6N2/6p2/1S7/4K4 b - 1")
             .expect("failed to parse SFEN string");
 
-        let filled_squares = [
-            (8, 0, PieceType::Lance, Color::White),
-            (7, 0, PieceType::Knight, Color::White),
-            (6, 0, PieceType::Silver, Color::White),
-            (5, 0, PieceType::Gold, Color::White),
-            (4, 0, PieceType::King, Color::White),
-            (3, 0, PieceType::ProLance, Color::Black),
-            (2, 0, PieceType::Pawn, Color::White),
-            (1, 0, PieceType::Knight, Color::White),
-            (0, 0, PieceType::Lance, Color::White),
-            (7, 1, PieceType::Pawn, Color::White),
-            (1, 1, PieceType::ProBishop, Color::Black),
-            (8, 2, PieceType::Pawn, Color::White),
-            (6, 2, PieceType::ProPawn, Color::Black),
-            (5, 2, PieceType::Pawn, Color::White),
-            (4, 2, PieceType::Silver, Color::White),
-            (2, 2, PieceType::Pawn, Color::White),
-            (1, 2, PieceType::Pawn, Color::White),
-            (0, 2, PieceType::Pawn, Color::White),
-            (8, 6, PieceType::Pawn, Color::Black),
-            (7, 6, PieceType::ProRook, Color::White),
-            (5, 6, PieceType::Pawn, Color::Black),
-            (4, 6, PieceType::Pawn, Color::Black),
-            (3, 6, PieceType::Pawn, Color::White),
-            (2, 6, PieceType::Pawn, Color::Black),
-            (1, 6, PieceType::Pawn, Color::Black),
-            (0, 6, PieceType::Pawn, Color::Black),
-            (7, 7, PieceType::Rook, Color::Black),
-            (8, 8, PieceType::Lance, Color::Black),
-            (7, 8, PieceType::Knight, Color::Black),
-            (6, 8, PieceType::Silver, Color::Black),
-            (5, 8, PieceType::Gold, Color::Black),
-            (4, 8, PieceType::King, Color::Black),
-            (3, 8, PieceType::Gold, Color::Black),
-            (2, 8, PieceType::Silver, Color::Black),
-            (1, 8, PieceType::Knight, Color::Black),
+        let cases = [
+            ("7b7a", true, true),
+            ("3d4b", true, true),
+            ("3f2d", false, false),
+            ("8c8b", true, false),
+            ("8c8a", true, true),
+            ("8e8d", false, false),
+            ("8h8g", false, false),
+            ("8h7g", false, false),
+            ("5i5h", false, false),
+            ("P*5e", false, false),
+            ("1a1b", false, false),
         ];
 
-        let empty_squares = [
-            (0, 1, 1),
-            (2, 1, 5),
-            (8, 1, 1),
-            (3, 2, 1),
-            (7, 2, 1),
-            (0, 3, 9),
-            (0, 4, 9),
-            (0, 5, 9),
-            (6, 6, 1),
-            (0, 7, 7),
-            (8, 7, 1),
-            (0, 8, 1),
-        ];
+        for (i, case) in cases.iter().enumerate() {
+            let m = Move::from_sfen(case.0).unwrap();
+            assert_eq!(case.1, pos.can_promote(&m), "failed at #{i}");
+            assert_eq!(case.2, pos.must_promote(&m), "failed at #{i}");
+        }
 
-        let hand_pieces = [
+        // White's promotion zone is on the other side.
+        pos.set_sfen("4k4/9/9/9/9/9/6p2/9/4K4 w - 1")
+            .expect("failed to parse SFEN string");
+        assert!(pos.can_promote(&Move::from_sfen("3g3h").unwrap()));
+        assert!(!pos.must_promote(&Move::from_sfen("3g3h").unwrap()));
+    }
+
+    #[test]
+    fn hash_and_eq() {
+        use std::collections::HashMap;
+
+        setup();
+
+        let cases = [("7g7f", "3c3d", "2g2f"), ("2g2f", "3c3d", "7g7f")];
+
+        let mut book = HashMap::new();
+        for (i, case) in cases.iter().enumerate() {
+            let mut pos = Position::initial();
+            for m in [case.0, case.1, case.2] {
+                pos.make_move(Move::from_sfen(m).unwrap())
+                    .unwrap_or_else(|_| panic!("failed at #{i}"));
+            }
+            *book.entry(pos).or_insert(0) += 1;
+        }
+        assert_eq!(1, book.len());
+        assert_eq!(Some(&2), book.values().next());
+
+        let mut pos = Position::new();
+        pos.set_sfen("lnsgkgsnl/1r5b1/pppppp1pp/6p2/9/2P4P1/PP1PPPP1P/1B5R1/LNSGKGSNL w - 4")
+            .expect("failed to parse SFEN string");
+        assert!(book.contains_key(&pos));
+
+        // Different ply or side to move makes a different position.
+        pos.set_sfen("lnsgkgsnl/1r5b1/pppppp1pp/6p2/9/2P4P1/PP1PPPP1P/1B5R1/LNSGKGSNL w - 10")
+            .expect("failed to parse SFEN string");
+        assert!(!book.contains_key(&pos));
+        pos.set_sfen("lnsgkgsnl/1r5b1/pppppp1pp/6p2/9/2P4P1/PP1PPPP1P/1B5R1/LNSGKGSNL b - 4")
+            .expect("failed to parse SFEN string");
+        assert!(!book.contains_key(&pos));
+    }
+
+    #[test]
+    fn same_position() {
+        setup();
+
+        let base = "lnsgkgsnl/1r5b1/pppppp1pp/6p2/9/2P4P1/PP1PPPP1P/1B5R1/LNSGKGSNL w - 4";
+        let cases = [
             (
-                Piece {
-                    piece_type: PieceType::Pawn,
-                    color: Color::Black,
-                },
-                1,
+                "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1 moves 7g7f 3c3d 2g2f",
+                true,
             ),
             (
-                Piece {
-                    piece_type: PieceType::Gold,
-                    color: Color::Black,
-                },
-                1,
+                "lnsgkgsnl/1r5b1/pppppp1pp/6p2/9/2P4P1/PP1PPPP1P/1B5R1/LNSGKGSNL w - 10",
+                true,
             ),
             (
-                Piece {
-                    piece_type: PieceType::Bishop,
-                    color: Color::Black,
-                },
+                "lnsgkgsnl/1r5b1/pppppp1pp/6p2/9/2P4P1/PP1PPPP1P/1B5R1/LNSGKGSNL b - 4",
+                false,
+            ),
+            (
+                "lnsgkgsnl/1r5b1/pppppp1pp/6p2/9/2P4P1/PP1PPPP1P/1B5R1/LNSGKGSNL w P 4",
+                false,
+            ),
+            (
+                "lnsgkgsnl/1r5b1/pppppp1pp/6p2/9/2P5P/PP1PPPP2/1B5R1/LNSGKGSNL w - 4",
+                false,
+            ),
+        ];
+
+        let pos = Position::from_sfen(base).expect("failed to parse SFEN string");
+        for (i, case) in cases.iter().enumerate() {
+            let other = Position::from_sfen(case.0).expect("failed to parse SFEN string");
+            assert_eq!(case.1, pos.same_position(&other), "failed at #{i}");
+            assert_eq!(case.1, other.same_position(&pos), "failed at #{i}");
+        }
+    }
+
+    #[test]
+    fn move_number() {
+        setup();
+
+        let moves = ["7g7f", "3c3d", "2g2f", "8c8d", "2f2e"];
+        let mut pos = Position::initial();
+        assert_eq!((1, 1), (pos.ply(), pos.move_number()));
+
+        for (i, m) in moves.iter().enumerate() {
+            pos.play_usi_moves(m)
+                .unwrap_or_else(|_| panic!("failed at #{i}"));
+            let ply = i as u16 + 2;
+            assert_eq!(ply, pos.ply(), "failed at #{i}");
+            assert_eq!(ply / 2 + ply % 2, pos.move_number(), "failed at #{i}");
+        }
+        assert_eq!((6, 3), (pos.ply(), pos.move_number()));
+
+        pos.unmake_move().unwrap();
+        assert_eq!((5, 3), (pos.ply(), pos.move_number()));
+
+        let cases = [
+            (
+                "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL w - 2",
                 1,
             ),
             (
-                Piece {
-                    piece_type: PieceType::Pawn,
-                    color: Color::White,
-                },
-                2,
+                "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 100",
+                50,
             ),
+            (
+                "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 101",
+                51,
+            ),
+        ];
+        for (i, case) in cases.iter().enumerate() {
+            pos.set_sfen(case.0).expect("failed to parse SFEN string");
+            assert_eq!(case.1, pos.move_number(), "failed at #{i}");
+        }
+    }
+
+    #[test]
+    fn snapshot() {
+        setup();
+
+        let mut pos = Position::from_sfen(
+            "ln7/ks+R6/pp7/9/9/9/9/9/9 b Ss 1 moves S*7a S*7c 7a8b+ 7c8b S*7a S*7c 7a8b+ 7c8b S*7a S*7c",
+        )
+        .expect("failed to parse SFEN string");
+        let mut snapshot = pos.snapshot();
+        let sfen = pos.to_sfen_current();
+        assert_eq!(pos, snapshot);
+        assert_eq!(sfen, snapshot.to_sfen_current());
+        assert_eq!(
+            pos.king_square(Color::Black),
+            snapshot.king_square(Color::Black)
+        );
+        assert!(snapshot.move_history().is_empty());
+        assert_eq!(1, snapshot.repetition_count());
+
+        // Repetition is detected only by the original position.
+        let moves = ["7a8b+", "7c8b"].map(|s| Move::from_sfen(s).unwrap());
+        assert!(pos.make_move(moves[0]).is_ok());
+        assert_eq!(Err(MoveError::Repetition), pos.make_move(moves[1]));
+        assert!(snapshot.apply_moves(&moves).is_ok());
+        assert_eq!(2, snapshot.move_history().len());
+
+        snapshot.unmake_move().unwrap();
+        snapshot.unmake_move().unwrap();
+        assert_eq!(sfen, snapshot.to_sfen_current());
+        assert!(snapshot.move_history().is_empty());
+    }
+
+    #[test]
+    fn set_board() {
+        setup();
+
+        let cases = [
+            "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1",
+            "l6nl/5+P1gk/2np1S3/p1p4Pp/3P2Sp1/1PPb2P1P/P5GS1/R8/LN4bKL w RGgsn5p 1",
+            "9/9/9/9/9/9/9/9/9 b - 1",
         ];
 
-        for case in filled_squares.iter() {
-            let (file, row, pt, c) = *case;
+        for (i, case) in cases.iter().enumerate() {
+            let expected = Position::from_sfen(case).expect("failed to parse SFEN string");
+
+            let mut pos = Position::from_sfen("4k4/9/9/9/9/9/9/9/4K4 b - 1 moves 5i5h")
+                .expect("failed to parse SFEN string");
+            pos.set_board(&expected.board());
+
+            assert_eq!(expected.board(), pos.board(), "failed at #{i}");
+            assert!(pos.move_history().is_empty(), "failed at #{i}");
+            for c in Color::iter() {
+                assert_eq!(
+                    expected.king_square(c),
+                    pos.king_square(c),
+                    "failed at #{i}"
+                );
+                for pt in PieceType::iter() {
+                    assert_eq!(
+                        expected.pieces(c, pt).squares().collect::<Vec<_>>(),
+                        pos.pieces(c, pt).squares().collect::<Vec<_>>(),
+                        "failed at #{i}"
+                    );
+                }
+            }
             assert_eq!(
-                Some(Piece {
-                    piece_type: pt,
-                    color: c,
-                }),
-                *pos.piece_at(Square::new(file, row).unwrap())
+                case.split(' ').next(),
+                pos.to_sfen().split(' ').next(),
+                "failed at #{i}"
             );
         }
+    }
 
-        for case in empty_squares.iter() {
-            let (file, row, len) = *case;
-            for i in file..(file + len) {
-                assert_eq!(None, *pos.piece_at(Square::new(i, row).unwrap()));
-            }
-        }
+    #[test]
+    fn null_move() {
+        setup();
 
-        for case in hand_pieces.iter() {
-            let (p, n) = *case;
-            assert_eq!(n, pos.hand(p));
-        }
+        let mut pos = Position::new();
+        pos.set_sfen("lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1 moves 7g7f")
+            .expect("failed to parse SFEN string");
+        let original = pos.clone();
+        let hash = pos.zobrist_hash();
+
+        pos.make_null_move();
+        assert_eq!(Color::Black, pos.side_to_move());
+        assert_eq!(3, pos.ply());
+        assert_ne!(hash, pos.zobrist_hash());
+
+        // Black moves twice in a row.
+        assert!(pos.make_move_fast(Move::from_sfen("7f7e").unwrap()).is_ok());
+        assert!(pos.unmake_move_fast().is_ok());
+
+        assert!(pos.unmake_null_move().is_ok());
+        assert_eq!(original, pos);
+        assert_eq!(hash, pos.zobrist_hash());
+        assert_eq!(original.to_sfen(), pos.to_sfen());
+        assert!(pos.unmake_null_move().is_err());
+    }
+
+    #[test]
+    fn null_move_history() {
+        setup();
+
+        let mut pos = Position::new();
+        pos.set_sfen("lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1 moves 7g7f")
+            .expect("failed to parse SFEN string");
+
+        pos.make_null_move();
+        assert_eq!(1, pos.repetition_count());
+        assert!(pos.make_move(Move::from_sfen("7f7e").unwrap()).is_ok());
+
+        let sfen =
+            "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1 moves 7g7f pass 7f7e";
+        assert_eq!(sfen, pos.to_sfen());
+        assert_eq!("1.P-7f --\n2.P-7e", pos.to_psn());
+        assert_eq!(2, pos.move_history().len());
+
+        // The null move can be replayed.
+        let replayed = Position::from_sfen(sfen).expect("failed to parse SFEN string");
+        assert_eq!(pos, replayed);
+        assert_eq!(sfen, replayed.to_sfen());
+        let mut played = Position::initial();
+        assert!(played.play_usi_moves("7g7f pass 7f7e").is_ok());
+        assert_eq!(sfen, played.to_sfen());
+
+        // Moves are undone in the reverse order.
+        assert!(pos.unmake_null_move().is_err());
+        assert!(pos.unmake_move().is_ok());
+        assert!(pos.unmake_move().is_err());
+        assert_eq!(None, pos.last_capture());
+        assert!(pos.unmake_null_move().is_ok());
+        assert!(pos.unmake_move().is_ok());
+        assert!(pos.unmake_move().is_ok());
+        assert_eq!(Position::initial(), pos);
+
+        // A null move as the first move of the side to move.
+        let mut pos = Position::initial();
+        pos.make_null_move();
+        assert_eq!(
+            "lnsgkgsnl/1r5b1/ppppppppp/9/9/9/PPPPPPPPP/1B5R1/LNSGKGSNL b - 1 moves pass",
+            pos.to_sfen()
+        );
+        assert_eq!("1.--", pos.to_psn());
+    }
 
+    #[test]
+    fn set_side_to_move() {
+        setup();
+
+        let mut pos = Position::new();
+        pos.set_sfen("4k4/9/9/9/9/9/9/9/4K3R b - 1 moves 1i1h")
+            .expect("failed to parse SFEN string");
         assert_eq!(Color::White, pos.side_to_move());
-        assert_eq!(1024, pos.ply());
+        assert!(!pos.in_check(Color::White));
+
+        pos.set_side_to_move(Color::Black);
+        assert_eq!(Color::Black, pos.side_to_move());
+        assert_eq!(1, pos.move_history().len());
+        assert_eq!("4k4/9/9/9/9/9/9/8R/4K4 b - 2", pos.to_sfen_current());
+        assert_eq!(pos.zobrist_hash(), pos.hash_history.last().unwrap().0);
+        assert_eq!(2, pos.hash_history.len());
+
+        // Black can move again from the edited position.
+        assert!(pos.make_move(Move::from_sfen("1h5h").unwrap()).is_ok());
+        assert_eq!("4k4/9/9/9/9/9/9/4R4/4K4 w - 3", pos.to_sfen_current());
+        assert!(pos.in_check(Color::White));
+
+        // Both moves can be undone, each giving the turn back to the side which made it.
+        assert!(pos.unmake_move().is_ok());
+        assert_eq!("4k4/9/9/9/9/9/9/8R/4K4 b - 2", pos.to_sfen_current());
+        assert!(pos.unmake_move().is_ok());
+        assert_eq!("4k4/9/9/9/9/9/9/9/4K3R b - 1", pos.to_sfen_current());
+        assert_eq!(1, pos.hash_history.len());
     }
 }
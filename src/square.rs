@@ -1,6 +1,7 @@
-use crate::Color;
-use std::fmt;
-use std::iter;
+use crate::{Color, SfenError, SquareError};
+use core::fmt;
+use core::iter;
+use core::str::FromStr;
 
 const ASCII_1: u8 = b'1';
 const ASCII_9: u8 = b'9';
@@ -27,7 +28,9 @@ const ASCII_LOWER_I: u8 = b'i';
 /// assert_eq!(4, sq.file());
 /// assert_eq!(4, sq.rank());
 /// ```
-#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+///
+/// Squares are ordered by their [`index`](#method.index).
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
 pub struct Square {
     inner: u8,
 }
@@ -36,7 +39,21 @@ impl Square {
     /// Creates a new instance of `Square`.
     ///
     /// `file` can take a value from 0('1') to 8('9'), while `rank` is from 0('a') to 9('i').
-    pub fn new(file: u8, rank: u8) -> Option<Self> {
+    /// This can be used in constant expressions as well.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::Square;
+    /// use shogi::square::consts::*;
+    ///
+    /// const CENTER: Option<Square> = Square::new(4, 4);
+    /// const CENTER_INDEX: usize = SQ_5E.index();
+    ///
+    /// assert_eq!(Some(SQ_5E), CENTER);
+    /// assert_eq!(40, CENTER_INDEX);
+    /// ```
+    pub const fn new(file: u8, rank: u8) -> Option<Self> {
         if file > 8 || rank > 8 {
             return None;
         }
@@ -46,6 +63,53 @@ impl Square {
         })
     }
 
+    /// Creates a new instance of `Square`, returning an error describing which value is out of range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::{Square, SquareError};
+    ///
+    /// assert_eq!(Ok(Square::new(4, 4).unwrap()), Square::try_new(4, 4));
+    /// assert_eq!(Err(SquareError::RankOutOfRange(9)), Square::try_new(4, 9));
+    /// ```
+    pub fn try_new(file: u8, rank: u8) -> Result<Self, SquareError> {
+        if file > 8 {
+            return Err(SquareError::FileOutOfRange(file));
+        }
+        if rank > 8 {
+            return Err(SquareError::RankOutOfRange(rank));
+        }
+
+        Ok(Square {
+            inner: file * 9 + rank,
+        })
+    }
+
+    /// Creates a new instance of `Square` without checking the range of the arguments.
+    ///
+    /// # Safety
+    ///
+    /// Both `file` and `rank` must be less than 9.
+    /// Other methods and the bitboard operations assume that a `Square` is always in the board.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::Square;
+    ///
+    /// let sq = unsafe { Square::new_unchecked(4, 4) };
+    /// assert_eq!(Square::new(4, 4), Some(sq));
+    /// ```
+    #[inline(always)]
+    pub const unsafe fn new_unchecked(file: u8, rank: u8) -> Self {
+        debug_assert!(file < 9 && rank < 9);
+
+        Square {
+            inner: file * 9 + rank,
+        }
+    }
+
     /// Creates a new instance of `Square` from SFEN formatted string.
     pub fn from_sfen(s: &str) -> Option<Self> {
         let bytes: &[u8] = s.as_bytes();
@@ -73,7 +137,7 @@ impl Square {
     }
 
     /// Creates a new instance of `Square` with the given index value.
-    pub fn from_index(index: u8) -> Option<Self> {
+    pub const fn from_index(index: u8) -> Option<Self> {
         if index >= 81 {
             return None;
         }
@@ -86,13 +150,70 @@ impl Square {
         SquareIter { current: 0 }
     }
 
+    /// Returns an iterator over the squares in the given file, from rank 'a' to 'i'.
+    ///
+    /// Nothing is yielded if `file` is out of range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::Square;
+    /// use shogi::square::consts::*;
+    ///
+    /// let squares = Square::file_iter(4).collect::<Vec<_>>();
+    /// assert_eq!(9, squares.len());
+    /// assert_eq!(SQ_5A, squares[0]);
+    /// assert_eq!(SQ_5I, squares[8]);
+    /// ```
+    pub fn file_iter(file: u8) -> impl Iterator<Item = Square> {
+        (0..9).filter_map(move |rank| Square::new(file, rank))
+    }
+
+    /// Returns an iterator over the squares in the given rank, from file '1' to '9'.
+    ///
+    /// Nothing is yielded if `rank` is out of range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::Square;
+    /// use shogi::square::consts::*;
+    ///
+    /// let squares = Square::rank_iter(4).collect::<Vec<_>>();
+    /// assert_eq!(9, squares.len());
+    /// assert_eq!(SQ_1E, squares[0]);
+    /// assert_eq!(SQ_9E, squares[8]);
+    /// ```
+    pub fn rank_iter(rank: u8) -> impl Iterator<Item = Square> {
+        (0..9).filter_map(move |file| Square::new(file, rank))
+    }
+
+    /// Returns an iterator over the squares in the promotion zone of the given color, in the same order as [`iter`](#method.iter).
+    ///
+    /// See [`Factory::promote_zone`](crate::bitboard::Factory::promote_zone) for the bitboard version.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shogi::{Color, Square};
+    /// use shogi::square::consts::*;
+    ///
+    /// let squares = Square::promotion_zone(Color::White).collect::<Vec<_>>();
+    /// assert_eq!(27, squares.len());
+    /// assert_eq!(SQ_1G, squares[0]);
+    /// assert_eq!(SQ_9I, squares[26]);
+    /// ```
+    pub fn promotion_zone(c: Color) -> impl Iterator<Item = Square> {
+        Square::iter().filter(move |sq| sq.in_promotion_zone(c))
+    }
+
     /// Returns a file of the square.
-    pub fn file(self) -> u8 {
+    pub const fn file(self) -> u8 {
         self.inner / 9
     }
 
     /// Returns a rank of the square.
-    pub fn rank(self) -> u8 {
+    pub const fn rank(self) -> u8 {
         self.inner % 9
     }
 
@@ -110,11 +231,11 @@ impl Square {
     /// assert_eq!(4, shifted.rank());
     /// ```
     #[must_use]
-    pub fn shift(self, df: i8, dr: i8) -> Option<Self> {
+    pub const fn shift(self, df: i8, dr: i8) -> Option<Self> {
         let f = self.file() as i8 + df;
         let r = self.rank() as i8 + dr;
 
-        if !(0..9).contains(&f) || !(0..9).contains(&r) {
+        if f < 0 || f >= 9 || r < 0 || r >= 9 {
             return None;
         }
 
@@ -136,26 +257,33 @@ impl Square {
     /// assert_eq!(6, sq.relative_rank(Color::Black));
     /// assert_eq!(2, sq.relative_rank(Color::White));
     /// ```
-    pub fn relative_rank(self, c: Color) -> u8 {
-        if c == Color::Black {
-            self.rank()
-        } else {
-            8 - self.rank()
+    pub const fn relative_rank(self, c: Color) -> u8 {
+        match c {
+            Color::Black => self.rank(),
+            Color::White => 8 - self.rank(),
         }
     }
 
     /// Tests if the square is in a promotion zone.
-    pub fn in_promotion_zone(self, c: Color) -> bool {
+    pub const fn in_promotion_zone(self, c: Color) -> bool {
         self.relative_rank(c) < 3
     }
 
     /// Converts the instance into the unique number for array indexing purpose.
     #[inline(always)]
-    pub fn index(self) -> usize {
+    pub const fn index(self) -> usize {
         self.inner as usize
     }
 }
 
+impl FromStr for Square {
+    type Err = SfenError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Square::from_sfen(s).ok_or(SfenError::IllegalSquare)
+    }
+}
+
 impl fmt::Display for Square {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
         debug_assert!(
@@ -226,6 +354,14 @@ impl iter::Iterator for SquareIter {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::bitboard::Factory as BBFactory;
+
+    #[test]
+    fn from_str() {
+        assert_eq!(Ok(consts::SQ_5E), "5e".parse());
+        assert_eq!(Err(SfenError::IllegalSquare), "5j".parse::<Square>());
+        assert_eq!(Err(SfenError::IllegalSquare), "".parse::<Square>());
+    }
 
     #[test]
     fn new() {
@@ -242,6 +378,92 @@ mod tests {
         assert_eq!(None, Square::new(10, 10));
     }
 
+    #[test]
+    fn const_fn() {
+        const SQ: Square = match Square::new(2, 6) {
+            Some(sq) => sq,
+            None => panic!(),
+        };
+        const FILE: u8 = SQ.file();
+        const RANK: u8 = SQ.rank();
+        const INDEX: usize = SQ.index();
+        const SHIFTED: Option<Square> = SQ.shift(-1, 1);
+        const FROM_INDEX: Option<Square> = Square::from_index(81);
+
+        assert_eq!(consts::SQ_3G, SQ);
+        assert_eq!(2, FILE);
+        assert_eq!(6, RANK);
+        assert_eq!(24, INDEX);
+        assert_eq!(Some(consts::SQ_2H), SHIFTED);
+        assert_eq!(None, FROM_INDEX);
+    }
+
+    #[test]
+    fn file_rank_iter() {
+        for i in 0..9 {
+            let file = Square::file_iter(i).collect::<Vec<_>>();
+            assert_eq!(9, file.len(), "failed at #{i}");
+            assert!(file.iter().all(|sq| sq.file() == i), "failed at #{i}");
+            assert!(
+                file.iter()
+                    .enumerate()
+                    .all(|(r, sq)| sq.rank() as usize == r),
+                "failed at #{i}"
+            );
+
+            let rank = Square::rank_iter(i).collect::<Vec<_>>();
+            assert_eq!(9, rank.len(), "failed at #{i}");
+            assert!(rank.iter().all(|sq| sq.rank() == i), "failed at #{i}");
+            assert!(
+                rank.iter()
+                    .enumerate()
+                    .all(|(f, sq)| sq.file() as usize == f),
+                "failed at #{i}"
+            );
+        }
+
+        assert_eq!(0, Square::file_iter(9).count());
+        assert_eq!(0, Square::rank_iter(9).count());
+    }
+
+    #[test]
+    fn ord() {
+        for (i, w) in Square::iter().collect::<Vec<_>>().windows(2).enumerate() {
+            assert!(w[0] < w[1], "failed at #{i}");
+            assert_eq!(w[0].index().cmp(&w[1].index()), w[0].cmp(&w[1]));
+        }
+
+        use consts::{SQ_1A, SQ_5E, SQ_9I};
+
+        let mut squares = alloc::collections::BTreeSet::new();
+        squares.insert(SQ_5E);
+        squares.insert(SQ_1A);
+        squares.insert(SQ_9I);
+        squares.insert(SQ_1A);
+        assert_eq!(
+            vec![SQ_1A, SQ_5E, SQ_9I],
+            squares.into_iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn try_new() {
+        for file in 0..9 {
+            for rank in 0..9 {
+                let sq = Square::new(file, rank).unwrap();
+                assert_eq!(Ok(sq), Square::try_new(file, rank));
+                assert_eq!(sq, unsafe { Square::new_unchecked(file, rank) });
+            }
+        }
+
+        assert_eq!(Err(SquareError::FileOutOfRange(9)), Square::try_new(9, 0));
+        assert_eq!(Err(SquareError::RankOutOfRange(9)), Square::try_new(0, 9));
+        assert_eq!(
+            Err(SquareError::FileOutOfRange(10)),
+            Square::try_new(10, 10)
+        );
+    }
+
     #[test]
     fn from_sfen() {
         let ok_cases = [
@@ -341,6 +563,27 @@ mod tests {
         }
     }
 
+    #[test]
+    fn promotion_zone() {
+        let cases = [(Color::Black, [0, 1, 2]), (Color::White, [6, 7, 8])];
+
+        for (i, case) in cases.iter().enumerate() {
+            let squares = Square::promotion_zone(case.0).collect::<Vec<_>>();
+            assert_eq!(27, squares.len(), "failed at #{i}");
+            assert!(
+                squares.iter().all(|sq| case.1.contains(&sq.rank())),
+                "failed at #{i}"
+            );
+            assert_eq!(
+                BBFactory::promote_zone(case.0)
+                    .squares()
+                    .collect::<Vec<_>>(),
+                squares,
+                "failed at #{i}"
+            );
+        }
+    }
+
     #[test]
     fn in_promotion_zone() {
         let cases = [
@@ -1,13 +1,15 @@
-use std::cmp::min;
-use std::time::Duration;
+use core::cmp::max;
+use core::time::Duration;
 
 use crate::Color;
 
 /// Represents various time controls.
 ///
 /// Currently
-/// [Byo-yomi](https://en.wikipedia.org/wiki/Time_control#Byo-yomi) and
-/// [Fischer Clock](https://en.wikipedia.org/wiki/Time_control#Increment_and_delay_methods)
+/// [Byo-yomi](https://en.wikipedia.org/wiki/Time_control#Byo-yomi),
+/// [Fischer Clock](https://en.wikipedia.org/wiki/Time_control#Increment_and_delay_methods),
+/// sudden death and
+/// [Canadian overtime](https://en.wikipedia.org/wiki/Time_control#Canadian_overtime)
 /// are supported.
 ///
 /// # Examples
@@ -19,7 +21,10 @@ use crate::Color;
 /// let mut byoyomi = TimeControl::Byoyomi{
 ///     black_time: Duration::from_secs(10),
 ///     white_time: Duration::from_secs(10),
-///     byoyomi: Duration::from_secs(5)
+///     byoyomi: Duration::from_secs(5),
+///     periods: 1,
+///     black_periods_used: 0,
+///     white_periods_used: 0,
 /// };
 ///
 /// // Black player can use the time up to black_time + byoyomi.
@@ -44,12 +49,41 @@ use crate::Color;
 /// assert_eq!(Duration::from_secs(8), fischer_clock.black_time());
 /// assert_eq!(Duration::from_secs(10), fischer_clock.white_time());
 /// ```
+///
+/// ```
+/// use std::time::Duration;
+/// use shogi::{Color, TimeControl};
+///
+/// let mut canadian = TimeControl::Canadian{
+///     black_time: Duration::from_secs(10),
+///     white_time: Duration::from_secs(10),
+///     period: Duration::from_secs(60),
+///     moves_per_period: 2,
+///     black_moves: 0,
+///     white_moves: 0,
+/// };
+///
+/// // Black player enters overtime and has 1 more move to make within the period.
+/// canadian.consume(Color::Black, Duration::from_secs(20));
+/// assert_eq!(Duration::from_secs(50), canadian.black_time());
+///
+/// // The period resets once all moves in the period are made.
+/// canadian.consume(Color::Black, Duration::from_secs(30));
+/// assert_eq!(Duration::from_secs(60), canadian.black_time());
+/// ```
 #[derive(Debug, Clone, Copy)]
 pub enum TimeControl {
+    /// After the main time runs out, each move needs to be made within `byoyomi`.
+    ///
+    /// A move taking longer than `byoyomi` uses up periods, out of `periods` in total.
+    /// `black_periods_used` and `white_periods_used` hold the number of periods each player has used up.
     Byoyomi {
         black_time: Duration,
         white_time: Duration,
         byoyomi: Duration,
+        periods: u8,
+        black_periods_used: u8,
+        white_periods_used: u8,
     },
     FischerClock {
         black_time: Duration,
@@ -57,6 +91,23 @@ pub enum TimeControl {
         black_inc: Duration,
         white_inc: Duration,
     },
+    SuddenDeath {
+        black_time: Duration,
+        white_time: Duration,
+    },
+    /// After the main time runs out, `moves_per_period` moves need to be made within `period`.
+    ///
+    /// `black_time` and `white_time` hold the time left in the current period during overtime.
+    /// `black_moves` and `white_moves` hold the number of moves left in the current period,
+    /// and are zero while the player is still in the main time.
+    Canadian {
+        black_time: Duration,
+        white_time: Duration,
+        period: Duration,
+        moves_per_period: u32,
+        black_moves: u32,
+        white_moves: u32,
+    },
 }
 
 impl TimeControl {
@@ -65,6 +116,8 @@ impl TimeControl {
         match *self {
             TimeControl::Byoyomi { black_time, .. } => black_time,
             TimeControl::FischerClock { black_time, .. } => black_time,
+            TimeControl::SuddenDeath { black_time, .. } => black_time,
+            TimeControl::Canadian { black_time, .. } => black_time,
         }
     }
 
@@ -73,12 +126,113 @@ impl TimeControl {
         match *self {
             TimeControl::Byoyomi { white_time, .. } => white_time,
             TimeControl::FischerClock { white_time, .. } => white_time,
+            TimeControl::SuddenDeath { white_time, .. } => white_time,
+            TimeControl::Canadian { white_time, .. } => white_time,
+        }
+    }
+
+    /// Returns the current remaining time for the given player.
+    ///
+    /// This is the same as `black_time` or `white_time` depending on the color.
+    pub fn remaining(&self, c: Color) -> Duration {
+        match c {
+            Color::Black => self.black_time(),
+            Color::White => self.white_time(),
+        }
+    }
+
+    /// Checks if the given player has no time left to make a move.
+    ///
+    /// For byo-yomi, the player is expired once the main time is zero and all periods are used up.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use shogi::{Color, TimeControl};
+    ///
+    /// let mut sudden_death = TimeControl::SuddenDeath{
+    ///     black_time: Duration::from_secs(10),
+    ///     white_time: Duration::from_secs(10),
+    /// };
+    ///
+    /// assert!(!sudden_death.consume(Color::Black, Duration::from_secs(15)));
+    /// assert!(sudden_death.is_expired(Color::Black));
+    /// assert!(!sudden_death.is_expired(Color::White));
+    /// ```
+    pub fn is_expired(&self, c: Color) -> bool {
+        if !self.remaining(c).is_zero() {
+            return false;
+        }
+
+        match *self {
+            TimeControl::Byoyomi {
+                byoyomi,
+                periods,
+                black_periods_used,
+                white_periods_used,
+                ..
+            } => {
+                let periods_used = if c == Color::Black {
+                    black_periods_used
+                } else {
+                    white_periods_used
+                };
+
+                byoyomi.is_zero() || periods_used >= periods
+            }
+            TimeControl::FischerClock { .. } | TimeControl::SuddenDeath { .. } => true,
+            TimeControl::Canadian {
+                period,
+                black_moves,
+                white_moves,
+                ..
+            } => {
+                let moves = if c == Color::Black {
+                    black_moves
+                } else {
+                    white_moves
+                };
+
+                // Still in the main time, a new overtime period is available.
+                moves > 0 || period.is_zero()
+            }
         }
     }
 
+    /// Gives the given player additional time.
+    ///
+    /// For byo-yomi, the time is added to the main time rather than the byo-yomi period.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use shogi::{Color, TimeControl};
+    ///
+    /// let mut sudden_death = TimeControl::SuddenDeath{
+    ///     black_time: Duration::from_secs(10),
+    ///     white_time: Duration::from_secs(10),
+    /// };
+    ///
+    /// sudden_death.add_time(Color::White, Duration::from_secs(5));
+    /// assert_eq!(Duration::from_secs(15), sudden_death.white_time());
+    /// ```
+    pub fn add_time(&mut self, c: Color, d: Duration) {
+        *self.time_mut(c) += d;
+    }
+
+    /// Sets the remaining time of the given player.
+    ///
+    /// For byo-yomi, the main time is set and the byo-yomi period is left unchanged.
+    pub fn set_time(&mut self, c: Color, d: Duration) {
+        *self.time_mut(c) = d;
+    }
+
     /// Updates the current remaining time after consuming the given amount of time for the given player.
     ///
     /// Returns false if the given player runs out of time, true otherwise.
+    /// The remaining time of the player is set to zero when running out of time.
     ///
     /// # Examples
     ///
@@ -89,7 +243,10 @@ impl TimeControl {
     /// let mut byoyomi = TimeControl::Byoyomi{
     ///     black_time: Duration::from_secs(10),
     ///     white_time: Duration::from_secs(10),
-    ///     byoyomi: Duration::from_secs(5)
+    ///     byoyomi: Duration::from_secs(5),
+    ///     periods: 1,
+    ///     black_periods_used: 0,
+    ///     white_periods_used: 0,
     /// };
     ///
     /// assert!(byoyomi.consume(Color::Black, Duration::from_secs(15)));
@@ -100,18 +257,36 @@ impl TimeControl {
             TimeControl::Byoyomi {
                 ref mut black_time,
                 ref mut white_time,
-                ref byoyomi,
+                byoyomi,
+                periods,
+                ref mut black_periods_used,
+                ref mut white_periods_used,
             } => {
-                let target_time = if c == Color::Black {
-                    black_time
+                let (target_time, periods_used) = if c == Color::Black {
+                    (black_time, black_periods_used)
                 } else {
-                    white_time
+                    (white_time, white_periods_used)
                 };
 
-                if d > (*target_time + *byoyomi) {
-                    return false;
+                if d > *target_time {
+                    let overtime = d - *target_time;
+                    *target_time = Duration::ZERO;
+
+                    // The number of periods the move spans, including the one it is made in.
+                    let spanned = if byoyomi.is_zero() {
+                        u128::MAX
+                    } else {
+                        overtime.as_nanos().div_ceil(byoyomi.as_nanos())
+                    };
+
+                    if spanned > periods.saturating_sub(*periods_used) as u128 {
+                        *periods_used = periods;
+                        return false;
+                    }
+                    *periods_used += (spanned - 1) as u8;
+                } else {
+                    *target_time -= d;
                 }
-                *target_time -= min(*target_time, d);
             }
             TimeControl::FischerClock {
                 ref mut black_time,
@@ -127,14 +302,100 @@ impl TimeControl {
 
                 *stm_time += *inc_time;
                 if d > *stm_time {
+                    *stm_time = Duration::ZERO;
                     return false;
                 }
                 *stm_time -= d;
             }
+            TimeControl::SuddenDeath {
+                ref mut black_time,
+                ref mut white_time,
+            } => {
+                let stm_time = if c == Color::Black {
+                    black_time
+                } else {
+                    white_time
+                };
+
+                if d > *stm_time {
+                    *stm_time = Duration::ZERO;
+                    return false;
+                }
+                *stm_time -= d;
+            }
+            TimeControl::Canadian {
+                ref mut black_time,
+                ref mut white_time,
+                period,
+                moves_per_period,
+                ref mut black_moves,
+                ref mut white_moves,
+            } => {
+                let (stm_time, stm_moves) = if c == Color::Black {
+                    (black_time, black_moves)
+                } else {
+                    (white_time, white_moves)
+                };
+
+                let mut d = d;
+                if *stm_moves == 0 {
+                    if d <= *stm_time {
+                        *stm_time -= d;
+                        return true;
+                    }
+
+                    // The rest of the time is taken from the first overtime period.
+                    d -= *stm_time;
+                    *stm_time = period;
+                    *stm_moves = max(moves_per_period, 1);
+                }
+
+                if d > *stm_time {
+                    *stm_time = Duration::ZERO;
+                    return false;
+                }
+                *stm_time -= d;
+                *stm_moves -= 1;
+
+                if *stm_moves == 0 {
+                    *stm_time = period;
+                    *stm_moves = max(moves_per_period, 1);
+                }
+            }
         }
 
         true
     }
+
+    fn time_mut(&mut self, c: Color) -> &mut Duration {
+        let (black_time, white_time) = match *self {
+            TimeControl::Byoyomi {
+                ref mut black_time,
+                ref mut white_time,
+                ..
+            } => (black_time, white_time),
+            TimeControl::FischerClock {
+                ref mut black_time,
+                ref mut white_time,
+                ..
+            } => (black_time, white_time),
+            TimeControl::SuddenDeath {
+                ref mut black_time,
+                ref mut white_time,
+            } => (black_time, white_time),
+            TimeControl::Canadian {
+                ref mut black_time,
+                ref mut white_time,
+                ..
+            } => (black_time, white_time),
+        };
+
+        if c == Color::Black {
+            black_time
+        } else {
+            white_time
+        }
+    }
 }
 
 #[cfg(test)]
@@ -158,6 +419,9 @@ mod tests {
                 black_time: Duration::from_millis(case.0),
                 white_time: Duration::from_millis(case.0),
                 byoyomi: Duration::from_millis(case.1),
+                periods: 1,
+                black_periods_used: 0,
+                white_periods_used: 0,
             };
 
             assert!(t.consume(Color::Black, Duration::from_millis(case.2)));
@@ -174,6 +438,9 @@ mod tests {
                 black_time: Duration::from_millis(case.0),
                 white_time: Duration::from_millis(case.0),
                 byoyomi: Duration::from_millis(case.1),
+                periods: 1,
+                black_periods_used: 0,
+                white_periods_used: 0,
             };
 
             assert!(!t.consume(Color::Black, Duration::from_millis(case.2)));
@@ -218,4 +485,344 @@ mod tests {
             assert!(!t.consume(Color::White, Duration::from_secs(case.4)));
         }
     }
+
+    #[test]
+    fn consume_sudden_death() {
+        // black_time, white_time, consume, remaining_black, remaining_white
+        let ok_cases = [(50, 40, 10, 40, 40), (50, 40, 50, 0, 40), (0, 40, 0, 0, 40)];
+
+        // black_time, white_time, consume
+        let ng_cases = [(50, 40, 51), (0, 40, 1)];
+
+        for (i, case) in ok_cases.iter().enumerate() {
+            let mut t = TimeControl::SuddenDeath {
+                black_time: Duration::from_secs(case.0),
+                white_time: Duration::from_secs(case.1),
+            };
+
+            assert!(
+                t.consume(Color::Black, Duration::from_secs(case.2)),
+                "failed at #{i}"
+            );
+            assert_eq!(
+                Duration::from_secs(case.3),
+                t.black_time(),
+                "failed at #{i}"
+            );
+            assert_eq!(
+                Duration::from_secs(case.4),
+                t.white_time(),
+                "failed at #{i}"
+            );
+        }
+
+        for (i, case) in ng_cases.iter().enumerate() {
+            let mut t = TimeControl::SuddenDeath {
+                black_time: Duration::from_secs(case.0),
+                white_time: Duration::from_secs(case.1),
+            };
+
+            assert!(
+                !t.consume(Color::Black, Duration::from_secs(case.2)),
+                "failed at #{i}"
+            );
+        }
+    }
+
+    #[test]
+    fn consume_canadian() {
+        // main_time, period, moves_per_period, consumes, remaining, moves_left
+        type OkCase = (u64, u64, u32, &'static [u64], u64, u32);
+        let ok_cases: &[OkCase] = &[
+            (10, 60, 3, &[5], 5, 0),
+            (10, 60, 3, &[10], 0, 0),
+            (10, 60, 3, &[10, 20], 40, 2),
+            (10, 60, 3, &[15], 55, 2),
+            (10, 60, 3, &[70], 0, 2),
+            (10, 60, 3, &[15, 20, 20], 60, 3),
+            (10, 60, 3, &[15, 20, 25, 60], 0, 2),
+            (10, 60, 1, &[15, 60, 60], 60, 1),
+            (0, 60, 2, &[60, 0], 60, 2),
+        ];
+
+        // main_time, period, moves_per_period, consumes
+        let ng_cases: &[(u64, u64, u32, &[u64])] = &[
+            (10, 60, 3, &[71]),
+            (10, 60, 3, &[15, 50, 6]),
+            (10, 60, 3, &[70, 1]),
+            (0, 60, 2, &[60, 1]),
+        ];
+
+        for (i, case) in ok_cases.iter().enumerate() {
+            let mut t = TimeControl::Canadian {
+                black_time: Duration::from_secs(case.0),
+                white_time: Duration::from_secs(case.0),
+                period: Duration::from_secs(case.1),
+                moves_per_period: case.2,
+                black_moves: 0,
+                white_moves: 0,
+            };
+
+            for &d in case.3 {
+                assert!(
+                    t.consume(Color::Black, Duration::from_secs(d)),
+                    "failed at #{i}"
+                );
+            }
+            assert_eq!(
+                Duration::from_secs(case.4),
+                t.black_time(),
+                "failed at #{i}"
+            );
+            assert_eq!(
+                Duration::from_secs(case.0),
+                t.white_time(),
+                "failed at #{i}"
+            );
+
+            match t {
+                TimeControl::Canadian { black_moves, .. } => {
+                    assert_eq!(case.5, black_moves, "failed at #{i}")
+                }
+                _ => unreachable!(),
+            }
+        }
+
+        for (i, case) in ng_cases.iter().enumerate() {
+            let mut t = TimeControl::Canadian {
+                black_time: Duration::from_secs(case.0),
+                white_time: Duration::from_secs(case.0),
+                period: Duration::from_secs(case.1),
+                moves_per_period: case.2,
+                black_moves: 0,
+                white_moves: 0,
+            };
+
+            let (last, rest) = case.3.split_last().unwrap();
+            for &d in rest {
+                assert!(
+                    t.consume(Color::White, Duration::from_secs(d)),
+                    "failed at #{i}"
+                );
+            }
+            assert!(
+                !t.consume(Color::White, Duration::from_secs(*last)),
+                "failed at #{i}"
+            );
+        }
+    }
+
+    #[test]
+    fn add_time() {
+        let controls = [
+            TimeControl::Byoyomi {
+                black_time: Duration::from_secs(10),
+                white_time: Duration::from_secs(10),
+                byoyomi: Duration::from_secs(5),
+                periods: 1,
+                black_periods_used: 0,
+                white_periods_used: 0,
+            },
+            TimeControl::FischerClock {
+                black_time: Duration::from_secs(10),
+                white_time: Duration::from_secs(10),
+                black_inc: Duration::from_secs(5),
+                white_inc: Duration::from_secs(5),
+            },
+            TimeControl::SuddenDeath {
+                black_time: Duration::from_secs(15),
+                white_time: Duration::from_secs(15),
+            },
+            TimeControl::Canadian {
+                black_time: Duration::from_secs(10),
+                white_time: Duration::from_secs(10),
+                period: Duration::from_secs(5),
+                moves_per_period: 1,
+                black_moves: 0,
+                white_moves: 0,
+            },
+        ];
+
+        for (i, control) in controls.iter().enumerate() {
+            // The player would flag after spending 20 seconds without extra time.
+            let mut t = *control;
+            assert!(
+                !t.consume(Color::Black, Duration::from_secs(20)),
+                "failed at #{i}"
+            );
+
+            let mut t = *control;
+            t.add_time(Color::Black, Duration::from_secs(10));
+            assert_eq!(control.white_time(), t.white_time(), "failed at #{i}");
+            assert!(
+                t.consume(Color::Black, Duration::from_secs(20)),
+                "failed at #{i}"
+            );
+        }
+    }
+
+    #[test]
+    fn set_time() {
+        let mut t = TimeControl::Byoyomi {
+            black_time: Duration::from_secs(10),
+            white_time: Duration::from_secs(10),
+            byoyomi: Duration::from_secs(5),
+            periods: 1,
+            black_periods_used: 0,
+            white_periods_used: 0,
+        };
+
+        t.set_time(Color::White, Duration::from_secs(30));
+        assert_eq!(Duration::from_secs(10), t.black_time());
+        assert_eq!(Duration::from_secs(30), t.white_time());
+
+        t.set_time(Color::Black, Duration::from_secs(0));
+        assert_eq!(Duration::from_secs(0), t.black_time());
+        assert!(t.consume(Color::Black, Duration::from_secs(5)));
+        assert!(!t.consume(Color::Black, Duration::from_secs(6)));
+    }
+
+    #[test]
+    fn is_expired() {
+        let sudden_death = TimeControl::SuddenDeath {
+            black_time: Duration::from_secs(10),
+            white_time: Duration::from_secs(10),
+        };
+        let byoyomi = TimeControl::Byoyomi {
+            black_time: Duration::from_secs(10),
+            white_time: Duration::from_secs(10),
+            byoyomi: Duration::from_secs(5),
+            periods: 1,
+            black_periods_used: 0,
+            white_periods_used: 0,
+        };
+        let canadian = TimeControl::Canadian {
+            black_time: Duration::from_secs(10),
+            white_time: Duration::from_secs(10),
+            period: Duration::from_secs(60),
+            moves_per_period: 3,
+            black_moves: 0,
+            white_moves: 0,
+        };
+
+        // time control, consumes, remaining, expired
+        let cases: &[(TimeControl, &[u64], u64, bool)] = &[
+            (sudden_death, &[5], 5, false),
+            (sudden_death, &[10], 0, true),
+            (sudden_death, &[11], 0, true),
+            (byoyomi, &[10], 0, false),
+            (byoyomi, &[15], 0, false),
+            (byoyomi, &[16], 0, true),
+            (canadian, &[10], 0, false),
+            (canadian, &[70], 0, true),
+            (canadian, &[71], 0, true),
+            (canadian, &[15, 55], 0, true),
+            (canadian, &[15, 25, 30], 60, false),
+        ];
+
+        for (i, case) in cases.iter().enumerate() {
+            let mut t = case.0;
+            for &d in case.1 {
+                t.consume(Color::White, Duration::from_secs(d));
+            }
+
+            assert_eq!(
+                Duration::from_secs(case.2),
+                t.remaining(Color::White),
+                "failed at #{i}"
+            );
+            assert_eq!(case.3, t.is_expired(Color::White), "failed at #{i}");
+            assert_eq!(
+                Duration::from_secs(10),
+                t.remaining(Color::Black),
+                "failed at #{i}"
+            );
+            assert!(!t.is_expired(Color::Black), "failed at #{i}");
+        }
+    }
+
+    #[test]
+    fn consume_byoyomi_periods() {
+        // main_time, byoyomi, periods, consumes, remaining, periods_used
+        type OkCase = (u64, u64, u8, &'static [u64], u64, u8);
+        let ok_cases: &[OkCase] = &[
+            (10, 5, 3, &[15], 0, 0),
+            (10, 5, 3, &[15, 5, 5], 0, 0),
+            (10, 5, 3, &[16], 0, 1),
+            (10, 5, 3, &[20], 0, 1),
+            (10, 5, 3, &[21], 0, 2),
+            (10, 5, 3, &[25], 0, 2),
+            (0, 5, 3, &[11, 5], 0, 2),
+            (0, 5, 3, &[6, 6], 0, 2),
+        ];
+
+        // main_time, byoyomi, periods, consumes
+        let ng_cases: &[(u64, u64, u8, &[u64])] = &[
+            (10, 5, 3, &[26]),
+            (10, 5, 3, &[21, 6]),
+            (0, 5, 3, &[6, 6, 6]),
+            (10, 5, 0, &[11]),
+            (10, 0, 3, &[11]),
+        ];
+
+        for (i, case) in ok_cases.iter().enumerate() {
+            let mut t = TimeControl::Byoyomi {
+                black_time: Duration::from_secs(case.0),
+                white_time: Duration::from_secs(case.0),
+                byoyomi: Duration::from_secs(case.1),
+                periods: case.2,
+                black_periods_used: 0,
+                white_periods_used: 0,
+            };
+
+            for &d in case.3 {
+                assert!(
+                    t.consume(Color::Black, Duration::from_secs(d)),
+                    "failed at #{i}"
+                );
+            }
+            assert_eq!(
+                Duration::from_secs(case.4),
+                t.black_time(),
+                "failed at #{i}"
+            );
+            assert!(!t.is_expired(Color::Black), "failed at #{i}");
+
+            match t {
+                TimeControl::Byoyomi {
+                    black_periods_used,
+                    white_periods_used,
+                    ..
+                } => {
+                    assert_eq!(case.5, black_periods_used, "failed at #{i}");
+                    assert_eq!(0, white_periods_used, "failed at #{i}");
+                }
+                _ => unreachable!(),
+            }
+        }
+
+        for (i, case) in ng_cases.iter().enumerate() {
+            let mut t = TimeControl::Byoyomi {
+                black_time: Duration::from_secs(case.0),
+                white_time: Duration::from_secs(case.0),
+                byoyomi: Duration::from_secs(case.1),
+                periods: case.2,
+                black_periods_used: 0,
+                white_periods_used: 0,
+            };
+
+            let (last, rest) = case.3.split_last().unwrap();
+            for &d in rest {
+                assert!(
+                    t.consume(Color::White, Duration::from_secs(d)),
+                    "failed at #{i}"
+                );
+            }
+            assert!(
+                !t.consume(Color::White, Duration::from_secs(*last)),
+                "failed at #{i}"
+            );
+            assert!(t.is_expired(Color::White), "failed at #{i}");
+        }
+    }
 }